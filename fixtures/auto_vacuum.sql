@@ -0,0 +1,10 @@
+PRAGMA page_size = 512;
+PRAGMA auto_vacuum = FULL;
+
+CREATE TABLE items (value TEXT);
+CREATE TABLE notes (body TEXT);
+
+WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+INSERT INTO items SELECT printf('item %d', i) FROM n;
+
+INSERT INTO notes VALUES (printf('%.1200c', 'x'));
@@ -0,0 +1,10 @@
+CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT);
+CREATE TABLE posts (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT);
+CREATE TABLE tags (name TEXT);
+
+INSERT INTO users (name) VALUES ('alice');
+
+INSERT INTO posts (title) VALUES ('one'), ('two'), ('three'), ('four'), ('five');
+DELETE FROM posts WHERE id > 3;
+
+INSERT INTO tags (name) VALUES ('rust');
@@ -0,0 +1,4 @@
+-- The `UNIQUE` constraint is backed by an automatically created index, which has no SQL.
+CREATE TABLE t (a TEXT UNIQUE, b INTEGER);
+
+INSERT INTO t (a, b) VALUES ('one', 1), ('two', 2);
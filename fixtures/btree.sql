@@ -0,0 +1,7 @@
+PRAGMA page_size = 512;
+
+CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, value INTEGER);
+CREATE INDEX items_name ON items (name);
+
+WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 1000)
+INSERT INTO items SELECT i, 'item ' || i, i * 10 FROM n;
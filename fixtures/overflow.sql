@@ -0,0 +1,13 @@
+PRAGMA page_size = 512;
+
+CREATE TABLE documents (id INTEGER PRIMARY KEY, body TEXT);
+
+-- Small enough to be stored entirely on the leaf page.
+INSERT INTO documents (body) VALUES ('short');
+
+-- '00010002...1250', 5000 bytes which spills onto several overflow pages.
+WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 1250)
+INSERT INTO documents (body) SELECT group_concat(printf('%04d', i), '') FROM n;
+
+-- Just large enough to spill onto a single overflow page.
+INSERT INTO documents (body) VALUES (printf('%.600c', 'x'));
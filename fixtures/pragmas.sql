@@ -0,0 +1,10 @@
+PRAGMA auto_vacuum = INCREMENTAL;
+PRAGMA application_id = 1234567;
+PRAGMA user_version = 42;
+
+CREATE TABLE items (value TEXT);
+
+WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+INSERT INTO items SELECT printf('%.500c', 'x') FROM n;
+
+DELETE FROM items;
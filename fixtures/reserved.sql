@@ -0,0 +1,7 @@
+PRAGMA page_size = 512;
+-- Reserve 32 bytes at the end of each page, as an encryption extension would.
+.filectrl reserve_bytes 32
+
+CREATE TABLE items (id INTEGER PRIMARY KEY, value TEXT);
+
+INSERT INTO items VALUES (1, 'one'), (2, printf('%.1000c', 'x')), (3, 'three');
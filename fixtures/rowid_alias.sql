@@ -0,0 +1,5 @@
+CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT);
+CREATE TABLE ranked (id INTEGER PRIMARY KEY DESC, name TEXT);
+
+INSERT INTO items (id, name) VALUES (1, 'one'), (5, 'five'), (9, 'nine');
+INSERT INTO ranked (id, name) VALUES (1, 'one'), (5, 'five'), (9, 'nine');
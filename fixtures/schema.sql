@@ -0,0 +1,10 @@
+CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER, title TEXT);
+
+CREATE INDEX posts_user_id ON posts (user_id);
+
+CREATE VIEW user_posts AS SELECT users.name, posts.title FROM users JOIN posts ON posts.user_id = users.id;
+
+CREATE TRIGGER delete_user_posts AFTER DELETE ON users BEGIN
+    DELETE FROM posts WHERE user_id = old.id;
+END;
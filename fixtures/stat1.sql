@@ -0,0 +1,10 @@
+CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, team TEXT);
+CREATE INDEX users_team_name ON users (team, name);
+CREATE TABLE logs (message TEXT);
+
+WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < 100)
+INSERT INTO users (name, team) SELECT 'user' || (n % 50), 'team' || (n % 4) FROM seq;
+
+INSERT INTO logs VALUES ('one'), ('two'), ('three');
+
+ANALYZE;
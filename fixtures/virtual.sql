@@ -0,0 +1,7 @@
+CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+
+-- Views and virtual tables have no B-Tree, so their root page is 0.
+CREATE VIEW user_names AS SELECT name FROM users;
+CREATE VIRTUAL TABLE page_stats USING dbstat;
+
+INSERT INTO users (name) VALUES ('alice'), ('bob');
@@ -0,0 +1,5 @@
+CREATE TABLE settings (name TEXT PRIMARY KEY, value TEXT) WITHOUT ROWID;
+CREATE TABLE pairs (a INTEGER NOT NULL, b INTEGER, PRIMARY KEY (a, b)) WITHOUT ROWID;
+
+INSERT INTO settings (name, value) VALUES ('theme', 'dark'), ('language', 'en');
+INSERT INTO pairs (a, b) VALUES (1, 2), (3, 4);
@@ -4,11 +4,14 @@
 //! identifiers). At the conclusion of this stage, a [`TokenBuffer`] will be produced which can be
 //! traversed for higher level parsing.
 
-use std::iter::{self, Peekable};
+use std::{
+    cell::Cell,
+    iter::{self, Peekable},
+};
 
 use derive_more::Deref;
 
-use crate::parse::FullBufferParser;
+use crate::parse::{FullBufferParser, ParseError, ParseErrorKind, Token};
 
 /// A low level token, which is directly constructed from at least one character.
 pub trait BufferToken: Clone + Sized {
@@ -37,53 +40,168 @@ impl<T: Sized> Outcome<T> {
     }
 }
 
+/// Location of a token within the source, as a range of byte offsets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    /// Offset of the first byte of the token.
+    pub start: usize,
+    /// Offset after the last byte of the token.
+    pub end: usize,
+}
+
+/// Whitespace and comments which were skipped whilst tokenising, as they appeared in the source.
+#[derive(Clone, Copy, Debug, Deref, PartialEq, Eq)]
+pub struct Trivia<'a>(&'a str);
+
 /// Buffered stream of tokens.
 #[derive(Deref)]
 pub struct TokenBuffer<BaseToken> {
     /// Underlying buffer containing all tokens.
+    #[deref]
     buffer: Box<[BaseToken]>,
+    /// Location of each token in the source.
+    spans: Box<[Span]>,
+    /// Source that was tokenised, if it was retained with [`Self::new_preserving`].
+    source: Option<Box<str>>,
 }
 
 impl<BaseToken> TokenBuffer<BaseToken> {
     /// Tokenise the source, and produce a new [`TokenBuffer`].
-    pub fn new(source: &str) -> Result<Self, String>
+    pub fn new(source: &str) -> Result<Self, ParseError>
     where
         BaseToken: BufferToken,
     {
-        let mut chars = source.chars().peekable();
+        // Count the bytes pulled from the source, so the position of each token can be tracked.
+        let consumed = Cell::new(0);
+        let mut chars = source
+            .chars()
+            .inspect(|c| consumed.set(consumed.get() + c.len_utf8()))
+            .peekable();
+
+        // Current position within the source, excluding any peeked character.
+        let position = |chars: &mut Peekable<_>| {
+            let peeked = chars.peek().map_or(0, |c: &char| c.len_utf8());
+            consumed.get() - peeked
+        };
 
-        Ok(Self::new_with_tokens(
-            iter::from_fn(move || {
-                let c = chars.next()?;
+        let (tokens, spans) = iter::from_fn(move || {
+            let start = position(&mut chars);
+            let c = chars.next()?;
 
-                match BaseToken::from_char(c, &mut chars) {
-                    Outcome::Token(token) => Some(Some(Ok(token))),
-                    Outcome::Skip => Some(None),
-                    Outcome::Unexpected => Some(Some(Err(format!("unexpected character: {c}")))),
-                }
-            })
-            .flatten()
-            .collect::<Result<Vec<_>, _>>()?,
-        ))
+            let outcome = BaseToken::from_char(c, &mut chars);
+            let span = Span {
+                start,
+                end: position(&mut chars),
+            };
+
+            match outcome {
+                Outcome::Token(token) => Some(Some(Ok((token, span)))),
+                Outcome::Skip => Some(None),
+                Outcome::Unexpected => Some(Some(Err(ParseError::new(
+                    ParseErrorKind::UnexpectedCharacter(c),
+                    Span {
+                        start,
+                        end: start + c.len_utf8(),
+                    },
+                )))),
+            }
+        })
+        .flatten()
+        .collect::<Result<(Vec<_>, Vec<_>), _>>()?;
+
+        Ok(Self {
+            buffer: tokens.into_boxed_slice(),
+            spans: spans.into_boxed_slice(),
+            source: None,
+        })
+    }
+
+    /// Tokenise the source like [`Self::new`], but retain the source so that the [`Trivia`]
+    /// before each token is available, and the tokens can be re-emitted with [`Self::emit`].
+    pub fn new_preserving(source: &str) -> Result<Self, ParseError>
+    where
+        BaseToken: BufferToken,
+    {
+        Ok(Self {
+            source: Some(source.into()),
+            ..Self::new(source)?
+        })
     }
 
-    /// Create a new buffer with the provided tokens.
-    pub(crate) fn new_with_tokens(tokens: Vec<BaseToken>) -> Self {
+    /// Produce the [`Trivia`] preceding the token at `index`. An `index` equal to the number of
+    /// tokens will produce any trivia following the last token. Will be `None` if the buffer wasn't
+    /// created with [`Self::new_preserving`], or if `index` is out of range.
+    pub fn trivia(&self, index: usize) -> Option<Trivia<'_>> {
+        let source = self.source.as_deref()?;
+
+        let start = match index.checked_sub(1) {
+            Some(previous) => self.spans.get(previous)?.end,
+            None => 0,
+        };
+        let end = match self.spans.get(index) {
+            Some(span) => span.start,
+            None if index == self.spans.len() => source.len(),
+            None => return None,
+        };
+
+        Some(Trivia(&source[start..end]))
+    }
+
+    /// Re-emit each token as it appeared in the source, with the [`Trivia`] preceding it. Will be
+    /// `None` if the buffer wasn't created with [`Self::new_preserving`].
+    pub fn emit(&self) -> Option<String> {
+        let source = self.source.as_deref()?;
+
+        let mut emitted = String::with_capacity(source.len());
+        for (index, span) in self.spans.iter().enumerate() {
+            emitted.push_str(&self.trivia(index)?);
+            emitted.push_str(&source[span.start..span.end]);
+        }
+        emitted.push_str(&self.trivia(self.spans.len())?);
+
+        Some(emitted)
+    }
+
+    /// Create a new buffer with the provided tokens, such as those produced by a separate lexer.
+    /// As there's no source, every token will have an empty span.
+    ///
+    /// ```
+    /// use lib_parse::prelude::*;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// enum Word {
+    ///     Hello,
+    ///     World,
+    /// }
+    ///
+    /// let buffer = TokenBuffer::from_tokens(vec![Word::Hello, Word::World]);
+    /// let parser = buffer.parser();
+    ///
+    /// assert_eq!(parser.parse::<Word>().unwrap(), Word::Hello);
+    /// assert_eq!(parser.parse::<Word>().unwrap(), Word::World);
+    /// assert!(parser.is_empty());
+    /// ```
+    pub fn from_tokens(tokens: Vec<BaseToken>) -> Self {
         Self {
+            spans: vec![Span::default(); tokens.len()].into_boxed_slice(),
             buffer: tokens.into_boxed_slice(),
+            source: None,
         }
     }
 
     /// Create an empty [`TokenBuffer`].
     pub fn empty() -> Self {
-        Self {
-            buffer: vec![].into_boxed_slice(),
-        }
+        Self::from_tokens(Vec::new())
+    }
+
+    /// Location of each token in the source.
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
     }
 
     /// Create a new cursor into this buffer.
-    pub fn cursor(&self) -> Cursor<BaseToken> {
-        Cursor::new(self)
+    pub fn cursor(&self) -> Cursor<'_, BaseToken> {
+        Cursor::new(&self.buffer, &self.spans)
     }
 
     /// Create a new stream to operate on this token buffer.
@@ -97,6 +215,8 @@ impl<BaseToken> TokenBuffer<BaseToken> {
 pub struct Cursor<'b, BaseToken> {
     /// Buffer that this cursor refers to.
     buffer: &'b [BaseToken],
+    /// Span of each token in the buffer.
+    spans: &'b [Span],
     /// Next offset into the buffer.
     offset: usize,
 }
@@ -108,9 +228,15 @@ impl<'b, BaseToken> Clone for Cursor<'b, BaseToken> {
 impl<'b, BaseToken> Copy for Cursor<'b, BaseToken> {}
 
 impl<'b, BaseToken> Cursor<'b, BaseToken> {
-    /// Create a new cursor on the provided buffer.
-    pub fn new(buffer: &'b [BaseToken]) -> Self {
-        Self { buffer, offset: 0 }
+    /// Create a new cursor on the provided buffer, where each token has a corresponding span.
+    pub fn new(buffer: &'b [BaseToken], spans: &'b [Span]) -> Self {
+        assert_eq!(buffer.len(), spans.len(), "each token must have a span");
+
+        Self {
+            buffer,
+            spans,
+            offset: 0,
+        }
     }
 
     /// Produce the token that the cursor is currently pointed at.
@@ -127,12 +253,51 @@ impl<'b, BaseToken> Cursor<'b, BaseToken> {
     /// Split this cursor into two cursors, one which will advance until `offset` (exclusive), and
     /// another which will start from `offset` and advance till the end of the buffer.
     pub(crate) fn split_cursor(self, offset: usize) -> (Self, Self) {
+        let split = self.offset + offset;
+
         (
-            Self::new(&self.buffer[self.offset..self.offset + offset]),
-            Self::new(&self.buffer[self.offset + offset..]),
+            Self::new(
+                &self.buffer[self.offset..split],
+                &self.spans[self.offset..split],
+            ),
+            Self::new(&self.buffer[split..], &self.spans[split..]),
         )
     }
 
+    /// Split this cursor at the first token matching `T`. The first cursor will advance until the
+    /// matching token (exclusive), and the second will start from the matching token. If no token
+    /// matches, `None` is produced.
+    pub fn split_at_token<T: Token<BaseToken>>(self) -> Option<(Self, Self)> {
+        let offset = (0..self.remaining()).find(|&n| {
+            T::peek(Self {
+                offset: self.offset + n,
+                ..self
+            })
+        })?;
+
+        Some(self.split_cursor(offset))
+    }
+
+    /// Span of the token the cursor is currently pointed at. At the end of the buffer, this will
+    /// be an empty span following the last token.
+    pub fn span(&self) -> Option<Span> {
+        self.spans.get(self.offset).copied().or_else(|| {
+            let end = self.spans.last()?.end;
+            Some(Span { start: end, end })
+        })
+    }
+
+    /// Produce the token `n` positions ahead of the cursor, without advancing. A value of `0` will
+    /// produce the token that the cursor is currently pointed at.
+    pub fn peek_n(&self, n: usize) -> Option<&'b BaseToken> {
+        self.buffer.get(self.offset.checked_add(n)?)
+    }
+
+    /// Number of tokens remaining before the end of the buffer.
+    pub fn remaining(&self) -> usize {
+        self.buffer.len().saturating_sub(self.offset)
+    }
+
     /// Determine if the cursor is at the end of the buffer.
     pub fn eof(&self) -> bool {
         self.offset >= self.buffer.len()
@@ -222,7 +387,106 @@ mod test {
                 panic!("expected `Err`, found `Ok`");
             };
 
-            assert_eq!(e, format!("unexpected character: {c}"));
+            assert_eq!(e.kind, ParseErrorKind::UnexpectedCharacter(c));
+            assert_eq!(e.to_string(), format!("unexpected character: {c}"));
+        }
+    }
+
+    mod spans {
+        use super::*;
+
+        use crate::common::token::CommonToken;
+
+        /// Produce the source text of each token.
+        fn token_text(source: &str) -> Vec<&str> {
+            TokenBuffer::<CommonToken>::new(source)
+                .unwrap()
+                .spans()
+                .iter()
+                .map(|span| &source[span.start..span.end])
+                .collect()
+        }
+
+        #[rstest]
+        #[case("", &[])]
+        #[case("abc", &["abc"])]
+        #[case("  select  a,b ;", &["select", "a", ",", "b", ";"])]
+        #[case("a -- comment\n b", &["a", "b"])]
+        #[case("\"é\"  b", &["\"é\"", "b"])]
+        #[case("[x y](1.5)", &["[x y]", "(", "1.5", ")"])]
+        #[case("a<=1 b<>c d==e f>g", &["a", "<=", "1", "b", "<>", "c", "d", "==", "e", "f", ">", "g"])]
+        fn token_spans(#[case] source: &str, #[case] expected: &[&str]) {
+            assert_eq!(token_text(source), expected);
+        }
+
+        #[test]
+        fn unexpected_character() {
+            let e = TokenBuffer::<CommonToken>::new("\"é\" ü").err().unwrap();
+
+            assert_eq!(e.span, Some(Span { start: 5, end: 7 }));
+        }
+
+        #[test]
+        fn cursor_span() {
+            let buffer = TokenBuffer::<CommonToken>::new("a  bc").unwrap();
+            let cursor = buffer.cursor();
+
+            assert_eq!(cursor.span(), Some(Span { start: 0, end: 1 }));
+            let cursor = cursor.next_cursor();
+            assert_eq!(cursor.span(), Some(Span { start: 3, end: 5 }));
+            // End of the buffer is located after the last token.
+            let cursor = cursor.next_cursor();
+            assert_eq!(cursor.span(), Some(Span { start: 5, end: 5 }));
+
+            assert_eq!(TokenBuffer::<CommonToken>::empty().cursor().span(), None);
+        }
+    }
+
+    mod preserving {
+        use super::*;
+
+        use crate::common::token::CommonToken;
+
+        #[rstest]
+        #[case("")]
+        #[case("   ")]
+        #[case("select * from t;")]
+        #[case(
+            "  SELECT a,\n       b  -- trailing comment\n  FROM   t\n /* block\n comment */ WHERE a <> 'x''y';\n"
+        )]
+        #[case("select [my col], \"é\" from t where a==1.50e3")]
+        fn round_trip(#[case] source: &str) {
+            let buffer = TokenBuffer::<CommonToken>::new_preserving(source).unwrap();
+
+            assert_eq!(buffer.emit().unwrap(), source);
+        }
+
+        #[test]
+        fn same_tokens() {
+            let source = "select a, -- comment\n b from t;";
+
+            assert_eq!(
+                *TokenBuffer::<CommonToken>::new_preserving(source).unwrap(),
+                *TokenBuffer::<CommonToken>::new(source).unwrap()
+            );
+        }
+
+        #[test]
+        fn trivia() {
+            let buffer = TokenBuffer::<CommonToken>::new_preserving(" a /* c */ b\n").unwrap();
+
+            assert_eq!(buffer.trivia(0).as_deref(), Some(&" "));
+            assert_eq!(buffer.trivia(1).as_deref(), Some(&" /* c */ "));
+            assert_eq!(buffer.trivia(2).as_deref(), Some(&"\n"));
+            assert_eq!(buffer.trivia(3), None);
+        }
+
+        #[test]
+        fn not_preserved() {
+            let buffer = TokenBuffer::<CommonToken>::new(" a ").unwrap();
+
+            assert_eq!(buffer.trivia(0), None);
+            assert_eq!(buffer.emit(), None);
         }
     }
 
@@ -239,6 +503,7 @@ mod test {
             let buffer = TokenBuffer::<A>::new(source).unwrap();
             let cursor = Cursor {
                 buffer: &buffer,
+                spans: &buffer.spans,
                 offset,
             };
 
@@ -253,6 +518,52 @@ mod test {
             assert_eq!(cursor.eof(), expected);
         }
 
+        #[rstest]
+        #[case("abc", 0, 0, Some('a'))]
+        #[case("abc", 0, 2, Some('c'))]
+        #[case("abc", 0, 3, None)]
+        #[case("abc", 1, 0, Some('b'))]
+        #[case("abc", 1, 1, Some('c'))]
+        #[case("abc", 3, 0, None)]
+        #[case("", 0, 0, None)]
+        #[case("abc", 1, usize::MAX, None)]
+        fn peek_n(
+            #[case] source: &str,
+            #[case] offset: usize,
+            #[case] n: usize,
+            #[case] expected: Option<char>,
+        ) {
+            let buffer = TokenBuffer::from_tokens(source.chars().map(CharToken).collect());
+            let cursor = Cursor {
+                buffer: &buffer,
+                spans: &buffer.spans,
+                offset,
+            };
+
+            assert_eq!(cursor.peek_n(n).map(|token| token.0), expected);
+            // Peeking doesn't advance the cursor.
+            assert_eq!(cursor.offset, offset);
+        }
+
+        #[rstest]
+        #[case("", 0, 0)]
+        #[case("a", 0, 1)]
+        #[case("aaaaa", 0, 5)]
+        #[case("aaaaa", 3, 2)]
+        #[case("aaaaa", 5, 0)]
+        #[case("aaaaa", 6, 0)]
+        fn remaining(#[case] source: &str, #[case] offset: usize, #[case] expected: usize) {
+            let buffer = TokenBuffer::<A>::new(source).unwrap();
+            let cursor = Cursor {
+                buffer: &buffer,
+                spans: &buffer.spans,
+                offset,
+            };
+
+            assert_eq!(cursor.remaining(), expected);
+            assert_eq!(cursor.remaining() == 0, cursor.eof());
+        }
+
         mod token {
             use super::*;
 
@@ -283,6 +594,77 @@ mod test {
     #[derive(Clone, Debug, From)]
     struct CharToken(char);
 
+    /// Token which matches a [`CharToken`] of `C`.
+    struct Matches<const C: char>;
+    impl<const C: char> Token<CharToken> for Matches<C> {
+        fn peek(cursor: Cursor<'_, CharToken>) -> bool {
+            cursor.peek_n(0).is_some_and(|token| token.0 == C)
+        }
+
+        fn display() -> &'static str {
+            "char"
+        }
+    }
+
+    /// Collect the remaining tokens of a cursor.
+    fn chars(cursor: Cursor<'_, CharToken>) -> String {
+        (0..cursor.remaining())
+            .filter_map(|n| cursor.peek_n(n))
+            .map(|token| token.0)
+            .collect()
+    }
+
+    mod split_at_token {
+        use super::*;
+
+        #[rstest]
+        #[case("a,b", 0, "a", ",b")]
+        #[case("ab,cd,ef", 0, "ab", ",cd,ef")]
+        #[case(",a", 0, "", ",a")]
+        #[case("a,", 0, "a", ",")]
+        #[case("a,b,c", 2, "b", ",c")]
+        fn first_comma(
+            #[case] source: &str,
+            #[case] start_offset: usize,
+            #[case] before: &str,
+            #[case] after: &str,
+        ) {
+            let buffer = TokenBuffer::from_tokens(source.chars().map(CharToken).collect());
+            let cursor = Cursor {
+                buffer: &buffer,
+                spans: &buffer.spans,
+                offset: start_offset,
+            };
+
+            let (cursor_a, cursor_b) = cursor.split_at_token::<Matches<','>>().unwrap();
+
+            assert_eq!(chars(cursor_a), before);
+            assert_eq!(chars(cursor_b), after);
+        }
+
+        #[rstest]
+        #[case("")]
+        #[case("a")]
+        #[case("abc")]
+        fn absent(#[case] source: &str) {
+            let buffer = TokenBuffer::from_tokens(source.chars().map(CharToken).collect());
+
+            assert!(buffer.cursor().split_at_token::<Matches<','>>().is_none());
+        }
+
+        #[test]
+        fn past_offset() {
+            let buffer = TokenBuffer::from_tokens("a,b".chars().map(CharToken).collect());
+            let cursor = Cursor {
+                buffer: &buffer,
+                spans: &buffer.spans,
+                offset: 2,
+            };
+
+            assert!(cursor.split_at_token::<Matches<','>>().is_none());
+        }
+    }
+
     #[rstest]
     #[case(vec!['a'.into(), 'b'.into()], 0, 1, Some('a'), Some('b'))]
     #[case(vec!['a'.into(), 'b'.into()], 1, 1, Some('b'), None)]
@@ -294,9 +676,10 @@ mod test {
         #[case] first_expected: Option<char>,
         #[case] second_expected: Option<char>,
     ) {
-        let buffer = TokenBuffer::new_with_tokens(tokens);
+        let buffer = TokenBuffer::from_tokens(tokens);
         let cursor = Cursor {
             buffer: &buffer,
+            spans: &buffer.spans,
             offset: start_offset,
         };
 
@@ -1,68 +1,78 @@
-use crate::{BufferParser, Cursor, Parse, Token, TokenRepr, parse::Delimiter};
+use crate::{BufferParser, Cursor, Parse, ParseError, Token, TokenRepr, parse::Delimiter};
 
 use super::token::{CommonToken, Punct};
 
-#[derive(Clone, Copy, Debug)]
-pub struct Parenthesis;
-impl Delimiter<CommonToken> for Parenthesis {
-    type Left = LeftParenthesis;
-    type Right = RightParenthesis;
+/// Declare a [`Delimiter`], alongside the tokens for its opening and closing [`Punct`].
+macro_rules! delimiter {
+    (
+        $(#[$meta:meta])*
+        $delimiter:ident,
+        ($left:ident, $left_punct:ident, $left_repr:literal),
+        ($right:ident, $right_punct:ident, $right_repr:literal)
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug)]
+        pub struct $delimiter;
+        impl Delimiter<CommonToken> for $delimiter {
+            type Left = $left;
+            type Right = $right;
 
-    fn new(_left: Self::Left, _right: Self::Right) -> Self {
-        Self
-    }
-}
-
-pub struct LeftParenthesis;
-impl Parse<CommonToken> for LeftParenthesis {
-    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, String> {
-        match parser.parse()? {
-            Punct::LeftSmooth => Ok(LeftParenthesis),
-            _ => Err("expected `(`".into()),
+            fn new(_left: Self::Left, _right: Self::Right) -> Self {
+                Self
+            }
         }
-    }
-}
-impl Token<CommonToken> for LeftParenthesis {
-    fn peek(cursor: Cursor<'_, CommonToken>) -> bool {
-        let Some((token, _)) = cursor.token() else {
-            return false;
-        };
 
-        let Some(punct) = Punct::from_base(token) else {
-            return false;
-        };
+        delimiter!(@token $left, $left_punct, $left_repr);
+        delimiter!(@token $right, $right_punct, $right_repr);
+    };
 
-        matches!(punct, Punct::LeftSmooth)
-    }
+    (@token $token:ident, $punct:ident, $repr:literal) => {
+        pub struct $token;
+        impl Parse<CommonToken> for $token {
+            fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+                match parser.parse()? {
+                    Punct::$punct => Ok($token),
+                    punct => Err(ParseError::expected(concat!("`", $repr, "`"), punct)),
+                }
+            }
+        }
+        impl Token<CommonToken> for $token {
+            fn peek(cursor: Cursor<'_, CommonToken>) -> bool {
+                let Some((token, _)) = cursor.token() else {
+                    return false;
+                };
 
-    fn display() -> &'static str {
-        "("
-    }
-}
+                let Some(punct) = Punct::from_base(token) else {
+                    return false;
+                };
+
+                matches!(punct, Punct::$punct)
+            }
 
-pub struct RightParenthesis;
-impl Parse<CommonToken> for RightParenthesis {
-    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, String> {
-        match parser.parse()? {
-            Punct::RightSmooth => Ok(RightParenthesis),
-            _ => Err("expected `)`".into()),
+            fn display() -> &'static str {
+                $repr
+            }
         }
-    }
+    };
 }
-impl Token<CommonToken> for RightParenthesis {
-    fn peek(cursor: Cursor<'_, CommonToken>) -> bool {
-        let Some((token, _)) = cursor.token() else {
-            return false;
-        };
 
-        let Some(punct) = Punct::from_base(token) else {
-            return false;
-        };
+delimiter!(
+    Parenthesis,
+    (LeftParenthesis, LeftSmooth, "("),
+    (RightParenthesis, RightSmooth, ")")
+);
 
-        matches!(punct, Punct::RightSmooth)
-    }
+delimiter!(
+    /// Square brackets. `[` begins a quoted identifier (`[my col]`) when lexing SQL with
+    /// [`CommonToken`], so the SQL lexer never produces these tokens. Bracket groups come from
+    /// token streams of other grammars, built with `TokenBuffer::from_tokens`.
+    Bracket,
+    (LeftBracket, LeftSquare, "["),
+    (RightBracket, RightSquare, "]")
+);
 
-    fn display() -> &'static str {
-        ")"
-    }
-}
+delimiter!(
+    Brace,
+    (LeftBrace, LeftCurly, "{"),
+    (RightBrace, RightCurly, "}")
+);
@@ -4,31 +4,70 @@ use derive_more::{Deref, From};
 
 use crate::{
     buffer::{BufferToken, Cursor, Outcome},
-    parse::{BufferParser, Parse, Token, token::TokenRepr},
+    parse::{
+        BufferParser, Parse, ParseError, Token,
+        token::{MatchesToken, TokenRepr},
+    },
 };
 
 /// An identifier. Can begin with any letter or an underscore, and can contain any letter, number,
-/// or underscore.
+/// or underscore. Alternatively it may be quoted with `"`, `[ ]`, or `` ` ``, in which case it can
+/// contain any character.
 #[derive(Clone, Debug, Deref, PartialEq)]
-pub struct Ident(String);
+pub struct Ident {
+    #[deref]
+    name: String,
+    /// Whether the identifier was quoted, in which case it will never match a keyword.
+    quoted: bool,
+}
 
 impl Ident {
     fn new(ident: impl ToString) -> Self {
-        Self(ident.to_string())
+        Self {
+            name: ident.to_string(),
+            quoted: false,
+        }
+    }
+
+    fn new_quoted(ident: impl ToString) -> Self {
+        Self {
+            name: ident.to_string(),
+            quoted: true,
+        }
+    }
+
+    /// Whether the identifier was quoted in the source.
+    pub fn is_quoted(&self) -> bool {
+        self.quoted
+    }
+
+    /// Compare against a string ignoring ASCII case, as identifiers and keywords are
+    /// case-insensitive in SQL.
+    pub fn eq_ignore_case(&self, other: &str) -> bool {
+        self.name.eq_ignore_ascii_case(other)
     }
 }
 
 impl<S: ?Sized + AsRef<str>> PartialEq<S> for Ident {
+    /// Compare against a string exactly, consistent with comparing two identifiers. See
+    /// [`Ident::eq_ignore_case`] to compare as SQL does.
     fn eq(&self, other: &S) -> bool {
-        self.0 == other.as_ref()
+        self.name == other.as_ref()
+    }
+}
+
+impl MatchesToken for Ident {
+    /// Quoted identifiers never match, as quoting allows keywords to be used as identifiers.
+    fn matches_token(&self, token: &str) -> bool {
+        !self.quoted && self.eq_ignore_case(token)
     }
 }
 
 impl Parse<CommonToken> for Ident {
-    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, String> {
+    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
         match parser.parse()? {
             CommonToken::Ident(ident) => Ok(ident),
-            _ => Err("unexpected token (expected ident)".into()),
+            token => Err(ParseError::expected("identifier", token)),
         }
     }
 }
@@ -56,14 +95,176 @@ impl TokenRepr<CommonToken> for Ident {
     }
 }
 
+/// A numeric literal. Integers which are too large to be represented are treated as floating
+/// point values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// Produce a number from its textual representation.
+    fn from_str(number: &str) -> Option<Self> {
+        number
+            .parse()
+            .map(Self::Integer)
+            .ok()
+            .or_else(|| number.parse().map(Self::Float).ok())
+    }
+}
+
+impl Parse<CommonToken> for Number {
+    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        match parser.parse()? {
+            CommonToken::Number(number) => Ok(number),
+            token => Err(ParseError::expected("number", token)),
+        }
+    }
+}
+
+impl Token<CommonToken> for Number {
+    fn peek(cursor: Cursor<'_, CommonToken>) -> bool {
+        let Some((token, _)) = cursor.token() else {
+            return false;
+        };
+
+        matches!(token, CommonToken::Number(_))
+    }
+
+    fn display() -> &'static str {
+        "number"
+    }
+}
+
+impl TokenRepr<CommonToken> for Number {
+    fn from_base(base: CommonToken) -> Option<Self> {
+        match base {
+            CommonToken::Number(number) => Some(number),
+            _ => None,
+        }
+    }
+}
+
+/// A string literal, quoted with `'`. A repeated `'` within the literal is an escaped quote.
+#[derive(Clone, Debug, Deref, PartialEq)]
+pub struct StringLiteral(String);
+
+impl StringLiteral {
+    fn new(s: impl ToString) -> Self {
+        Self(s.to_string())
+    }
+}
+
+impl Parse<CommonToken> for StringLiteral {
+    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        match parser.parse()? {
+            CommonToken::String(s) => Ok(s),
+            token => Err(ParseError::expected("string", token)),
+        }
+    }
+}
+
+impl Token<CommonToken> for StringLiteral {
+    fn peek(cursor: Cursor<'_, CommonToken>) -> bool {
+        let Some((token, _)) = cursor.token() else {
+            return false;
+        };
+
+        matches!(token, CommonToken::String(_))
+    }
+
+    fn display() -> &'static str {
+        "string"
+    }
+}
+
+impl TokenRepr<CommonToken> for StringLiteral {
+    fn from_base(base: CommonToken) -> Option<Self> {
+        match base {
+            CommonToken::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// A blob literal, written as hexadecimal digits quoted with `'` and prefixed by `x` (such as
+/// `x'00ff'`). There must be an even number of digits.
+#[derive(Clone, Debug, Deref, PartialEq)]
+pub struct BlobLiteral(Vec<u8>);
+
+impl BlobLiteral {
+    /// Decode the hexadecimal digits of a blob literal, producing `None` if there is an odd
+    /// number of digits, or any character isn't a hexadecimal digit.
+    fn from_hex(hex: &str) -> Option<Self> {
+        if !hex.len().is_multiple_of(2) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        Some(Self(
+            hex.as_bytes()
+                .chunks_exact(2)
+                .map(|digits| u8::from_str_radix(std::str::from_utf8(digits).unwrap(), 16).unwrap())
+                .collect(),
+        ))
+    }
+}
+
+impl Parse<CommonToken> for BlobLiteral {
+    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        match parser.parse()? {
+            CommonToken::Blob(blob) => Ok(blob),
+            token => Err(ParseError::expected("blob", token)),
+        }
+    }
+}
+
+impl Token<CommonToken> for BlobLiteral {
+    fn peek(cursor: Cursor<'_, CommonToken>) -> bool {
+        let Some((token, _)) = cursor.token() else {
+            return false;
+        };
+
+        matches!(token, CommonToken::Blob(_))
+    }
+
+    fn display() -> &'static str {
+        "blob"
+    }
+}
+
+impl TokenRepr<CommonToken> for BlobLiteral {
+    fn from_base(base: CommonToken) -> Option<Self> {
+        match base {
+            CommonToken::Blob(blob) => Some(blob),
+            _ => None,
+        }
+    }
+}
+
 /// A punctuation symbol.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Punct {
     Asterisk,
     Comma,
     Semicolon,
+    Period,
     LeftSmooth,
     RightSmooth,
+    /// `[`, which is never produced when lexing SQL (see [`Bracket`](super::delimiter::Bracket)).
+    LeftSquare,
+    /// `]`, which is never produced when lexing SQL (see [`Bracket`](super::delimiter::Bracket)).
+    RightSquare,
+    LeftCurly,
+    RightCurly,
+    /// `=` or `==`.
+    Equals,
+    /// `!=` or `<>`.
+    NotEquals,
+    LessThan,
+    LessThanEquals,
+    GreaterThan,
+    GreaterThanEquals,
 }
 
 impl<S: ?Sized + AsRef<str>> PartialEq<S> for Punct {
@@ -72,21 +273,36 @@ impl<S: ?Sized + AsRef<str>> PartialEq<S> for Punct {
             Punct::Asterisk => "*",
             Punct::Comma => ",",
             Punct::Semicolon => ";",
+            Punct::Period => ".",
             Punct::LeftSmooth => "(",
             Punct::RightSmooth => ")",
+            Punct::LeftSquare => "[",
+            Punct::RightSquare => "]",
+            Punct::LeftCurly => "{",
+            Punct::RightCurly => "}",
+            Punct::Equals => "=",
+            Punct::NotEquals => "!=",
+            Punct::LessThan => "<",
+            Punct::LessThanEquals => "<=",
+            Punct::GreaterThan => ">",
+            Punct::GreaterThanEquals => ">=",
         };
 
         c == other.as_ref()
     }
 }
 
+impl MatchesToken for Punct {
+    fn matches_token(&self, token: &str) -> bool {
+        self == token
+    }
+}
+
 impl Parse<CommonToken> for Punct {
-    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, String> {
+    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
         match parser.parse()? {
             CommonToken::Punct(punct) => Ok(punct),
-            token => Err(format!(
-                "unexpected token (expected punct, found {token:?})"
-            )),
+            token => Err(ParseError::expected("punct", token)),
         }
     }
 }
@@ -114,17 +330,38 @@ impl TokenRepr<CommonToken> for Punct {
     }
 }
 
-/// A token comprising of an identifier, or a piece of punctuation. Any whitespace encountered will
-/// be ignored.
+/// A token comprising of an identifier, a literal, or a piece of punctuation. Any whitespace
+/// encountered will be ignored.
 #[derive(Clone, Debug, From, PartialEq)]
 pub enum CommonToken {
     Ident(Ident),
+    Number(Number),
+    String(StringLiteral),
+    Blob(BlobLiteral),
     Punct(Punct),
 }
 
 impl BufferToken for CommonToken {
     fn from_char(c: char, chars: &mut Peekable<impl Iterator<Item = char>>) -> Outcome<Self> {
         match c {
+            'x' | 'X' if chars.next_if_eq(&'\'').is_some() => {
+                let mut hex = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => hex.push(c),
+                        // Unterminated blob literal.
+                        None => return Outcome::Unexpected,
+                    }
+                }
+
+                match BlobLiteral::from_hex(&hex) {
+                    Some(blob) => Outcome::Token(blob.into()),
+                    // Odd number of digits, or a character which isn't a digit.
+                    None => Outcome::Unexpected,
+                }
+            }
             c @ ('a'..='z' | 'A'..='Z' | '_') => {
                 let ident = iter::once(c)
                     .chain(crate::util::take_while(chars, |c| {
@@ -134,13 +371,107 @@ impl BufferToken for CommonToken {
 
                 Outcome::Token(Ident::new(ident).into())
             }
+            c @ '0'..='9' => {
+                let mut number = iter::once(c)
+                    .chain(crate::util::take_while(chars, char::is_ascii_digit))
+                    .collect::<String>();
+
+                if chars.next_if_eq(&'.').is_some() {
+                    number.push('.');
+                    number.extend(crate::util::take_while(chars, char::is_ascii_digit));
+                }
+
+                if let Some(e) = chars.next_if(|c| matches!(c, 'e' | 'E')) {
+                    number.push(e);
+                    number.extend(chars.next_if(|c| matches!(c, '+' | '-')));
+                    number.extend(crate::util::take_while(chars, char::is_ascii_digit));
+                }
+
+                // Numbers cannot run directly into an identifier (such as `1abc`).
+                if chars
+                    .peek()
+                    .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+                {
+                    return Outcome::Unexpected;
+                }
+
+                match Number::from_str(&number) {
+                    Some(number) => Outcome::Token(number.into()),
+                    // Malformed exponent, such as `1e`.
+                    None => Outcome::Unexpected,
+                }
+            }
+            '\'' => {
+                let mut s = String::new();
+
+                loop {
+                    match chars.next() {
+                        // A repeated quote is an escaped quote.
+                        Some('\'') if chars.next_if_eq(&'\'').is_some() => s.push('\''),
+                        Some('\'') => break,
+                        Some(c) => s.push(c),
+                        // Unterminated string literal.
+                        None => return Outcome::Unexpected,
+                    }
+                }
+
+                Outcome::Token(StringLiteral::new(s).into())
+            }
+            c @ ('"' | '[' | '`') => {
+                let close = if c == '[' { ']' } else { c };
+                let mut ident = String::new();
+
+                loop {
+                    match chars.next() {
+                        // A repeated closing quote is an escaped quote (not applicable to `[ ]`).
+                        Some(c) if c == close && close != ']' && chars.peek() == Some(&close) => {
+                            chars.next();
+                            ident.push(c);
+                        }
+                        Some(c) if c == close => break,
+                        Some(c) => ident.push(c),
+                        // Unterminated quoted identifier.
+                        None => return Outcome::Unexpected,
+                    }
+                }
+
+                Outcome::Token(Ident::new_quoted(ident).into())
+            }
+            // Line comment, continuing until the end of the line.
+            '-' if chars.next_if_eq(&'-').is_some() => {
+                crate::util::take_while(chars, |c| *c != '\n').for_each(drop);
+
+                Outcome::Skip
+            }
+            // Block comment, continuing until the closing `*/`.
+            '/' if chars.next_if_eq(&'*').is_some() => loop {
+                match chars.next() {
+                    Some('*') if chars.next_if_eq(&'/').is_some() => break Outcome::Skip,
+                    Some(_) => {}
+                    // Unterminated block comment.
+                    None => break Outcome::Unexpected,
+                }
+            },
             c if c.is_ascii_punctuation() => Outcome::Token(
                 match c {
                     '*' => Punct::Asterisk,
                     ',' => Punct::Comma,
                     ';' => Punct::Semicolon,
+                    '.' => Punct::Period,
                     '(' => Punct::LeftSmooth,
                     ')' => Punct::RightSmooth,
+                    '{' => Punct::LeftCurly,
+                    '}' => Punct::RightCurly,
+                    '=' => {
+                        chars.next_if_eq(&'=');
+                        Punct::Equals
+                    }
+                    '!' if chars.next_if_eq(&'=').is_some() => Punct::NotEquals,
+                    '<' if chars.next_if_eq(&'=').is_some() => Punct::LessThanEquals,
+                    '<' if chars.next_if_eq(&'>').is_some() => Punct::NotEquals,
+                    '<' => Punct::LessThan,
+                    '>' if chars.next_if_eq(&'=').is_some() => Punct::GreaterThanEquals,
+                    '>' => Punct::GreaterThan,
                     _ => return Outcome::Unexpected,
                 }
                 .into(),
@@ -199,6 +530,46 @@ mod test {
         #[case("*", Punct::Asterisk.into())]
         #[case(",", Punct::Comma.into())]
         #[case(";", Punct::Semicolon.into())]
+        #[case(".", Punct::Period.into())]
+        #[case("=", Punct::Equals.into())]
+        #[case("==", Punct::Equals.into())]
+        #[case("!=", Punct::NotEquals.into())]
+        #[case("<>", Punct::NotEquals.into())]
+        #[case("<", Punct::LessThan.into())]
+        #[case("<=", Punct::LessThanEquals.into())]
+        #[case(">", Punct::GreaterThan.into())]
+        #[case(">=", Punct::GreaterThanEquals.into())]
+        #[case("{", Punct::LeftCurly.into())]
+        #[case("}", Punct::RightCurly.into())]
+        #[case("\"my col\"", Ident::new_quoted("my col").into())]
+        #[case("[my col]", Ident::new_quoted("my col").into())]
+        #[case("`my col`", Ident::new_quoted("my col").into())]
+        #[case("\"select\"", Ident::new_quoted("select").into())]
+        #[case("[from table]", Ident::new_quoted("from table").into())]
+        #[case("`order`", Ident::new_quoted("order").into())]
+        #[case("\"say \"\"hi\"\"\"", Ident::new_quoted("say \"hi\"").into())]
+        #[case("`a``b`", Ident::new_quoted("a`b").into())]
+        #[case("[a\"b]", Ident::new_quoted("a\"b").into())]
+        #[case("''", StringLiteral::new("").into())]
+        #[case("'andy'", StringLiteral::new("andy").into())]
+        #[case("'hello world'", StringLiteral::new("hello world").into())]
+        #[case("'it''s'", StringLiteral::new("it's").into())]
+        #[case("''''", StringLiteral::new("'").into())]
+        #[case("'select \"x\"'", StringLiteral::new("select \"x\"").into())]
+        #[case("'a\nb'", StringLiteral::new("a\nb").into())]
+        #[case("x''", BlobLiteral(vec![]).into())]
+        #[case("x'00ff'", BlobLiteral(vec![0x00, 0xff]).into())]
+        #[case("X'aBcD'", BlobLiteral(vec![0xab, 0xcd]).into())]
+        #[case("x", Ident::new("x").into())]
+        #[case("xyz", Ident::new("xyz").into())]
+        #[case("1", Number::Integer(1).into())]
+        #[case("0123", Number::Integer(123).into())]
+        #[case("1.5", Number::Float(1.5).into())]
+        #[case("1.", Number::Float(1.0).into())]
+        #[case("1e3", Number::Float(1000.0).into())]
+        #[case("2.5E-1", Number::Float(0.25).into())]
+        #[case("9223372036854775807", Number::Integer(i64::MAX).into())]
+        #[case("9223372036854775808", Number::Float(9223372036854775808.0).into())]
         fn valid(#[case] token: &'static str, #[case] expected: CommonToken) {
             let token = parse_token::<CommonToken>(token);
             assert_eq!(token, expected);
@@ -206,9 +577,27 @@ mod test {
 
         #[rstest]
         #[case("!")]
-        #[case("1")]
+        #[case("'")]
+        #[case("'abc")]
+        #[case("'abc''")]
         #[case("1abc")]
+        #[case("1e")]
+        #[case("1.5x")]
+        #[case("-")]
+        #[case("-a")]
+        #[case("/")]
+        #[case("/a")]
+        #[case("/* abc")]
+        #[case("/* abc *")]
+        #[case("/*/")]
         #[case("!abc")]
+        #[case("\"abc")]
+        #[case("[abc")]
+        #[case::close_bracket("]")]
+        #[case("`abc")]
+        #[case::odd_length("x'abc'")]
+        #[case::not_hex("x'zz'")]
+        #[case::unterminated("x'00")]
         fn unexpected(#[case] token: &'static str) {
             parse_unexpected::<CommonToken>(token);
         }
@@ -218,8 +607,96 @@ mod test {
         #[case("\t")]
         #[case("\n")]
         #[case(" abc")]
+        #[case("--")]
+        #[case("-- abc")]
+        #[case("-- abc\nabc")]
+        #[case("/**/")]
+        #[case("/* abc */")]
+        #[case("/* a\nb */ abc")]
+        #[case("/***/")]
+        #[case("/* -- */")]
         fn skip(#[case] token: &'static str) {
             parse_skip::<CommonToken>(token);
         }
     }
+
+    mod ident {
+        use super::*;
+
+        #[rstest]
+        #[case("select", "select")]
+        #[case("SELECT", "select")]
+        #[case("Users", "users")]
+        #[case("my_Col", "MY_col")]
+        fn eq_ignores_case(#[case] ident: &str, #[case] other: &str) {
+            assert!(Ident::new(ident).eq_ignore_case(other));
+        }
+
+        #[rstest]
+        #[case("select", "selects")]
+        #[case("users", "user")]
+        fn ne(#[case] ident: &str, #[case] other: &str) {
+            assert!(!Ident::new(ident).eq_ignore_case(other));
+        }
+
+        #[rstest]
+        #[case(Ident::new("select"), true)]
+        #[case(Ident::new("SELECT"), true)]
+        #[case(Ident::new_quoted("select"), false)]
+        #[case(Ident::new("selects"), false)]
+        fn matches_token(#[case] ident: Ident, #[case] expected: bool) {
+            assert_eq!(ident.matches_token("select"), expected);
+        }
+
+        #[rstest]
+        #[case("select", "select", true)]
+        #[case("SELECT", "select", false)]
+        fn eq_str_consistent(#[case] ident: &str, #[case] other: &str, #[case] expected: bool) {
+            assert_eq!(Ident::new(ident) == other, expected);
+            assert_eq!(Ident::new(ident) == Ident::new(other), expected);
+        }
+
+        #[test]
+        fn preserves_case() {
+            let Outcome::Token(CommonToken::Ident(ident)) =
+                CommonToken::from_char('U', &mut "sers".chars().peekable())
+            else {
+                panic!("expected identifier");
+            };
+
+            assert_eq!(ident.as_str(), "Users");
+        }
+    }
+
+    mod comments {
+        use super::*;
+
+        use crate::buffer::TokenBuffer;
+
+        #[rstest]
+        #[case("select a -- trailing comment")]
+        #[case("-- leading comment\nselect a")]
+        #[case("select /* inline */ a")]
+        #[case("select/**/a")]
+        #[case("select -- first\n/* second\n -- nested */ a -- third")]
+        #[case("/* multi\nline */select\n-- comment\na\n")]
+        fn matches_uncommented(#[case] source: &str) {
+            let commented = TokenBuffer::<CommonToken>::new(source).unwrap();
+            let uncommented = TokenBuffer::<CommonToken>::new("select a").unwrap();
+
+            assert_eq!(*commented, *uncommented);
+        }
+
+        #[test]
+        fn comment_ends_at_newline() {
+            let buffer = TokenBuffer::<CommonToken>::new("a -- b\nc").unwrap();
+
+            assert_eq!(buffer[..], [Ident::new("a").into(), Ident::new("c").into()]);
+        }
+
+        #[test]
+        fn unterminated_block_comment() {
+            assert!(TokenBuffer::<CommonToken>::new("select /* a").is_err());
+        }
+    }
 }
@@ -7,10 +7,10 @@ pub use self::{parse::entrypoint::*, prelude::*};
 
 pub mod prelude {
     pub use crate::{
-        buffer::{BufferToken, Cursor, TokenBuffer},
+        buffer::{BufferToken, Cursor, Span, TokenBuffer, Trivia},
         parse::{
-            BufferParser, Parse, Token, lookahead::Lookahead, punctuated::Punctuated,
-            token::TokenRepr,
+            BufferParser, Parse, ParseError, ParseErrorKind, Token, lookahead::Lookahead,
+            punctuated::Punctuated, token::TokenRepr,
         },
     };
 }
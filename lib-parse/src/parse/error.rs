@@ -0,0 +1,161 @@
+use std::fmt::Debug;
+
+use thiserror::Error;
+
+use crate::buffer::Span;
+
+/// Error produced whilst tokenising or parsing, optionally with the location in the source that
+/// it occurred.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("{kind}")]
+pub struct ParseError {
+    /// The kind of error.
+    pub kind: ParseErrorKind,
+    /// Location of the token which caused the error.
+    pub span: Option<Span>,
+}
+
+impl ParseError {
+    /// Create a new error at the provided location.
+    pub fn new(kind: ParseErrorKind, span: Span) -> Self {
+        Self {
+            kind,
+            span: Some(span),
+        }
+    }
+
+    /// Create an [`ParseErrorKind::Expected`] error, using the debug representation of the token
+    /// that was found.
+    pub fn expected(expected: impl ToString, found: impl Debug) -> Self {
+        ParseErrorKind::Expected {
+            expected: expected.to_string(),
+            found: format!("{found:?}"),
+        }
+        .into()
+    }
+
+    /// Set the location of the error, if it doesn't already have one.
+    pub(crate) fn or_span(mut self, span: Option<Span>) -> Self {
+        self.span = self.span.or(span);
+        self
+    }
+}
+
+impl From<ParseErrorKind> for ParseError {
+    fn from(kind: ParseErrorKind) -> Self {
+        Self { kind, span: None }
+    }
+}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        ParseErrorKind::Custom(message).into()
+    }
+}
+
+impl From<&str> for ParseError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+/// The kind of [`ParseError`].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A character was encountered which can't begin any token.
+    #[error("unexpected character: {0}")]
+    UnexpectedCharacter(char),
+    /// The end of the input was reached whilst a token was still required.
+    #[error("unexpected end of input")]
+    UnexpectedEof,
+    /// A token was found, but it wasn't what was expected.
+    #[error("expected {expected}, found {found}")]
+    Expected { expected: String, found: String },
+    /// The closing delimiter of a group couldn't be found.
+    #[error("unclosed delimiter")]
+    UnclosedDelimiter,
+    /// Any other error.
+    #[error("{0}")]
+    Custom(String),
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+
+    use crate::{
+        buffer::TokenBuffer,
+        common::{
+            delimiter::Parenthesis,
+            token::{CommonToken, Ident, Punct},
+        },
+        parse_str,
+    };
+
+    #[rstest]
+    #[case("", ParseErrorKind::UnexpectedEof.into())]
+    #[case("!", ParseError::new(ParseErrorKind::UnexpectedCharacter('!'), Span { start: 0, end: 1 }))]
+    #[case(
+        "*",
+        ParseError::expected("identifier", CommonToken::Punct(Punct::Asterisk))
+    )]
+    fn parse_ident(#[case] source: &str, #[case] expected: ParseError) {
+        assert_eq!(
+            parse_str::<Ident, CommonToken>(source).unwrap_err(),
+            expected
+        );
+    }
+
+    #[test]
+    fn located_at_offending_token() {
+        let buffer = TokenBuffer::<CommonToken>::new("a bc  *").unwrap();
+        let parser = buffer.parser();
+
+        let e = parser
+            .parse_with(|parser| {
+                Ok((
+                    parser.parse::<Ident>()?,
+                    parser.parse::<Ident>()?,
+                    parser.parse::<Ident>()?,
+                ))
+            })
+            .unwrap_err();
+
+        assert_eq!(e.span, Some(Span { start: 6, end: 7 }));
+    }
+
+    #[test]
+    fn located_at_end_of_input() {
+        let buffer = TokenBuffer::<CommonToken>::new("a ").unwrap();
+        let parser = buffer.parser();
+
+        let e = parser
+            .parse_with(|parser| Ok((parser.parse::<Ident>()?, parser.parse::<Ident>()?)))
+            .unwrap_err();
+
+        assert_eq!(e.kind, ParseErrorKind::UnexpectedEof);
+        assert_eq!(e.span, Some(Span { start: 1, end: 1 }));
+    }
+
+    #[test]
+    fn unclosed_delimiter_located_at_opening() {
+        let buffer = TokenBuffer::<CommonToken>::new("a (b (c)").unwrap();
+        let parser = buffer.parser();
+        parser.parse::<Ident>().unwrap();
+
+        let e = parser.group::<Parenthesis>().err().unwrap();
+
+        assert_eq!(e.kind, ParseErrorKind::UnclosedDelimiter);
+        assert_eq!(e.span, Some(Span { start: 2, end: 3 }));
+    }
+
+    #[rstest]
+    #[case(ParseErrorKind::UnexpectedEof.into(), "unexpected end of input")]
+    #[case(ParseError::expected("`;`", Punct::Comma), "expected `;`, found Comma")]
+    #[case(ParseError::from("custom"), "custom")]
+    fn display(#[case] error: ParseError, #[case] expected: &str) {
+        assert_eq!(error.to_string(), expected);
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt::Debug;
+
 use super::*;
 
 /// Utility for determining the next token at the cursor. It will track which tokens have been
@@ -30,29 +32,31 @@ impl<'b, BaseToken> Lookahead<'b, BaseToken> {
         false
     }
 
-    /// Consume this instance and create an error message containing all peek attempts.
-    pub fn error(self) -> String {
-        match self.comparisons.len() {
-            0 => {
-                if self.cursor.eof() {
-                    "unexpected end of input".into()
-                } else {
-                    "unexpected token".into()
-                }
-            }
-            1 => {
-                format!("expected {}", self.comparisons[0])
-            }
-            2 => {
-                format!(
-                    "expected {} or {}",
-                    self.comparisons[0], self.comparisons[1]
-                )
-            }
-            _ => {
-                format!("expected one of: {}", self.comparisons.join(", "))
-            }
-        }
+    /// Peek for a token one position after the current token, without advancing. This attempt
+    /// isn't recorded, as any resulting error should refer to the current token.
+    pub fn peek2<T: Token<BaseToken>>(&self) -> bool {
+        T::peek(self.cursor.next_cursor())
+    }
+
+    /// Consume this instance and create an error containing all peek attempts.
+    pub fn error(self) -> ParseError
+    where
+        BaseToken: Clone + Debug,
+    {
+        let found = match self.cursor.token() {
+            Some((token, _)) => format!("{token:?}"),
+            None if self.comparisons.is_empty() => return ParseErrorKind::UnexpectedEof.into(),
+            None => "end of input".to_string(),
+        };
+
+        let expected = match self.comparisons.len() {
+            0 => return format!("unexpected token: {found}").into(),
+            1 => self.comparisons[0].to_string(),
+            2 => format!("{} or {}", self.comparisons[0], self.comparisons[1]),
+            _ => format!("one of: {}", self.comparisons.join(", ")),
+        };
+
+        ParseErrorKind::Expected { expected, found }.into()
     }
 }
 
@@ -62,13 +66,13 @@ mod test {
 
     use super::*;
 
-    #[derive(Clone, From)]
+    #[derive(Clone, Debug, From)]
     enum BaseToken {
         Some(SomeToken),
         Other(OtherToken),
     }
 
-    #[derive(Clone)]
+    #[derive(Clone, Debug)]
     struct SomeToken;
     impl Token<BaseToken> for SomeToken {
         fn peek(cursor: Cursor<'_, BaseToken>) -> bool {
@@ -84,7 +88,7 @@ mod test {
         }
     }
 
-    #[derive(Clone)]
+    #[derive(Clone, Debug)]
     struct OtherToken;
     impl Token<BaseToken> for OtherToken {
         fn peek(cursor: Cursor<'_, BaseToken>) -> bool {
@@ -102,7 +106,7 @@ mod test {
 
     #[test]
     fn can_peek() {
-        let buffer = TokenBuffer::new_with_tokens(vec![SomeToken.into()]);
+        let buffer = TokenBuffer::from_tokens(vec![SomeToken.into()]);
         let parser = buffer.parser();
         let mut lookahead = parser.lookahead();
 
@@ -121,12 +125,15 @@ mod test {
 
         assert!(!lookahead.peek::<SomeToken>());
         assert_eq!(lookahead.comparisons.len(), 1);
-        assert_eq!(lookahead.error(), format!("expected some token"));
+        assert_eq!(
+            lookahead.error().to_string(),
+            "expected some token, found end of input"
+        );
     }
 
     #[test]
     fn cant_peek() {
-        let buffer = TokenBuffer::new_with_tokens(vec![SomeToken.into()]);
+        let buffer = TokenBuffer::from_tokens(vec![SomeToken.into()]);
         let parser = buffer.parser();
         let mut lookahead = parser.lookahead();
 
@@ -134,6 +141,59 @@ mod test {
         assert_eq!(lookahead.comparisons.len(), 1);
         // Lookahead shouldn't modify the token.
         assert!(!lookahead.cursor.eof());
-        assert_eq!(lookahead.error(), format!("expected other token"))
+        assert_eq!(
+            lookahead.error().kind,
+            ParseErrorKind::Expected {
+                expected: "other token".into(),
+                found: "Some(SomeToken)".into()
+            }
+        );
+    }
+
+    mod peek2 {
+        use super::*;
+
+        #[test]
+        fn second_token() {
+            let buffer = TokenBuffer::from_tokens(vec![SomeToken.into(), OtherToken.into()]);
+            let parser = buffer.parser();
+            let mut lookahead = parser.lookahead();
+
+            assert!(lookahead.peek2::<OtherToken>());
+            assert!(!lookahead.peek2::<SomeToken>());
+            // The current token is unaffected.
+            assert!(lookahead.peek::<SomeToken>());
+            assert!(!parser.is_empty());
+        }
+
+        #[test]
+        fn distinguish_sequence() {
+            let buffer = TokenBuffer::from_tokens(vec![SomeToken.into(), SomeToken.into()]);
+            let parser = buffer.parser();
+            let lookahead = parser.lookahead();
+
+            assert!(lookahead.peek2::<SomeToken>());
+            assert!(!lookahead.peek2::<OtherToken>());
+        }
+
+        #[test]
+        fn single_token() {
+            let buffer = TokenBuffer::from_tokens(vec![SomeToken.into()]);
+            let parser = buffer.parser();
+            let lookahead = parser.lookahead();
+
+            assert!(!lookahead.peek2::<SomeToken>());
+            assert!(!lookahead.peek2::<OtherToken>());
+            assert!(lookahead.comparisons.is_empty());
+        }
+
+        #[test]
+        fn empty_buffer() {
+            let buffer = TokenBuffer::<BaseToken>::empty();
+            let parser = buffer.parser();
+            let lookahead = parser.lookahead();
+
+            assert!(!lookahead.peek2::<SomeToken>());
+        }
     }
 }
@@ -1,12 +1,18 @@
+pub mod error;
 pub mod lookahead;
 pub mod punctuated;
 pub mod token;
 
-use std::{cell::Cell, marker::PhantomData};
+use std::{cell::Cell, fmt::Debug, marker::PhantomData};
 
 use derive_more::Deref;
 
-pub use self::{lookahead::Lookahead, punctuated::Punctuated, token::TokenRepr};
+pub use self::{
+    error::{ParseError, ParseErrorKind},
+    lookahead::Lookahead,
+    punctuated::Punctuated,
+    token::TokenRepr,
+};
 
 use crate::buffer::{BufferToken, Cursor, TokenBuffer};
 
@@ -15,9 +21,30 @@ pub mod entrypoint {
     use super::*;
 
     /// Parse `T` from a string. Will use `BaseToken` as the low-level token when parsing.
-    pub fn parse_str<T: Parse<BaseToken>, BaseToken: BufferToken + 'static>(
+    ///
+    /// The entire string must be consumed, otherwise an error is produced at the first remaining
+    /// token. See [`parse_str_partial`] to allow remaining tokens.
+    pub fn parse_str<T: Parse<BaseToken>, BaseToken: BufferToken + Debug + 'static>(
         s: &str,
-    ) -> Result<T, String> {
+    ) -> Result<T, ParseError> {
+        let buffer = TokenBuffer::<BaseToken>::new(s)?;
+        let parser = buffer.parser();
+
+        let value = T::parse(&parser)?;
+
+        let cursor = parser.cursor();
+        if let Some(token) = cursor.peek_n(0) {
+            return Err(ParseError::expected("end of input", token).or_span(cursor.span()));
+        }
+
+        Ok(value)
+    }
+
+    /// Parse `T` from the beginning of a string, ignoring any tokens which remain afterwards. Will
+    /// use `BaseToken` as the low-level token when parsing.
+    pub fn parse_str_partial<T: Parse<BaseToken>, BaseToken: BufferToken + 'static>(
+        s: &str,
+    ) -> Result<T, ParseError> {
         let buffer = TokenBuffer::<BaseToken>::new(s)?;
         let parser = buffer.parser();
 
@@ -28,15 +55,19 @@ pub mod entrypoint {
 /// A value which can be parsed from a [`BufferParser`] containing `BaseToken`s.
 pub trait Parse<BaseToken>: Sized {
     /// Parse a value with the provided parser.
-    fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, String>;
+    fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, ParseError>;
 }
 
 impl<T> Parse<T> for T
 where
     T: Clone,
 {
-    fn parse(parser: BufferParser<'_, T>) -> Result<Self, String> {
-        parser.step(|cursor| cursor.token().ok_or_else(|| "unexpected token".to_string()))
+    fn parse(parser: BufferParser<'_, T>) -> Result<Self, ParseError> {
+        parser.step(|cursor| {
+            cursor
+                .token()
+                .ok_or_else(|| ParseErrorKind::UnexpectedEof.into())
+        })
     }
 }
 
@@ -75,16 +106,19 @@ impl<'b, BaseToken> FullBufferParser<'b, BaseToken> {
         }
     }
 
-    /// Parse `T` with the provided function.
+    /// Parse `T` with the provided function. If an error without a location is produced, it will
+    /// be located at the token where parsing began.
     pub fn parse_with<T>(
         &'b self,
-        function: fn(BufferParser<'b, BaseToken>) -> Result<T, String>,
-    ) -> Result<T, String> {
-        function(self)
+        function: fn(BufferParser<'b, BaseToken>) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let span = self.cursor().span();
+
+        function(self).map_err(|e| e.or_span(span))
     }
 
     /// Parse `T` with the [`Parse`] implementation.
-    pub fn parse<T: Parse<BaseToken>>(&'b self) -> Result<T, String> {
+    pub fn parse<T: Parse<BaseToken>>(&'b self) -> Result<T, ParseError> {
         self.parse_with(T::parse)
     }
 
@@ -94,8 +128,8 @@ impl<'b, BaseToken> FullBufferParser<'b, BaseToken> {
         &self,
         function: impl for<'c> FnOnce(
             StepCursor<'c, 'b, BaseToken>,
-        ) -> Result<(T, Cursor<'c, BaseToken>), String>,
-    ) -> Result<T, String> {
+        ) -> Result<(T, Cursor<'c, BaseToken>), ParseError>,
+    ) -> Result<T, ParseError> {
         let (result, cursor) = function(StepCursor {
             marker: PhantomData,
             cursor: self.cursor.get(),
@@ -106,38 +140,43 @@ impl<'b, BaseToken> FullBufferParser<'b, BaseToken> {
 
     pub fn group<D: Delimiter<BaseToken>>(
         &self,
-    ) -> Result<(D, FullBufferParser<'b, BaseToken>), String> {
+    ) -> Result<(D, FullBufferParser<'b, BaseToken>), ParseError> {
+        let opening_span = self.cursor().span();
         let opening = self.parse::<D::Left>()?;
         let cursor = self.cursor();
 
-        let offset = self
-            .step(|step_cursor| {
-                let mut cursor = *step_cursor;
+        let offset = self.step(|step_cursor| {
+            let mut cursor = *step_cursor;
 
-                // Scan ahead to find the closing delimiter
-                let mut offset = 0;
-                let mut depth = 0;
+            // Scan ahead to find the closing delimiter
+            let mut offset = 0;
+            let mut depth = 0;
 
-                loop {
-                    if D::Right::peek(cursor) {
-                        if depth == 0 {
-                            break;
-                        }
+            loop {
+                if cursor.eof() {
+                    return Err(
+                        ParseError::from(ParseErrorKind::UnclosedDelimiter).or_span(opening_span)
+                    );
+                }
 
-                        depth -= 1;
+                if D::Right::peek(cursor) {
+                    if depth == 0 {
+                        break;
                     }
 
-                    if D::Left::peek(cursor) {
-                        depth += 1;
-                    }
+                    depth -= 1;
+                }
 
-                    offset += 1;
-                    cursor = cursor.next_cursor();
+                if D::Left::peek(cursor) {
+                    depth += 1;
                 }
 
-                Ok((offset, cursor))
-            })
-            .unwrap();
+                offset += 1;
+                cursor = cursor.next_cursor();
+            }
+
+            Ok((offset, cursor))
+        })?;
 
         let (inner, _after) = cursor.split_cursor(offset);
 
@@ -146,11 +185,59 @@ impl<'b, BaseToken> FullBufferParser<'b, BaseToken> {
         Ok((D::new(opening, closing), FullBufferParser::new(inner)))
     }
 
+    /// Parse a group surrounded by the delimiter `D`, producing the tokens within it without
+    /// parsing them. Any nested groups are included in the tokens.
+    pub fn group_raw<D: Delimiter<BaseToken>>(&self) -> Result<(D, Vec<BaseToken>), ParseError>
+    where
+        BaseToken: Clone,
+    {
+        let (delimiter, inner) = self.group::<D>()?;
+        let cursor = inner.cursor();
+
+        Ok((
+            delimiter,
+            (0..cursor.remaining())
+                .filter_map(|n| cursor.peek_n(n))
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    /// Create an independent parser beginning from this position in the buffer. Parsing with the
+    /// fork won't advance this parser, unless it's adopted with [`Self::commit`].
+    pub fn fork(&self) -> FullBufferParser<'b, BaseToken> {
+        FullBufferParser::new(self.cursor())
+    }
+
+    /// Advance this parser to the position of a parser created with [`Self::fork`].
+    pub fn commit(&self, fork: &FullBufferParser<'b, BaseToken>) {
+        self.cursor.set(fork.cursor.get());
+    }
+
     /// Begin a lookahead from this position in the buffer.
     pub fn lookahead(&self) -> Lookahead<'b, BaseToken> {
         Lookahead::new(self.cursor())
     }
 
+    /// Parse `T` only if it's the next token, otherwise produce `None` without advancing. This is
+    /// a shorthand for an optional token, when a [`Lookahead`] isn't required for error reporting.
+    pub fn parse_if_peek<T: Token<BaseToken> + Parse<BaseToken>>(
+        &'b self,
+    ) -> Result<Option<T>, ParseError> {
+        if T::peek(self.cursor()) {
+            self.parse().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parse and discard `T` if it's the next token, reporting whether it was consumed. See
+    /// [`Self::parse_if_peek`]. Will fail if `T` is peeked but can't be parsed, rather than
+    /// reporting it as absent.
+    pub fn eat<T: Token<BaseToken> + Parse<BaseToken>>(&'b self) -> Result<bool, ParseError> {
+        Ok(self.parse_if_peek::<T>()?.is_some())
+    }
+
     /// Check if the end of the buffer has been reached.
     pub fn is_empty(&self) -> bool {
         self.cursor().eof()
@@ -196,7 +283,7 @@ mod test {
         }
 
         impl Parse<AOrB> for A {
-            fn parse(parser: BufferParser<'_, AOrB>) -> Result<Self, String> {
+            fn parse(parser: BufferParser<'_, AOrB>) -> Result<Self, ParseError> {
                 match parser.parse()? {
                     AOrB::A(a) => Ok(a),
                     _ => Err("expected `a`".into()),
@@ -204,7 +291,7 @@ mod test {
             }
         }
         impl Parse<AOrB> for B {
-            fn parse(parser: BufferParser<'_, AOrB>) -> Result<Self, String> {
+            fn parse(parser: BufferParser<'_, AOrB>) -> Result<Self, ParseError> {
                 match parser.parse()? {
                     AOrB::B(b) => Ok(b),
                     _ => Err("expected `b`".into()),
@@ -215,7 +302,7 @@ mod test {
         /// Ensure that the `BaseToken` of the buffer can be directly parsed out.
         #[test]
         fn base_token() {
-            let buffer = TokenBuffer::<AOrB>::new_with_tokens(vec![A.into()]);
+            let buffer = TokenBuffer::<AOrB>::from_tokens(vec![A.into()]);
             let parser = buffer.parser();
 
             let _a_or_b: AOrB = parser.parse().unwrap();
@@ -226,7 +313,7 @@ mod test {
         /// parsed out.
         #[test]
         fn into_token() {
-            let buffer = TokenBuffer::<AOrB>::new_with_tokens(vec![A.into(), B.into()]);
+            let buffer = TokenBuffer::<AOrB>::from_tokens(vec![A.into(), B.into()]);
             let parser = buffer.parser();
 
             let _a: A = parser.parse().unwrap();
@@ -235,6 +322,50 @@ mod test {
         }
     }
 
+    mod entrypoint {
+        use super::*;
+
+        use crate::{
+            buffer::Span,
+            common::token::{CommonToken, Ident, Punct},
+            parse::entrypoint::*,
+        };
+
+        #[test]
+        fn parse_str_consumed() {
+            assert_eq!(parse_str::<Ident, CommonToken>("a").unwrap(), "a");
+        }
+
+        #[test]
+        fn parse_str_trailing() {
+            let e = parse_str::<Ident, CommonToken>("a ;").unwrap_err();
+
+            assert_eq!(
+                e,
+                ParseError::new(
+                    ParseErrorKind::Expected {
+                        expected: "end of input".to_string(),
+                        found: format!("{:?}", CommonToken::Punct(Punct::Semicolon)),
+                    },
+                    Span { start: 2, end: 3 }
+                )
+            );
+        }
+
+        #[test]
+        fn parse_str_partial_trailing() {
+            assert_eq!(
+                parse_str_partial::<Ident, CommonToken>("a b ;").unwrap(),
+                "a"
+            );
+        }
+
+        #[test]
+        fn parse_str_partial_error() {
+            assert!(parse_str_partial::<Ident, CommonToken>(";").is_err());
+        }
+    }
+
     mod step {
         use super::*;
 
@@ -243,7 +374,7 @@ mod test {
 
         #[test]
         fn success() {
-            let buffer = TokenBuffer::new_with_tokens(vec![Token]);
+            let buffer = TokenBuffer::from_tokens(vec![Token]);
             let parser = buffer.parser();
 
             assert!(!parser.is_empty());
@@ -253,7 +384,7 @@ mod test {
 
         #[test]
         fn fail() {
-            let buffer = TokenBuffer::new_with_tokens(vec![Token]);
+            let buffer = TokenBuffer::from_tokens(vec![Token]);
             let parser = buffer.parser();
 
             assert!(!parser.is_empty());
@@ -263,4 +394,294 @@ mod test {
             assert!(!parser.is_empty());
         }
     }
+
+    mod fork {
+        use super::*;
+
+        use crate::common::token::{CommonToken, Ident, Punct};
+
+        /// Parse two identifiers.
+        fn two_idents(parser: BufferParser<'_, CommonToken>) -> Result<(Ident, Ident), ParseError> {
+            Ok((parser.parse()?, parser.parse()?))
+        }
+
+        #[test]
+        fn failed_fork_leaves_position() {
+            let buffer = TokenBuffer::<CommonToken>::new("a ;").unwrap();
+            let parser = buffer.parser();
+
+            let fork = parser.fork();
+            assert!(fork.parse_with(two_idents).is_err());
+
+            // Fork advanced past `a`, but the original parser is untouched.
+            assert!(fork.parse::<Ident>().is_err());
+            assert_eq!(parser.parse::<Ident>().unwrap(), "a");
+        }
+
+        #[test]
+        fn commit() {
+            let buffer = TokenBuffer::<CommonToken>::new("a b ;").unwrap();
+            let parser = buffer.parser();
+
+            let fork = parser.fork();
+            let (a, b) = fork.parse_with(two_idents).unwrap();
+            assert_eq!((a.as_str(), b.as_str()), ("a", "b"));
+
+            parser.commit(&fork);
+            assert_eq!(parser.parse::<Punct>().unwrap(), ";");
+            assert!(parser.is_empty());
+        }
+
+        #[test]
+        fn fork_is_independent() {
+            let buffer = TokenBuffer::<CommonToken>::new("a b").unwrap();
+            let parser = buffer.parser();
+
+            let fork = parser.fork();
+            parser.parse::<Ident>().unwrap();
+
+            assert_eq!(fork.parse::<Ident>().unwrap(), "a");
+            assert_eq!(parser.parse::<Ident>().unwrap(), "b");
+            assert!(parser.is_empty());
+        }
+    }
+
+    mod parse_if_peek {
+        use super::*;
+
+        use crate::common::{
+            delimiter::LeftParenthesis,
+            token::{CommonToken, Ident},
+        };
+
+        #[test]
+        fn present() {
+            let buffer = TokenBuffer::<CommonToken>::new("( a").unwrap();
+            let parser = buffer.parser();
+
+            assert!(parser.parse_if_peek::<LeftParenthesis>().unwrap().is_some());
+            assert_eq!(parser.parse::<Ident>().unwrap(), "a");
+        }
+
+        #[test]
+        fn absent() {
+            let buffer = TokenBuffer::<CommonToken>::new("a").unwrap();
+            let parser = buffer.parser();
+
+            assert!(parser.parse_if_peek::<LeftParenthesis>().unwrap().is_none());
+            assert_eq!(parser.parse::<Ident>().unwrap(), "a");
+        }
+
+        #[test]
+        fn end_of_input() {
+            let buffer = TokenBuffer::<CommonToken>::new("").unwrap();
+            let parser = buffer.parser();
+
+            assert!(parser.parse_if_peek::<LeftParenthesis>().unwrap().is_none());
+            assert!(parser.is_empty());
+        }
+    }
+
+    mod eat {
+        use super::*;
+
+        use crate::common::{
+            delimiter::LeftParenthesis,
+            token::{CommonToken, Ident},
+        };
+
+        #[test]
+        fn advances_on_match() {
+            let buffer = TokenBuffer::<CommonToken>::new("( ( a").unwrap();
+            let parser = buffer.parser();
+
+            assert!(parser.eat::<LeftParenthesis>().unwrap());
+            assert!(parser.eat::<LeftParenthesis>().unwrap());
+            assert!(!parser.eat::<LeftParenthesis>().unwrap());
+            assert_eq!(parser.parse::<Ident>().unwrap(), "a");
+            assert!(!parser.eat::<LeftParenthesis>().unwrap());
+        }
+
+        /// Two identifiers, which is peeked by its first identifier alone.
+        struct IdentPair;
+
+        impl Token<CommonToken> for IdentPair {
+            fn peek(cursor: Cursor<'_, CommonToken>) -> bool {
+                Ident::peek(cursor)
+            }
+
+            fn display() -> &'static str {
+                "identifier pair"
+            }
+        }
+
+        impl Parse<CommonToken> for IdentPair {
+            fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+                parser.parse::<Ident>()?;
+                parser.parse::<Ident>()?;
+
+                Ok(Self)
+            }
+        }
+
+        #[test]
+        fn reports_parse_error() {
+            let buffer = TokenBuffer::<CommonToken>::new("a (").unwrap();
+            let parser = buffer.parser();
+
+            // The peek succeeds, so the failure to parse is reported rather than treated as absent.
+            assert!(parser.eat::<IdentPair>().is_err());
+        }
+    }
+
+    mod group {
+        use super::*;
+
+        use rstest::rstest;
+
+        use crate::common::{
+            delimiter::{Brace, Bracket, Parenthesis},
+            token::{CommonToken, Ident, Punct},
+        };
+
+        #[test]
+        fn balanced() {
+            let buffer = TokenBuffer::<CommonToken>::new("(a (b) c) d").unwrap();
+            let parser = buffer.parser();
+
+            let (_parens, inner) = parser.group::<Parenthesis>().unwrap();
+
+            assert_eq!(
+                std::iter::from_fn(|| (!inner.is_empty()).then(|| inner.parse::<CommonToken>()))
+                    .count(),
+                5
+            );
+            assert!(!parser.is_empty());
+        }
+
+        #[test]
+        fn empty() {
+            let buffer = TokenBuffer::<CommonToken>::new("()").unwrap();
+            let parser = buffer.parser();
+
+            let (_parens, inner) = parser.group::<Parenthesis>().unwrap();
+
+            assert!(inner.is_empty());
+            assert!(parser.is_empty());
+        }
+
+        #[rstest]
+        #[case("(a")]
+        #[case("(")]
+        #[case("(a (b)")]
+        #[case("((a)")]
+        fn unclosed(#[case] source: &str) {
+            let buffer = TokenBuffer::<CommonToken>::new(source).unwrap();
+            let parser = buffer.parser();
+
+            assert_eq!(
+                parser.group::<Parenthesis>().err().map(|e| e.kind),
+                Some(ParseErrorKind::UnclosedDelimiter)
+            );
+        }
+
+        #[test]
+        fn brace() {
+            let buffer = TokenBuffer::<CommonToken>::new("{a {b} c} d").unwrap();
+            let parser = buffer.parser();
+
+            let (_braces, inner) = parser.group::<Brace>().unwrap();
+
+            assert_eq!(inner.parse::<Ident>().unwrap(), "a");
+            let (_braces, nested) = inner.group::<Brace>().unwrap();
+            assert_eq!(nested.parse::<Ident>().unwrap(), "b");
+            assert_eq!(inner.parse::<Ident>().unwrap(), "c");
+            assert!(inner.is_empty());
+
+            assert_eq!(parser.parse::<Ident>().unwrap(), "d");
+        }
+
+        #[test]
+        fn brace_unclosed() {
+            let buffer = TokenBuffer::<CommonToken>::new("{a {b}").unwrap();
+            let parser = buffer.parser();
+
+            assert_eq!(
+                parser.group::<Brace>().err().map(|e| e.kind),
+                Some(ParseErrorKind::UnclosedDelimiter)
+            );
+        }
+
+        #[test]
+        fn bracket() {
+            // `[` begins a quoted identifier when lexed, so the tokens are provided directly.
+            let ident = TokenBuffer::<CommonToken>::new("a").unwrap()[0].clone();
+            let buffer = TokenBuffer::from_tokens(vec![
+                Punct::LeftSquare.into(),
+                ident,
+                Punct::RightSquare.into(),
+            ]);
+            let parser = buffer.parser();
+
+            let (_brackets, inner) = parser.group::<Bracket>().unwrap();
+
+            assert_eq!(inner.parse::<Ident>().unwrap(), "a");
+            assert!(inner.is_empty());
+            assert!(parser.is_empty());
+        }
+
+        #[test]
+        fn bracket_unclosed() {
+            let buffer = TokenBuffer::from_tokens(vec![Punct::LeftSquare.into()]);
+            let parser = buffer.parser();
+
+            assert_eq!(
+                parser.group::<Bracket>().err().map(|e| e.kind),
+                Some(ParseErrorKind::UnclosedDelimiter)
+            );
+        }
+
+        mod raw {
+            use super::*;
+
+            /// Tokenise `source` in its entirety.
+            fn tokens(source: &str) -> Vec<CommonToken> {
+                let buffer = TokenBuffer::<CommonToken>::new(source).unwrap();
+                let parser = buffer.parser();
+
+                std::iter::from_fn(|| (!parser.is_empty()).then(|| parser.parse().unwrap()))
+                    .collect()
+            }
+
+            #[rstest]
+            #[case("(a > 0) b", "a > 0")]
+            #[case(
+                "(length(name) < 10 and 'x)' != y) b",
+                "length(name) < 10 and 'x)' != y"
+            )]
+            #[case("((a)) b", "(a)")]
+            #[case("() b", "")]
+            fn captures_inner(#[case] source: &str, #[case] inner: &str) {
+                let buffer = TokenBuffer::<CommonToken>::new(source).unwrap();
+                let parser = buffer.parser();
+
+                let (_parens, raw) = parser.group_raw::<Parenthesis>().unwrap();
+
+                assert_eq!(raw, tokens(inner));
+                assert_eq!(parser.parse::<CommonToken>().unwrap(), tokens("b")[0]);
+                assert!(parser.is_empty());
+            }
+
+            #[test]
+            fn unclosed() {
+                let buffer = TokenBuffer::<CommonToken>::new("(a (b)").unwrap();
+                let parser = buffer.parser();
+
+                assert_eq!(
+                    parser.group_raw::<Parenthesis>().err().map(|e| e.kind),
+                    Some(ParseErrorKind::UnclosedDelimiter)
+                );
+            }
+        }
+    }
 }
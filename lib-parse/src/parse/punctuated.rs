@@ -3,7 +3,7 @@ use super::*;
 /// A sequence of `T` separated by `P`. Both an empty sequence, and a trailing `P` are
 /// representations, but whether they're accepted or not is determined by the parsing
 /// implementation.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Punctuated<T, P> {
     pairs: Vec<(T, P)>,
     last: Option<T>,
@@ -20,7 +20,9 @@ impl<T, P> Punctuated<T, P> {
 
     /// Parse `T` from the buffer, until the buffer is empty. An empty sequence and trailing `P`
     /// are both accepted.
-    pub fn parse_terminated<BaseToken>(input: BufferParser<'_, BaseToken>) -> Result<Self, String>
+    pub fn parse_terminated<BaseToken>(
+        input: BufferParser<'_, BaseToken>,
+    ) -> Result<Self, ParseError>
     where
         T: Parse<BaseToken>,
         P: Parse<BaseToken>,
@@ -31,8 +33,8 @@ impl<T, P> Punctuated<T, P> {
     /// Parse with a function until the buffer is empty. See [`Self::parse_terminated`].
     pub fn parse_terminated_with<BaseToken>(
         input: BufferParser<'_, BaseToken>,
-        parser: fn(BufferParser<'_, BaseToken>) -> Result<T, String>,
-    ) -> Result<Self, String>
+        parser: fn(BufferParser<'_, BaseToken>) -> Result<T, ParseError>,
+    ) -> Result<Self, ParseError>
     where
         P: Parse<BaseToken>,
     {
@@ -61,7 +63,7 @@ impl<T, P> Punctuated<T, P> {
     /// sequence or trailing punctuation is not allowed.
     pub fn parse_separated_non_empty<BaseToken>(
         input: BufferParser<'_, BaseToken>,
-    ) -> Result<Self, String>
+    ) -> Result<Self, ParseError>
     where
         T: Parse<BaseToken>,
         P: Token<BaseToken> + Parse<BaseToken>,
@@ -72,8 +74,8 @@ impl<T, P> Punctuated<T, P> {
     /// Parse with a function until there is no more `P`. See [`Self::parse_separated_non_empty`].
     pub fn parse_separated_non_empty_with<BaseToken>(
         input: BufferParser<'_, BaseToken>,
-        parser: fn(BufferParser<'_, BaseToken>) -> Result<T, String>,
-    ) -> Result<Self, String>
+        parser: fn(BufferParser<'_, BaseToken>) -> Result<T, ParseError>,
+    ) -> Result<Self, ParseError>
     where
         P: Token<BaseToken> + Parse<BaseToken>,
     {
@@ -94,6 +96,66 @@ impl<T, P> Punctuated<T, P> {
         Ok(punctuated)
     }
 
+    /// Append a value to the end of the sequence. Will panic if the sequence currently ends with a
+    /// value, as a `P` must separate each value.
+    pub fn push_value(&mut self, value: T) {
+        assert!(
+            self.last.is_none(),
+            "punctuation must be pushed before another value"
+        );
+
+        self.last = Some(value);
+    }
+
+    /// Append punctuation to the end of the sequence. Will panic if the sequence doesn't currently
+    /// end with a value.
+    pub fn push_punct(&mut self, punct: P) {
+        let value = self
+            .last
+            .take()
+            .expect("a value must be pushed before punctuation");
+
+        self.pairs.push((value, punct));
+    }
+
+    /// Append a value to the end of the sequence, inserting the default `P` beforehand if the
+    /// sequence currently ends with a value.
+    pub fn push(&mut self, value: T)
+    where
+        P: Default,
+    {
+        if self.last.is_some() {
+            self.push_punct(P::default());
+        }
+
+        self.push_value(value);
+    }
+
+    /// Produce an iterator of references to each `T`, in order.
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        Iter {
+            pairs: self.pairs.iter(),
+            last: self.last.iter(),
+        }
+    }
+
+    /// Produce an iterator of mutable references to each `T`, in order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.pairs
+            .iter_mut()
+            .map(|(value, _)| value)
+            .chain(self.last.as_mut())
+    }
+
+    /// Fetch a reference to the `T` at the provided index.
+    pub fn get(&self, i: usize) -> Option<&T> {
+        match self.pairs.get(i) {
+            Some((value, _)) => Some(value),
+            None if i == self.pairs.len() => self.last.as_ref(),
+            None => None,
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.pairs.len() + if self.last.is_some() { 1 } else { 0 }
     }
@@ -109,6 +171,14 @@ impl<T, P> Default for Punctuated<T, P> {
     }
 }
 
+impl<T, P: Default> FromIterator<T> for Punctuated<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut punctuated = Self::new();
+        iter.into_iter().for_each(|value| punctuated.push(value));
+        punctuated
+    }
+}
+
 impl<T, P> IntoIterator for Punctuated<T, P> {
     type Item = T;
     type IntoIter = std::vec::IntoIter<T>;
@@ -126,6 +196,33 @@ impl<T, P> IntoIterator for Punctuated<T, P> {
     }
 }
 
+impl<'a, T, P> IntoIterator for &'a Punctuated<T, P> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator of references to each `T` in a [`Punctuated`], created with [`Punctuated::iter`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a, T, P> {
+    pairs: std::slice::Iter<'a, (T, P)>,
+    last: std::option::Iter<'a, T>,
+}
+
+impl<'a, T, P> Iterator for Iter<'a, T, P> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.pairs.next() {
+            Some((value, _)) => Some(value),
+            None => self.last.next(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -141,17 +238,17 @@ mod test {
     #[derive(Clone)]
     struct Value;
     impl Parse<BaseToken> for Value {
-        fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, String> {
+        fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, ParseError> {
             match parser.parse()? {
                 BaseToken::Value => Ok(Value),
                 _ => Err("expected `value`".into()),
             }
         }
     }
-    #[derive(Clone)]
+    #[derive(Clone, Debug, Default)]
     struct Delimiter;
     impl Parse<BaseToken> for Delimiter {
-        fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, String> {
+        fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, ParseError> {
             match parser.parse()? {
                 BaseToken::Delimiter => Ok(Delimiter),
                 _ => Err("expected `delimiter`".into()),
@@ -181,7 +278,7 @@ mod test {
         #[case(vec![BaseToken::Value, BaseToken::Delimiter, BaseToken::Value], 2)]
         #[case(vec![BaseToken::Value, BaseToken::Delimiter, BaseToken::Value, BaseToken::Delimiter], 2)]
         fn success(#[case] tokens: Vec<BaseToken>, #[case] expected_len: usize) {
-            let buffer = TokenBuffer::new_with_tokens(tokens);
+            let buffer = TokenBuffer::from_tokens(tokens);
             let parser = buffer.parser();
 
             let result: Punctuated<Value, Delimiter> =
@@ -195,7 +292,7 @@ mod test {
         #[case(vec![BaseToken::Value, BaseToken::Value])]
         #[case(vec![BaseToken::Value, BaseToken::Delimiter, BaseToken::Other])]
         fn failure(#[case] tokens: Vec<BaseToken>) {
-            let buffer = TokenBuffer::new_with_tokens(tokens);
+            let buffer = TokenBuffer::from_tokens(tokens);
             let parser = buffer.parser();
 
             assert!(
@@ -220,7 +317,7 @@ mod test {
             #[case] expected_len: usize,
             #[case] expect_eof: bool,
         ) {
-            let buffer = TokenBuffer::new_with_tokens(tokens);
+            let buffer = TokenBuffer::from_tokens(tokens);
             let parser = buffer.parser();
 
             let result: Punctuated<Value, Delimiter> = parser
@@ -236,7 +333,7 @@ mod test {
         #[case(vec![BaseToken::Value, BaseToken::Delimiter, BaseToken::Other])]
         #[case(vec![BaseToken::Value, BaseToken::Delimiter, BaseToken::Delimiter])]
         fn failure(#[case] tokens: Vec<BaseToken>) {
-            let buffer = TokenBuffer::new_with_tokens(tokens);
+            let buffer = TokenBuffer::from_tokens(tokens);
             let parser = buffer.parser();
 
             assert!(
@@ -246,4 +343,132 @@ mod test {
             );
         }
     }
+
+    mod iter {
+        use super::*;
+
+        /// Create a sequence containing the provided values, with the final value optionally
+        /// followed by a trailing delimiter.
+        fn punctuated(values: &[u32], trailing: bool) -> Punctuated<u32, Delimiter> {
+            let (last, pairs) = match values.split_last() {
+                Some((last, pairs)) if !trailing => (Some(*last), pairs),
+                _ => (None, values),
+            };
+
+            Punctuated {
+                pairs: pairs.iter().map(|value| (*value, Delimiter)).collect(),
+                last,
+            }
+        }
+
+        #[rstest]
+        #[case(&[], false)]
+        #[case(&[1], false)]
+        #[case(&[1], true)]
+        #[case(&[1, 2, 3], false)]
+        #[case(&[1, 2, 3], true)]
+        fn matches_into_iter(#[case] values: &[u32], #[case] trailing: bool) {
+            let punctuated = punctuated(values, trailing);
+
+            assert_eq!(punctuated.iter().count(), punctuated.len());
+            assert!(punctuated.iter().eq(values));
+            assert!(punctuated.iter().copied().eq(punctuated.clone()));
+        }
+
+        #[rstest]
+        #[case(false)]
+        #[case(true)]
+        fn by_reference(#[case] trailing: bool) {
+            let punctuated = punctuated(&[1, 2, 3], trailing);
+
+            let mut values = Vec::new();
+            for value in &punctuated {
+                values.push(*value);
+            }
+            assert_eq!(values, [1, 2, 3]);
+
+            // Still usable after iterating.
+            assert_eq!(punctuated.len(), 3);
+            assert!(punctuated.into_iter().eq([1, 2, 3]));
+        }
+
+        #[rstest]
+        #[case(false)]
+        #[case(true)]
+        fn iter_mut(#[case] trailing: bool) {
+            let mut punctuated = punctuated(&[1, 2, 3], trailing);
+
+            punctuated.iter_mut().for_each(|value| *value *= 10);
+
+            assert!(punctuated.into_iter().eq([10, 20, 30]));
+        }
+
+        #[rstest]
+        #[case(false)]
+        #[case(true)]
+        fn get(#[case] trailing: bool) {
+            let punctuated = punctuated(&[1, 2, 3], trailing);
+
+            assert_eq!(punctuated.get(0), Some(&1));
+            assert_eq!(punctuated.get(2), Some(&3));
+            assert_eq!(punctuated.get(3), None);
+        }
+    }
+
+    mod push {
+        use super::*;
+
+        #[test]
+        fn push_value_and_punct() {
+            let mut punctuated = Punctuated::<u32, Delimiter>::new();
+
+            punctuated.push_value(1);
+            punctuated.push_punct(Delimiter);
+            punctuated.push_value(2);
+            assert_eq!(punctuated.len(), 2);
+
+            punctuated.push_punct(Delimiter);
+            assert_eq!(punctuated.len(), 2);
+
+            assert!(punctuated.into_iter().eq([1, 2]));
+        }
+
+        #[test]
+        #[should_panic]
+        fn value_after_value() {
+            let mut punctuated = Punctuated::<u32, Delimiter>::new();
+
+            punctuated.push_value(1);
+            punctuated.push_value(2);
+        }
+
+        #[test]
+        #[should_panic]
+        fn punct_on_empty() {
+            let mut punctuated = Punctuated::<u32, Delimiter>::new();
+
+            punctuated.push_punct(Delimiter);
+        }
+
+        #[test]
+        #[should_panic]
+        fn punct_after_punct() {
+            let mut punctuated = Punctuated::<u32, Delimiter>::new();
+
+            punctuated.push_value(1);
+            punctuated.push_punct(Delimiter);
+            punctuated.push_punct(Delimiter);
+        }
+
+        #[rstest]
+        #[case(&[])]
+        #[case(&[1])]
+        #[case(&[1, 2, 3])]
+        fn from_iter(#[case] values: &[u32]) {
+            let punctuated = values.iter().copied().collect::<Punctuated<_, Delimiter>>();
+
+            assert_eq!(punctuated.len(), values.len());
+            assert!(punctuated.into_iter().eq(values.iter().copied()));
+        }
+    }
 }
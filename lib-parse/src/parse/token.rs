@@ -5,8 +5,11 @@
 /// of the buffer, or another token that derives from it.
 ///
 /// The macro also accepts an optional identifier in square brackets, which it will use as the name
-/// of a function which will check if a string matches any of the tokens in a set (this is useful
-/// for testing for keywords in identifiers, for example).
+/// of a function which will check if a string matches any of the tokens in a set, ignoring ASCII
+/// case (this is useful for testing for keywords in identifiers, for example).
+///
+/// Tokens are matched using the [`MatchesToken`] implementation of the type, so it determines
+/// whether matching is case-sensitive.
 ///
 /// Finally the macro takes pairs of tokens and identifiers, where the token is the raw
 /// representation, and the identifier corresponds to a struct which will represent it.
@@ -43,21 +46,24 @@ macro_rules! define_tokens {
 
             impl<BaseToken> $crate::parse::Parse<BaseToken> for $name
             where
-                for<'s> $repr: $crate::parse::Parse<BaseToken> + ::std::cmp::PartialEq<&'s str>
+                $repr: $crate::parse::Parse<BaseToken> + $crate::parse::token::MatchesToken + ::std::fmt::Debug
             {
-                fn parse(parser: $crate::parse::BufferParser<'_, BaseToken>) -> Result<Self, String> {
+                fn parse(parser: $crate::parse::BufferParser<'_, BaseToken>) -> ::std::result::Result<Self, $crate::parse::ParseError> {
                     let repr = parser.parse::<$repr>()?;
-                    if repr == Self::TOKEN {
+                    if $crate::parse::token::MatchesToken::matches_token(&repr, Self::TOKEN) {
                         ::std::result::Result::Ok($name)
                     } else {
-                        ::std::result::Result::Err(format!("expected `{}`", Self::TOKEN))
+                        ::std::result::Result::Err($crate::parse::ParseError::expected(
+                            ::std::format!("`{}`", Self::TOKEN),
+                            repr,
+                        ))
                     }
                 }
             }
 
             impl<BaseToken> $crate::parse::Token<BaseToken> for $name
             where
-                $repr: $crate::parse::token::TokenRepr<BaseToken>,
+                $repr: $crate::parse::token::TokenRepr<BaseToken> + $crate::parse::token::MatchesToken,
                 BaseToken: ::std::clone::Clone
             {
                 fn peek(cursor: $crate::buffer::Cursor<'_, BaseToken>) -> bool {
@@ -65,7 +71,8 @@ macro_rules! define_tokens {
                         return false;
                     };
 
-                    <$repr as $crate::parse::token::TokenRepr<BaseToken>>::from_base(base).is_some()
+                    <$repr as $crate::parse::token::TokenRepr<BaseToken>>::from_base(base)
+                        .is_some_and(|repr| $crate::parse::token::MatchesToken::matches_token(&repr, Self::TOKEN))
                 }
 
                 fn display() -> &'static str {
@@ -81,10 +88,9 @@ macro_rules! define_tokens {
 
     ([is_fn] => $is_fn:ident { $($token:tt)* }) => {
         pub fn $is_fn(s: &str) -> bool {
-            match s {
-                $(stringify!($token))|* => true,
-                _ => false,
-            }
+            [$(stringify!($token)),*]
+                .iter()
+                .any(|token| token.eq_ignore_ascii_case(s))
         }
     };
 
@@ -121,38 +127,46 @@ impl<T> TokenRepr<T> for T {
     }
 }
 
+/// Helper trait to determine whether a representation corresponds to the raw representation of a
+/// token. Any types used as representation in the [`define_tokens`] macro must implement this
+/// trait, as the generated implementations use it to match tokens.
+pub trait MatchesToken {
+    /// Whether this value matches the raw representation of a token.
+    fn matches_token(&self, token: &str) -> bool;
+}
+
 #[cfg(test)]
 mod test {
     use derive_more::From;
 
     use crate::{
         buffer::TokenBuffer,
-        parse::{BufferParser, Parse},
+        parse::{BufferParser, Parse, ParseError, ParseErrorKind, Token},
     };
 
     use super::*;
 
-    #[derive(Clone)]
+    #[derive(Clone, Debug)]
     struct Ident(String);
-    impl<S: ?Sized + AsRef<str>> PartialEq<S> for Ident {
-        fn eq(&self, other: &S) -> bool {
-            self.0 == other.as_ref()
+    impl MatchesToken for Ident {
+        fn matches_token(&self, token: &str) -> bool {
+            self.0 == token
         }
     }
     impl Parse<BaseToken> for Ident {
-        fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, String> {
+        fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, ParseError> {
             Self::from_base(parser.parse::<BaseToken>()?).ok_or_else(|| "expected `ident`".into())
         }
     }
-    #[derive(Clone)]
+    #[derive(Clone, Debug)]
     struct Symbol(String);
-    impl<S: ?Sized + AsRef<str>> PartialEq<S> for Symbol {
-        fn eq(&self, other: &S) -> bool {
-            self.0 == other.as_ref()
+    impl MatchesToken for Symbol {
+        fn matches_token(&self, token: &str) -> bool {
+            self.0 == token
         }
     }
     impl Parse<BaseToken> for Symbol {
-        fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, String> {
+        fn parse(parser: BufferParser<'_, BaseToken>) -> Result<Self, ParseError> {
             Self::from_base(parser.parse::<BaseToken>()?).ok_or_else(|| "expected `symbol`".into())
         }
     }
@@ -193,7 +207,7 @@ mod test {
 
     #[test]
     fn parse_generated_tokens() {
-        let buffer = TokenBuffer::<BaseToken>::new_with_tokens(vec![
+        let buffer = TokenBuffer::<BaseToken>::from_tokens(vec![
             Ident("something".into()).into(),
             Ident("another".into()).into(),
             Symbol(",".into()).into(),
@@ -205,9 +219,52 @@ mod test {
         let _comma = parser.parse::<Token![,]>().unwrap();
     }
 
+    #[test]
+    fn parse_generated_token_mismatch() {
+        let buffer = TokenBuffer::<BaseToken>::from_tokens(vec![
+            Ident("another".into()).into(),
+            Symbol(";".into()).into(),
+        ]);
+        let parser = buffer.parser();
+
+        assert_eq!(
+            parser.parse::<Token![something]>().unwrap_err().kind,
+            ParseErrorKind::Expected {
+                expected: "`something`".to_string(),
+                found: r#"Ident("another")"#.to_string(),
+            }
+        );
+        assert_eq!(
+            parser.parse::<Token![,]>().unwrap_err().kind,
+            ParseErrorKind::Expected {
+                expected: "`,`".to_string(),
+                found: r#"Symbol(";")"#.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn peek_generated_tokens() {
+        let buffer = TokenBuffer::<BaseToken>::from_tokens(vec![
+            Ident("something".into()).into(),
+            Symbol(",".into()).into(),
+        ]);
+        let cursor = buffer.cursor();
+
+        assert!(<Token![something]>::peek(cursor));
+        assert!(!<Token![another]>::peek(cursor));
+        assert!(!<Token![,]>::peek(cursor));
+
+        let cursor = cursor.next_cursor();
+        assert!(<Token![,]>::peek(cursor));
+        assert!(!<Token![;]>::peek(cursor));
+        assert!(!<Token![something]>::peek(cursor));
+    }
+
     #[test]
     fn is_fn() {
         assert!(is_keyword("something"));
+        assert!(is_keyword("SomeThing"));
         assert!(!is_keyword("nothing"));
         assert!(!is_keyword(","));
     }
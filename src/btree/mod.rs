@@ -1,11 +1,13 @@
-use std::iter;
+use std::{collections::HashSet, fmt::Display, iter, ops::RangeInclusive};
+
+use derive_more::From;
+use thiserror::Error;
 
 use page::PageType;
-use zerocopy::{FromBytes, big_endian::*};
 
 use self::{
-    page::{Page, PageExt, Table},
-    payload::Payload,
+    page::{InteriorPage, Page, PageError, PageExt, Table},
+    payload::{Payload, PayloadError},
 };
 
 use crate::{ctx::Ctx, disk::var_int::VarInt};
@@ -13,54 +15,187 @@ use crate::{ctx::Ctx, disk::var_int::VarInt};
 pub mod page;
 pub mod payload;
 
-/// Traverse a B-Tree from a root page, producing an iterator of cells.
+/// Maximum depth of a B-Tree, matching the limit SQLite places on its cursors.
+pub const MAX_DEPTH: usize = 20;
+
+/// Traverse a B-Tree from a root page, producing an iterator of cells. Will panic if a page is
+/// invalid, see [`try_traverse`] to handle invalid pages.
+#[allow(unused)]
 pub fn traverse<T: Traversable>(ctx: Ctx, page: Page<T>) -> impl Iterator<Item = T::Cell> {
-    let mut stack = vec![page];
+    traverse_with_max_depth(ctx, page, MAX_DEPTH)
+}
+
+/// Traverse a B-Tree from a root page, producing an iterator of cells, or an error for each page
+/// which is invalid or can't be read. The cells of such a page aren't produced, but traversal
+/// continues with the remaining pages.
+pub fn try_traverse<T: Traversable>(
+    ctx: Ctx,
+    page: Page<T>,
+) -> impl Iterator<Item = Result<T::Cell, TraverseError>> {
+    try_traverse_children(ctx, page, MAX_DEPTH, false, all_children)
+}
+
+/// Traverse a B-Tree from a root page, producing an iterator of cells. Pages deeper than
+/// `max_depth` (where the root page is at depth `0`) are not visited.
+///
+/// Child pointers to a page which has already been visited are ignored, so a corrupt B-Tree
+/// containing a cycle will still terminate.
+#[allow(unused)]
+pub fn traverse_with_max_depth<T: Traversable>(
+    ctx: Ctx,
+    page: Page<T>,
+    max_depth: usize,
+) -> impl Iterator<Item = T::Cell> {
+    traverse_children(ctx, page, max_depth, false, all_children)
+}
+
+/// Traverse a B-Tree from a root page, producing an iterator of cells in reverse order. The
+/// right-most leaf is visited first, so cells are produced without reading the entire tree.
+#[allow(unused)]
+pub fn traverse_rev<T: Traversable>(ctx: Ctx, page: Page<T>) -> impl Iterator<Item = T::Cell> {
+    traverse_children(ctx, page, MAX_DEPTH, true, all_children)
+}
+
+/// Every child of an interior page, in order.
+fn all_children<T: PageType>(interior_page: &InteriorPage<T>) -> Result<Vec<u32>, PageError> {
+    Ok(interior_page
+        .left_pointers()?
+        .chain(iter::once(interior_page.right_pointer))
+        .collect())
+}
+
+/// Traverse a table B-Tree from a root page, producing only the cells with a row id within
+/// `range`. Subtrees which cannot contain a row id within `range` are not visited.
+#[allow(unused)]
+pub fn scan_range(
+    ctx: Ctx,
+    page: Page<Table>,
+    range: RangeInclusive<i64>,
+) -> impl Iterator<Item = TableCell> {
+    let (start, end) = (*range.start(), *range.end());
+
+    traverse_children(ctx, page, MAX_DEPTH, false, move |interior_page| {
+        // Each child contains row ids greater than the previous cell's row id, up to and including
+        // its own row id. The right pointer contains everything beyond the last cell.
+        let mut lower = None;
+
+        Ok(interior_page
+            .cells()?
+            .map(|cell| (cell.left_pointer, Some(cell.row_id)))
+            .chain(iter::once((interior_page.right_pointer, None)))
+            .filter(|(_, upper)| {
+                let overlaps = lower.is_none_or(|lower| lower < end)
+                    && upper.is_none_or(|upper| upper >= start);
+                lower = *upper;
+                overlaps
+            })
+            .map(|(ptr, _)| ptr)
+            .collect())
+    })
+    .skip_while(move |cell| cell.row_id < RowId::from(start))
+    .take_while(move |cell| cell.row_id <= RowId::from(end))
+}
+
+/// Traverse a B-Tree like [`try_traverse_children`], panicking if a page is invalid.
+fn traverse_children<T: Traversable>(
+    ctx: Ctx,
+    page: Page<T>,
+    max_depth: usize,
+    reverse: bool,
+    children: impl Fn(&InteriorPage<T>) -> Result<Vec<u32>, PageError>,
+) -> impl Iterator<Item = T::Cell> {
+    try_traverse_children(ctx, page, max_depth, reverse, children)
+        .map(|cell| cell.expect("valid page"))
+}
+
+/// Traverse a B-Tree from a root page, only visiting the child pages of each interior page
+/// produced by `children`. See [`traverse_with_max_depth`] for the handling of depth and cycles.
+///
+/// If `reverse` is set, the children of each interior page and the cells of each leaf page are
+/// visited from right to left.
+fn try_traverse_children<T: Traversable>(
+    ctx: Ctx,
+    page: Page<T>,
+    max_depth: usize,
+    reverse: bool,
+    children: impl Fn(&InteriorPage<T>) -> Result<Vec<u32>, PageError>,
+) -> impl Iterator<Item = Result<T::Cell, TraverseError>> {
+    let mut stack = vec![(Ok(page), 0)];
+    let mut visited = HashSet::new();
     let mut leaf_iter = None;
 
     std::iter::from_fn(move || {
         match &mut leaf_iter {
             None => {
-                match stack.pop()? {
+                let (page, depth) = stack.pop()?;
+                let page = match page {
+                    Ok(page) => page,
+                    Err(e) => return Some(Some(Err(e))),
+                };
+
+                match page {
                     Page::Leaf(leaf_page) => {
                         // Buffer all of the pointers into a vec, so they can be referred to from
                         // the iterator.
-                        let ptrs = leaf_page.cell_content_pointers().collect::<Vec<_>>();
+                        let mut ptrs = match leaf_page.cell_content_pointers() {
+                            Ok(ptrs) => ptrs.collect::<Vec<_>>(),
+                            Err(e) => return Some(Some(Err(e.into()))),
+                        };
+                        if reverse {
+                            ptrs.reverse();
+                        }
                         let ctx = ctx.clone();
 
                         leaf_iter = Some(ptrs.into_iter().map(move |ptr| {
                             let content = &leaf_page.cell_content_area()[ptr..];
 
-                            T::cell_from_content(
+                            Ok(T::cell_from_content(
                                 ctx.clone(),
                                 content,
                                 leaf_page.clone().to_page(),
                                 ptr,
-                            )
+                            )?)
                         }));
                     }
+                    Page::Interior(_) if depth >= max_depth => {}
                     Page::Interior(interior_page) => {
                         // Capture the current end of the array, so later pages don't jump ahead.
                         let insert_point = stack.len();
 
-                        interior_page
-                            .cell_content_pointers()
-                            .map({
-                                let cell_content = interior_page.cell_content_area();
-                                |ptr| &cell_content[ptr..]
-                            })
-                            .map(|cell_content| {
-                                let (left_pointer, _cell_content) =
-                                    U32::read_from_prefix(cell_content).unwrap();
-                                left_pointer.get()
-                            })
-                            .chain(iter::once(interior_page.right_pointer))
-                            .for_each(|ptr| {
-                                stack.insert(
-                                    insert_point,
-                                    Page::from_buffer(ctx.pager.get_page(ptr)),
-                                );
-                            });
+                        let mut children = match children(&interior_page) {
+                            Ok(children) => children,
+                            Err(e) => return Some(Some(Err(e.into()))),
+                        };
+                        children.retain(|ptr| visited.insert(*ptr));
+                        if reverse {
+                            children.reverse();
+                        }
+
+                        // Page ids begin at 1, so a zero pointer can't be read.
+                        let readable = children
+                            .iter()
+                            .copied()
+                            .filter(|ptr| *ptr != 0)
+                            .collect::<Vec<_>>();
+
+                        // Sibling pages are often stored consecutively, so read them together.
+                        // Any failure will be reported when the page is read individually.
+                        let _ = ctx.pager.prefetch(&readable);
+
+                        children.into_iter().for_each(|ptr| {
+                            let page = (ptr != 0)
+                                .then(|| ctx.pager.get_page(ptr).ok())
+                                .flatten()
+                                .ok_or(TraverseError::Unreadable(ptr))
+                                .and_then(|buffer| {
+                                    Ok(Page::try_from_buffer(
+                                        buffer,
+                                        ctx.header.page_size() as usize,
+                                    )?)
+                                });
+
+                            stack.insert(insert_point, (page, depth + 1));
+                        });
                     }
                 }
             }
@@ -77,6 +212,17 @@ pub fn traverse<T: Traversable>(ctx: Ctx, page: Page<T>) -> impl Iterator<Item =
     .flatten()
 }
 
+/// Error produced whilst traversing a B-Tree.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum TraverseError {
+    #[error("page {0} couldn't be read")]
+    Unreadable(u32),
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error(transparent)]
+    Payload(#[from] PayloadError),
+}
+
 pub trait Traversable: PageType {
     type Cell;
 
@@ -87,11 +233,27 @@ pub trait Traversable: PageType {
         content: &[u8],
         page: Page<Self>,
         cell_offset: usize,
-    ) -> Self::Cell;
+    ) -> Result<Self::Cell, PayloadError>;
+}
+
+/// Key of a row within a table B-Tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, From)]
+pub struct RowId(i64);
+
+impl From<RowId> for i64 {
+    fn from(row_id: RowId) -> Self {
+        row_id.0
+    }
+}
+
+impl Display for RowId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
 }
 
 pub struct TableCell {
-    pub row_id: i64,
+    pub row_id: RowId,
     pub payload: Payload<Table>,
 }
 
@@ -103,20 +265,180 @@ impl Traversable for Table {
         content: &[u8],
         page: Page<Self>,
         cell_offset: usize,
-    ) -> Self::Cell {
+    ) -> Result<Self::Cell, PayloadError> {
         let (payload_size, buf) = VarInt::from_buffer(content);
         let (row_id, payload) = VarInt::from_buffer(buf);
 
         let payload_offset = cell_offset + (content.len() - payload.len());
 
-        TableCell {
-            row_id: *row_id,
+        Ok(TableCell {
+            row_id: RowId::from(*row_id),
             payload: Payload::from_buf_with_payload_size(
                 ctx,
                 page,
                 payload_offset,
                 *payload_size as usize,
-            ),
-        }
+            )?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    use rstest::rstest;
+
+    /// Root page of the `items` table within `btree.db`.
+    const ITEMS_ROOT_PAGE: u32 = 2;
+
+    /// Open `btree.db`, applying `patch` to the bytes of the file.
+    fn open(patch: impl FnOnce(&mut [u8])) -> Ctx {
+        let mut bytes = std::fs::read("fixtures/btree.db").unwrap();
+        patch(&mut bytes);
+        Ctx::new(Cursor::new(bytes))
+    }
+
+    /// Row ids produced by traversing the `items` table.
+    fn row_ids(ctx: Ctx, max_depth: usize) -> Vec<i64> {
+        let page = Page::<Table>::from_buffer(ctx.pager.get_page(ITEMS_ROOT_PAGE).unwrap());
+
+        traverse_with_max_depth(ctx, page, max_depth)
+            .map(|cell| i64::from(cell.row_id))
+            .collect()
+    }
+
+    #[test]
+    fn full_traversal() {
+        assert_eq!(
+            row_ids(open(|_| {}), MAX_DEPTH),
+            (1..=1000).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn cyclic_right_pointer() {
+        let ctx = open(|bytes| {
+            // Point the right pointer of the root interior page back to itself.
+            let page_offset = 512 * (ITEMS_ROOT_PAGE as usize - 1);
+            bytes[page_offset + 8..page_offset + 12]
+                .copy_from_slice(&ITEMS_ROOT_PAGE.to_be_bytes());
+        });
+
+        let row_ids = row_ids(ctx, MAX_DEPTH);
+
+        // Rows beneath the original right pointer are lost, but the remainder are produced once.
+        assert!(!row_ids.is_empty());
+        assert!(row_ids.len() < 1000);
+        assert!(row_ids.is_sorted_by(|a, b| a < b));
+    }
+
+    #[rstest]
+    #[case::past_end_of_file(9999)]
+    #[case::zero(0)]
+    fn try_traverse_unreadable_child(#[case] right_pointer: u32) {
+        let ctx = open(|bytes| {
+            let page_offset = 512 * (ITEMS_ROOT_PAGE as usize - 1);
+            bytes[page_offset + 8..page_offset + 12].copy_from_slice(&right_pointer.to_be_bytes());
+        });
+        let page = Page::<Table>::from_buffer(ctx.pager.get_page(ITEMS_ROOT_PAGE).unwrap());
+
+        let (cells, errors) = try_traverse(ctx, page).partition::<Vec<_>, _>(Result::is_ok);
+
+        // The cells beneath the other children are still produced.
+        assert!(!cells.is_empty());
+        assert_eq!(
+            errors.into_iter().map(Result::err).collect::<Vec<_>>(),
+            [Some(TraverseError::Unreadable(right_pointer))]
+        );
+    }
+
+    #[rstest]
+    #[case(1..=1000)]
+    #[case(1..=1)]
+    #[case(1000..=1000)]
+    #[case(250..=260)]
+    #[case(499..=731)]
+    #[case(-5..=3)]
+    #[case(998..=2000)]
+    #[case(2000..=3000)]
+    #[case(RangeInclusive::new(10, 5))]
+    #[case(i64::MIN..=i64::MAX)]
+    fn scan_range(#[case] range: RangeInclusive<i64>) {
+        let ctx = open(|_| {});
+        let page = Page::<Table>::from_buffer(ctx.pager.get_page(ITEMS_ROOT_PAGE).unwrap());
+
+        let expected = row_ids(ctx.clone(), MAX_DEPTH)
+            .into_iter()
+            .filter(|row_id| range.contains(row_id))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            super::scan_range(ctx, page, range)
+                .map(|cell| i64::from(cell.row_id))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn scan_range_prunes() {
+        let ctx = open(|bytes| {
+            // Corrupt the flag of the right-most child of the root page, so visiting it will panic.
+            let page_offset = 512 * (ITEMS_ROOT_PAGE as usize - 1);
+            let right_pointer =
+                u32::from_be_bytes(bytes[page_offset + 8..page_offset + 12].try_into().unwrap());
+            bytes[512 * (right_pointer as usize - 1)] = 0xff;
+        });
+        let page = Page::<Table>::from_buffer(ctx.pager.get_page(ITEMS_ROOT_PAGE).unwrap());
+
+        assert_eq!(
+            super::scan_range(ctx, page, 1..=10)
+                .map(|cell| i64::from(cell.row_id))
+                .collect::<Vec<_>>(),
+            (1..=10).collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[case::table("btree.db", ITEMS_ROOT_PAGE)]
+    #[case::schema("btree.db", 1)]
+    #[case::overflow("overflow.db", 2)]
+    fn traverse_rev(#[case] fixture: &str, #[case] root_page: u32) {
+        let ctx = Ctx::new(std::fs::File::open(format!("fixtures/{fixture}")).unwrap());
+        let page = || Page::<Table>::from_buffer(ctx.pager.get_page(root_page).unwrap());
+
+        let mut expected = traverse(ctx.clone(), page())
+            .map(|cell| (i64::from(cell.row_id), cell.payload.length))
+            .collect::<Vec<_>>();
+        expected.reverse();
+
+        assert_eq!(
+            super::traverse_rev(ctx.clone(), page())
+                .map(|cell| (i64::from(cell.row_id), cell.payload.length))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(1, 2)]
+    #[case(-1, 0)]
+    #[case(i64::MIN, i64::MAX)]
+    #[case(5, 5)]
+    #[case(7, -7)]
+    fn row_id_ordering(#[case] a: i64, #[case] b: i64) {
+        assert_eq!(RowId::from(a).cmp(&RowId::from(b)), a.cmp(&b));
+        assert_eq!(i64::from(RowId::from(a)), a);
+        assert_eq!(RowId::from(a).to_string(), a.to_string());
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(1, 1000)]
+    fn depth_limit(#[case] max_depth: usize, #[case] expected_rows: usize) {
+        assert_eq!(row_ids(open(|_| {}), max_depth).len(), expected_rows);
     }
 }
@@ -0,0 +1,406 @@
+//! Builder for crafting B-Tree pages in memory, so page parsing can be tested without
+//! hand-assembling bytes.
+
+use zerocopy::{IntoBytes, big_endian::*};
+
+use super::{PageKindFlag, PageTypeFlag};
+use crate::{ctx::pager::PageBuffer, disk::var_int::VarInt};
+
+/// Minimum size of a freeblock, which must be large enough to hold its own header.
+const MIN_FREEBLOCK_SIZE: usize = 4;
+
+/// Builds a single page, laying out the header, cell pointer array, cells and freeblocks.
+///
+/// Cells are stored from the end of the page in the order that they are added, with any
+/// freeblocks placed immediately before them. Payloads are stored entirely on the page, so they
+/// must be small enough to not require an overflow page.
+#[derive(Clone, Debug)]
+pub struct PageBuilder {
+    page_size: usize,
+    type_flag: PageTypeFlag,
+    kind_flag: PageKindFlag,
+    right_pointer: u32,
+    cells: Vec<Vec<u8>>,
+    freeblocks: Vec<usize>,
+}
+
+impl PageBuilder {
+    /// Create a builder for an empty page of the provided type and kind.
+    pub fn new(type_flag: PageTypeFlag, kind_flag: PageKindFlag, page_size: usize) -> Self {
+        Self {
+            page_size,
+            type_flag,
+            kind_flag,
+            right_pointer: 0,
+            cells: Vec::new(),
+            freeblocks: Vec::new(),
+        }
+    }
+
+    pub fn table_leaf(page_size: usize) -> Self {
+        Self::new(PageTypeFlag::Table, PageKindFlag::Leaf, page_size)
+    }
+
+    pub fn table_interior(page_size: usize, right_pointer: u32) -> Self {
+        Self::new(PageTypeFlag::Table, PageKindFlag::Interior, page_size)
+            .right_pointer(right_pointer)
+    }
+
+    pub fn index_leaf(page_size: usize) -> Self {
+        Self::new(PageTypeFlag::Index, PageKindFlag::Leaf, page_size)
+    }
+
+    pub fn index_interior(page_size: usize, right_pointer: u32) -> Self {
+        Self::new(PageTypeFlag::Index, PageKindFlag::Interior, page_size)
+            .right_pointer(right_pointer)
+    }
+
+    /// Set the right-most pointer of an interior page.
+    pub fn right_pointer(mut self, right_pointer: u32) -> Self {
+        assert_eq!(
+            self.kind_flag,
+            PageKindFlag::Interior,
+            "only interior pages have a right pointer"
+        );
+
+        self.right_pointer = right_pointer;
+        self
+    }
+
+    /// Add a cell to a table leaf page.
+    pub fn table_leaf_cell(self, row_id: i64, payload: &[u8]) -> Self {
+        self.assert_page(PageTypeFlag::Table, PageKindFlag::Leaf);
+
+        let mut cell = VarInt::to_bytes(VarInt::new(payload.len() as i64));
+        cell.extend(VarInt::to_bytes(VarInt::new(row_id)));
+        cell.extend(payload);
+
+        self.cell(cell)
+    }
+
+    /// Add a cell to a table interior page.
+    pub fn table_interior_cell(self, left_pointer: u32, row_id: i64) -> Self {
+        self.assert_page(PageTypeFlag::Table, PageKindFlag::Interior);
+
+        let mut cell = left_pointer.to_be_bytes().to_vec();
+        cell.extend(VarInt::to_bytes(VarInt::new(row_id)));
+
+        self.cell(cell)
+    }
+
+    /// Add a cell to an index leaf page.
+    pub fn index_leaf_cell(self, payload: &[u8]) -> Self {
+        self.assert_page(PageTypeFlag::Index, PageKindFlag::Leaf);
+
+        let mut cell = VarInt::to_bytes(VarInt::new(payload.len() as i64));
+        cell.extend(payload);
+
+        self.cell(cell)
+    }
+
+    /// Add a cell to an index interior page.
+    pub fn index_interior_cell(self, left_pointer: u32, payload: &[u8]) -> Self {
+        self.assert_page(PageTypeFlag::Index, PageKindFlag::Interior);
+
+        let mut cell = left_pointer.to_be_bytes().to_vec();
+        cell.extend(VarInt::to_bytes(VarInt::new(payload.len() as i64)));
+        cell.extend(payload);
+
+        self.cell(cell)
+    }
+
+    /// Add a cell with the provided raw content.
+    pub fn cell(mut self, content: impl Into<Vec<u8>>) -> Self {
+        self.cells.push(content.into());
+        self
+    }
+
+    /// Add a freeblock of `size` bytes (including its 4 byte header).
+    pub fn freeblock(mut self, size: usize) -> Self {
+        assert!(
+            size >= MIN_FREEBLOCK_SIZE,
+            "freeblock must be at least {MIN_FREEBLOCK_SIZE} bytes"
+        );
+
+        self.freeblocks.push(size);
+        self
+    }
+
+    /// Produce the bytes of the page.
+    pub fn build(&self) -> Vec<u8> {
+        let mut page = vec![0; self.page_size];
+
+        let header_length = match self.kind_flag {
+            PageKindFlag::Leaf => 8,
+            PageKindFlag::Interior => 12,
+        };
+        let pointers_end = header_length + self.cells.len() * size_of::<U16>();
+
+        // Fill the cell content area from the end of the page.
+        let mut content_start = self.page_size;
+        let mut place = |length: usize| {
+            content_start = content_start
+                .checked_sub(length)
+                .filter(|start| *start >= pointers_end)
+                .expect("page content to fit within the page");
+            content_start
+        };
+
+        let cell_offsets = self
+            .cells
+            .iter()
+            .map(|cell| {
+                let offset = place(cell.len());
+                page[offset..offset + cell.len()].copy_from_slice(cell);
+                offset
+            })
+            .collect::<Vec<_>>();
+
+        // Freeblocks are placed below the cells at descending offsets. Each is linked to the
+        // previously placed freeblock, so the list is in ascending order from the lowest.
+        let freeblock_offsets = self
+            .freeblocks
+            .iter()
+            .map(|size| (place(*size), *size))
+            .collect::<Vec<_>>();
+
+        let mut next_freeblock = 0;
+        for (offset, size) in &freeblock_offsets {
+            page[*offset..*offset + 2].copy_from_slice(U16::new(next_freeblock).as_bytes());
+            page[*offset + 2..*offset + 4].copy_from_slice(U16::new(*size as u16).as_bytes());
+            next_freeblock = *offset as u16;
+        }
+
+        // Cell pointer array.
+        for (i, offset) in cell_offsets.iter().enumerate() {
+            let start = header_length + i * size_of::<U16>();
+            page[start..start + 2].copy_from_slice(U16::new(*offset as u16).as_bytes());
+        }
+
+        // Header.
+        page[0] = self.flag();
+        page[1..3].copy_from_slice(U16::new(next_freeblock).as_bytes());
+        page[3..5].copy_from_slice(U16::new(self.cells.len() as u16).as_bytes());
+        // A cell content area offset of `0` is interpreted as 65536.
+        page[5..7].copy_from_slice(U16::new(content_start as u16).as_bytes());
+        if self.kind_flag == PageKindFlag::Interior {
+            page[8..12].copy_from_slice(U32::new(self.right_pointer).as_bytes());
+        }
+
+        page
+    }
+
+    /// Produce the page as a [`PageBuffer`], ready to be parsed.
+    pub fn build_buffer(&self) -> PageBuffer {
+        PageBuffer::from_bytes(self.build())
+    }
+
+    /// Value of the page flag for the configured type and kind.
+    fn flag(&self) -> u8 {
+        match self.kind_flag {
+            PageKindFlag::Leaf => self.type_flag.flag() | 0b1000,
+            PageKindFlag::Interior => self.type_flag.flag(),
+        }
+    }
+
+    fn assert_page(&self, type_flag: PageTypeFlag, kind_flag: PageKindFlag) {
+        assert_eq!(
+            (&self.type_flag, &self.kind_flag),
+            (&type_flag, &kind_flag),
+            "cell does not match page"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    use crate::btree::page::{AnyPage, Index, Page, PageExt, Table};
+
+    const PAGE_SIZE: usize = 512;
+
+    /// Decode the content of each cell in a leaf table page, as `(row_id, payload)`.
+    fn table_leaf_cells(page: &Page<Table>) -> Vec<(i64, Vec<u8>)> {
+        let content = page.cell_content_area();
+
+        page.cell_content_pointers()
+            .unwrap()
+            .map(|ptr| {
+                let (payload_size, buf) = VarInt::from_buffer(&content[ptr..]);
+                let (row_id, buf) = VarInt::from_buffer(buf);
+
+                (*row_id, buf[..*payload_size as usize].to_vec())
+            })
+            .collect()
+    }
+
+    /// Decode the payload of each cell in an index page.
+    fn index_payloads(page: &Page<Index>) -> Vec<Vec<u8>> {
+        let content = page.cell_content_area();
+        let prefix = match page {
+            Page::Leaf(_) => 0,
+            Page::Interior(_) => size_of::<U32>(),
+        };
+
+        page.cell_content_pointers()
+            .unwrap()
+            .map(|ptr| {
+                let (payload_size, buf) = VarInt::from_buffer(&content[ptr + prefix..]);
+
+                buf[..*payload_size as usize].to_vec()
+            })
+            .collect()
+    }
+
+    /// Walk the freeblock list, producing the offset and size of each freeblock.
+    fn freeblocks(page: &[u8], first: u16) -> Vec<(u16, u16)> {
+        std::iter::successors((first != 0).then_some(first), |offset| {
+            let offset = *offset as usize;
+            let next = u16::from_be_bytes([page[offset], page[offset + 1]]);
+            (next != 0).then_some(next)
+        })
+        .map(|offset| {
+            let offset_usize = offset as usize;
+            (
+                offset,
+                u16::from_be_bytes([page[offset_usize + 2], page[offset_usize + 3]]),
+            )
+        })
+        .collect()
+    }
+
+    #[rstest]
+    #[case::table_leaf(
+        PageBuilder::table_leaf(PAGE_SIZE),
+        PageTypeFlag::Table,
+        PageKindFlag::Leaf
+    )]
+    #[case::table_interior(
+        PageBuilder::table_interior(PAGE_SIZE, 7),
+        PageTypeFlag::Table,
+        PageKindFlag::Interior
+    )]
+    #[case::index_leaf(
+        PageBuilder::index_leaf(PAGE_SIZE),
+        PageTypeFlag::Index,
+        PageKindFlag::Leaf
+    )]
+    #[case::index_interior(
+        PageBuilder::index_interior(PAGE_SIZE, 7),
+        PageTypeFlag::Index,
+        PageKindFlag::Interior
+    )]
+    fn empty(
+        #[case] builder: PageBuilder,
+        #[case] type_flag: PageTypeFlag,
+        #[case] kind_flag: PageKindFlag,
+    ) {
+        let bytes = builder.build();
+        assert_eq!(bytes.len(), PAGE_SIZE);
+
+        let page = AnyPage::try_from_buffer(PageBuffer::from_bytes(bytes), PAGE_SIZE).unwrap();
+
+        assert_eq!(page.flag().type_flag, type_flag);
+        assert_eq!(page.flag().kind_flag, kind_flag);
+        assert_eq!(page.cell_count(), 0);
+    }
+
+    #[test]
+    fn table_leaf() {
+        let Page::Leaf(page) = Page::<Table>::from_buffer(
+            PageBuilder::table_leaf(PAGE_SIZE)
+                .table_leaf_cell(1, b"one")
+                .table_leaf_cell(2, b"")
+                .table_leaf_cell(300, &[0xab; 200])
+                .build_buffer(),
+        ) else {
+            panic!("expected leaf page");
+        };
+
+        assert_eq!(page.cell_count, 3);
+        assert_eq!(page.first_freeblock, None);
+        assert_eq!(
+            table_leaf_cells(&page.to_page()),
+            [
+                (1, b"one".to_vec()),
+                (2, Vec::new()),
+                (300, vec![0xab; 200])
+            ]
+        );
+    }
+
+    #[test]
+    fn table_interior() {
+        let Page::Interior(page) = Page::<Table>::from_buffer(
+            PageBuilder::table_interior(PAGE_SIZE, 9)
+                .table_interior_cell(3, 10)
+                .table_interior_cell(4, 200)
+                .build_buffer(),
+        ) else {
+            panic!("expected interior page");
+        };
+
+        assert_eq!(page.right_pointer, 9);
+        assert_eq!(
+            page.cells()
+                .unwrap()
+                .map(|cell| (cell.left_pointer, cell.row_id))
+                .collect::<Vec<_>>(),
+            [(3, 10), (4, 200)]
+        );
+        assert_eq!(page.left_pointers().unwrap().collect::<Vec<_>>(), [3, 4]);
+    }
+
+    #[rstest]
+    #[case::leaf(PageBuilder::index_leaf(PAGE_SIZE).index_leaf_cell(b"a").index_leaf_cell(b"bc"))]
+    #[case::interior(
+        PageBuilder::index_interior(PAGE_SIZE, 5)
+            .index_interior_cell(2, b"a")
+            .index_interior_cell(3, b"bc")
+    )]
+    fn index(#[case] builder: PageBuilder) {
+        let page = Page::<Index>::from_buffer(builder.build_buffer());
+
+        assert_eq!(index_payloads(&page), [b"a".to_vec(), b"bc".to_vec()]);
+    }
+
+    #[test]
+    fn freeblocks_linked_in_order() {
+        let bytes = PageBuilder::table_leaf(PAGE_SIZE)
+            .table_leaf_cell(1, b"one")
+            .freeblock(10)
+            .freeblock(4)
+            .build();
+
+        let page = Page::<Table>::from_buffer(PageBuffer::from_bytes(bytes.clone()));
+        let first = page.first_freeblock.unwrap().get();
+
+        let blocks = freeblocks(&bytes, first);
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks.is_sorted_by_key(|(offset, _)| *offset));
+        assert_eq!(blocks.iter().map(|(_, size)| *size).sum::<u16>(), 10 + 4);
+
+        // The freeblocks are within the cell content area, and don't overlap the cell.
+        let content_start = PAGE_SIZE - page.cell_content_area().len();
+        assert_eq!(blocks[0].0 as usize, content_start);
+        assert_eq!(table_leaf_cells(&page), [(1, b"one".to_vec())]);
+    }
+
+    #[test]
+    #[should_panic(expected = "page content to fit within the page")]
+    fn overfull() {
+        PageBuilder::table_leaf(PAGE_SIZE)
+            .table_leaf_cell(1, &[0; 300])
+            .table_leaf_cell(2, &[0; 300])
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "cell does not match page")]
+    fn mismatched_cell() {
+        let _ = PageBuilder::index_leaf(PAGE_SIZE).table_leaf_cell(1, b"");
+    }
+}
@@ -1,9 +1,12 @@
+#[cfg(test)]
+pub mod builder;
 mod disk;
 mod page_kind;
 mod page_type;
 
 use std::{marker::PhantomData, num::NonZero, ops::Deref};
 
+use thiserror::Error;
 use zerocopy::{FromBytes, big_endian::*};
 
 pub use self::{
@@ -44,6 +47,95 @@ impl<T: PageType> PageExt<T> for Page<T> {
     }
 }
 
+impl<T: PageType> Page<T> {
+    /// Create a new page from the provided buffer, validating that the buffer contains a full
+    /// page of `page_size` bytes, and that the page flag is valid for a page of type `T`.
+    pub fn try_from_buffer(buffer: PageBuffer, page_size: usize) -> Result<Self, PageError> {
+        check_size(&buffer, page_size)?;
+
+        PageFlag::new(buffer[0])
+            .filter(|flag| flag.type_flag.is::<T>())
+            .ok_or(PageError::Flag(buffer[0]))?;
+
+        Ok(Self::from_buffer(buffer))
+    }
+}
+
+/// A B-Tree page of any type, where the type is determined from the page flag.
+#[allow(unused)]
+#[derive(Clone, Debug)]
+pub enum AnyPage {
+    Table(Page<Table>),
+    Index(Page<Index>),
+}
+
+#[allow(unused)]
+impl AnyPage {
+    /// Create a new page from the provided buffer, using the page flag to select the page type.
+    pub fn from_buffer(buffer: PageBuffer) -> Self {
+        let flag = PageFlag::new(buffer[0]).expect("valid page flag");
+
+        match flag.type_flag {
+            PageTypeFlag::Table => Self::Table(Page::from_buffer(buffer)),
+            PageTypeFlag::Index => Self::Index(Page::from_buffer(buffer)),
+        }
+    }
+
+    /// Create a new page from the provided buffer, validating that the buffer contains a full
+    /// page of `page_size` bytes, and that the page flag is valid.
+    pub fn try_from_buffer(buffer: PageBuffer, page_size: usize) -> Result<Self, PageError> {
+        check_size(&buffer, page_size)?;
+
+        PageFlag::new(buffer[0]).ok_or(PageError::Flag(buffer[0]))?;
+
+        Ok(Self::from_buffer(buffer))
+    }
+
+    /// Flag of the inner page, describing its type and kind.
+    pub fn flag(&self) -> &PageFlag {
+        match self {
+            AnyPage::Table(page) => &page.flag,
+            AnyPage::Index(page) => &page.flag,
+        }
+    }
+
+    /// Number of cells in the inner page.
+    pub fn cell_count(&self) -> u16 {
+        match self {
+            AnyPage::Table(page) => page.cell_count,
+            AnyPage::Index(page) => page.cell_count,
+        }
+    }
+
+    /// Produce the inner page if it is a table page.
+    pub fn as_table(&self) -> Option<&Page<Table>> {
+        match self {
+            AnyPage::Table(page) => Some(page),
+            AnyPage::Index(_) => None,
+        }
+    }
+
+    /// Produce the inner page if it is an index page.
+    pub fn as_index(&self) -> Option<&Page<Index>> {
+        match self {
+            AnyPage::Index(page) => Some(page),
+            AnyPage::Table(_) => None,
+        }
+    }
+}
+
+/// Check that the buffer contains a full page of `page_size` bytes.
+fn check_size(buffer: &PageBuffer, page_size: usize) -> Result<(), PageError> {
+    if buffer.raw().len() != page_size {
+        return Err(PageError::Size {
+            expected: page_size,
+            found: buffer.raw().len(),
+        });
+    }
+
+    Ok(())
+}
+
 impl<T: PageType> Deref for Page<T> {
     type Target = PageCommon<T>;
 
@@ -114,34 +206,104 @@ impl<T: PageType> PageCommon<T> {
 
     /// Produce an iterator of pointers into the cell content area. The pointers will be relative
     /// to the cell content area (that is, the buffer returned by [`Self::cell_content_area`]).
-    pub fn cell_content_pointers(&self) -> impl Iterator<Item = usize> {
+    ///
+    /// Will fail if the cell content pointer array implied by [`Self::cell_count`] doesn't fit
+    /// within the page, or if a pointer precedes the cell content area.
+    pub fn cell_content_pointers(&self) -> Result<impl Iterator<Item = usize>, PageError> {
+        let start = self.cell_content_area_offset.get() as usize;
+
+        let pointers = self
+            .cell_pointer_array()?
+            .iter()
+            .enumerate()
+            .map(|(index, pointer)| {
+                let pointer = pointer.get();
+
+                // Adjust pointer to be relative to the cell content area
+                (pointer as usize)
+                    .checked_sub(start)
+                    .ok_or(PageError::CellPointer { index, pointer })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(pointers.into_iter())
+    }
+
+    /// Slice the cell pointer array, which begins immediately after the header, as big-endian
+    /// u16s. Will fail if the array implied by [`Self::cell_count`] doesn't fit within the page.
+    fn cell_pointer_array(&self) -> Result<&[U16], PageError> {
         // Determine the length of the cell content pointer array.
         let length = self.cell_count as usize * size_of::<U16>();
 
-        // Slice the buffer to include the cell content pointer array, which begins immediately
-        // after the header.
-        let buf = &self.after_header()[..length];
+        let after_header = self.after_header();
+        let buf = after_header.get(..length).ok_or(PageError::CellCount {
+            cell_count: self.cell_count,
+            available: after_header.len(),
+        })?;
 
-        // Cast the slice into an array of big-endian u16s
-        <[U16]>::ref_from_bytes_with_elems(buf, self.cell_count as usize)
-            .unwrap()
+        Ok(<[U16]>::ref_from_bytes_with_elems(buf, self.cell_count as usize).unwrap())
+    }
+
+    /// Check that each cell pointer refers to a cell within the cell content area, with enough
+    /// space for the fixed portion of the cell. Reports the first pointer which doesn't.
+    ///
+    /// The cells of a page which fails this check can't be read with
+    /// [`Self::cell_content_pointers`].
+    #[allow(unused)]
+    pub fn validate_cell_pointers(&self) -> Result<(), PageError> {
+        // Interior cells begin with a 4 byte child pointer, whilst leaf cells begin with a varint.
+        let min_cell_length = match self.flag.kind_flag {
+            PageKindFlag::Leaf => 1,
+            PageKindFlag::Interior => size_of::<U32>(),
+        };
+
+        let start = self.cell_content_area_offset.get() as usize;
+        let end = self.buffer.usable_len();
+
+        self.cell_pointer_array()?
             .iter()
-            // Fetch the value
-            .map(|pointer| pointer.get() as usize)
-            // Adjust pointer to be relative to the cell content area
-            .map(|pointer| pointer - self.cell_content_area_offset.get() as usize)
+            .enumerate()
+            .try_for_each(|(index, pointer)| {
+                let pointer = pointer.get();
+
+                if (pointer as usize) < start || pointer as usize + min_cell_length > end {
+                    return Err(PageError::CellPointer { index, pointer });
+                }
+
+                Ok(())
+            })
     }
 
-    /// Return a slice to the cell content area.
+    /// Return a slice to the cell content area, which ends before any reserved space at the end
+    /// of the page. If the cell content area begins beyond the usable space (which is only possible
+    /// in a corrupt page), the slice will be empty.
     pub fn cell_content_area(&self) -> &[u8] {
         let offset = self.cell_content_area_offset.get() as usize;
 
         // Slice into the raw buffer, as `cell_content_area_offset` includes additional offset for
         // header on first page.
-        &self.buffer.raw()[offset..]
+        self.buffer
+            .raw()
+            .get(offset..self.buffer.usable_len())
+            .unwrap_or_default()
     }
 }
 
+/// Error produced whilst reading a B-Tree page.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum PageError {
+    #[error(
+        "cell count ({cell_count}) exceeds space available for cell pointers ({available} bytes)"
+    )]
+    CellCount { cell_count: u16, available: usize },
+    #[error("page buffer has incorrect size (expected {expected} bytes, found {found})")]
+    Size { expected: usize, found: usize },
+    #[error("invalid page flag: {0:#04x}")]
+    Flag(u8),
+    #[error("cell pointer {index} ({pointer}) is outside of the cell content area")]
+    CellPointer { index: usize, pointer: u16 },
+}
+
 #[derive(Clone, Debug)]
 #[allow(unused)]
 pub struct PageFlag {
@@ -152,6 +314,12 @@ pub struct PageFlag {
 }
 
 impl PageFlag {
+    /// Raw value of the flag, as stored in the page header.
+    #[allow(unused)]
+    pub fn value(&self) -> u8 {
+        self.flag
+    }
+
     pub fn new(flag: u8) -> Option<Self> {
         Some(Self {
             flag,
@@ -160,3 +328,281 @@ impl PageFlag {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::fs::File;
+
+    use rstest::rstest;
+
+    use crate::{ctx::pager::Pager, disk::header::SQLITE_HEADER_SIZE};
+
+    /// Read a page from the B-Tree fixture, which has a page size of 512.
+    fn btree_page(page_id: u32) -> PageBuffer {
+        Pager::new(File::open("fixtures/btree.db").unwrap(), 512)
+            .get_page(page_id)
+            .unwrap()
+    }
+
+    mod any_page {
+        use super::*;
+
+        #[rstest]
+        #[case::table_interior(2, true, false)]
+        #[case::table_leaf(4, true, true)]
+        #[case::index_interior(3, false, false)]
+        #[case::index_leaf(6, false, true)]
+        fn from_buffer(#[case] page_id: u32, #[case] is_table: bool, #[case] is_leaf: bool) {
+            let page = AnyPage::from_buffer(btree_page(page_id));
+
+            assert_eq!(page.as_table().is_some(), is_table);
+            assert_eq!(page.as_index().is_some(), !is_table);
+
+            let leaf = match page {
+                AnyPage::Table(page) => matches!(page, Page::Leaf(_)),
+                AnyPage::Index(page) => matches!(page, Page::Leaf(_)),
+            };
+            assert_eq!(leaf, is_leaf);
+        }
+
+        #[rstest]
+        #[case::table_interior(2, PageTypeFlag::Table, PageKindFlag::Interior)]
+        #[case::table_leaf(4, PageTypeFlag::Table, PageKindFlag::Leaf)]
+        #[case::index_interior(3, PageTypeFlag::Index, PageKindFlag::Interior)]
+        #[case::index_leaf(6, PageTypeFlag::Index, PageKindFlag::Leaf)]
+        fn try_from_buffer(
+            #[case] page_id: u32,
+            #[case] type_flag: PageTypeFlag,
+            #[case] kind_flag: PageKindFlag,
+        ) {
+            let page = AnyPage::try_from_buffer(btree_page(page_id), 512).unwrap();
+
+            assert_eq!(page.flag().type_flag, type_flag);
+            assert_eq!(page.flag().kind_flag, kind_flag);
+        }
+
+        #[rstest]
+        #[case::table_interior(2, 0x05)]
+        #[case::table_leaf(4, 0x0d)]
+        #[case::index_interior(3, 0x02)]
+        #[case::index_leaf(6, 0x0a)]
+        fn summary(#[case] page_id: u32, #[case] flag: u8) {
+            let buffer = btree_page(page_id);
+            let page = AnyPage::from_buffer(buffer.clone());
+
+            assert_eq!(page.flag().value(), flag);
+
+            // Cell count is stored at offset 3 of the page header.
+            let cell_count = u16::from_be_bytes([buffer[3], buffer[4]]);
+            assert!(cell_count > 0);
+            assert_eq!(page.cell_count(), cell_count);
+        }
+
+        #[test]
+        fn try_from_short_buffer() {
+            let buffer = Pager::new(File::open("fixtures/btree.db").unwrap(), 256)
+                .get_page(4)
+                .unwrap();
+
+            assert!(matches!(
+                AnyPage::try_from_buffer(buffer, 512),
+                Err(PageError::Size {
+                    expected: 512,
+                    found: 256
+                })
+            ));
+        }
+
+        #[test]
+        fn try_from_invalid_flag() {
+            let mut bytes = std::fs::read("fixtures/btree.db").unwrap();
+            bytes[3 * 512] = 0x01;
+
+            assert!(matches!(
+                AnyPage::try_from_buffer(
+                    Pager::from_bytes(bytes).unwrap().get_page(4).unwrap(),
+                    512
+                ),
+                Err(PageError::Flag(0x01))
+            ));
+        }
+    }
+
+    mod cell_content_pointers {
+        use super::*;
+
+        use crate::btree::page::builder::PageBuilder;
+
+        /// Read a page from the B-Tree fixture, after overwriting its cell count.
+        fn with_cell_count(page_id: u32, cell_count: u16) -> PageBuffer {
+            let mut bytes = std::fs::read("fixtures/btree.db").unwrap();
+
+            // Cell count is stored at offset 3 of the page header.
+            let offset = (page_id as usize - 1) * 512 + 3;
+            bytes[offset..offset + 2].copy_from_slice(&cell_count.to_be_bytes());
+
+            Pager::from_bytes(bytes).unwrap().get_page(page_id).unwrap()
+        }
+
+        #[rstest]
+        #[case::table_interior(2)]
+        #[case::table_leaf(4)]
+        fn valid(#[case] page_id: u32) {
+            let page = Page::<Table>::from_buffer(btree_page(page_id));
+
+            assert_eq!(
+                page.cell_content_pointers().unwrap().count(),
+                page.cell_count as usize
+            );
+        }
+
+        #[rstest]
+        #[case::table_interior(2, 251)]
+        #[case::table_leaf(4, 253)]
+        #[case::max(4, u16::MAX)]
+        fn inflated_cell_count(#[case] page_id: u32, #[case] cell_count: u16) {
+            let page = Page::<Table>::from_buffer(with_cell_count(page_id, cell_count));
+
+            assert!(matches!(
+                page.cell_content_pointers(),
+                Err(PageError::CellCount { cell_count: count, .. }) if count == cell_count
+            ));
+        }
+
+        #[rstest]
+        #[case::start(500, Ok(vec![0]))]
+        #[case::before_content(499, Err(PageError::CellPointer { index: 0, pointer: 499 }))]
+        #[case::header(0, Err(PageError::CellPointer { index: 0, pointer: 0 }))]
+        fn content_area_relative(
+            #[case] pointer: u16,
+            #[case] expected: Result<Vec<usize>, PageError>,
+        ) {
+            let mut bytes = PageBuilder::table_leaf(512).build();
+            // Cell count, cell content area offset, and cell pointer array.
+            bytes[3..5].copy_from_slice(&1u16.to_be_bytes());
+            bytes[5..7].copy_from_slice(&500u16.to_be_bytes());
+            bytes[8..10].copy_from_slice(&pointer.to_be_bytes());
+            let page = Page::<Table>::from_buffer(PageBuffer::from_bytes(bytes));
+
+            assert_eq!(
+                page.cell_content_pointers()
+                    .map(|pointers| pointers.collect::<Vec<_>>()),
+                expected
+            );
+        }
+    }
+
+    mod cell_content_area {
+        use super::*;
+
+        use crate::btree::page::builder::PageBuilder;
+
+        /// Build an empty 4096 byte table leaf page, with the provided cell content area offset.
+        fn with_offset(offset: u16) -> Page<Table> {
+            let mut bytes = PageBuilder::table_leaf(4096).build();
+            bytes[5..7].copy_from_slice(&offset.to_be_bytes());
+
+            Page::<Table>::from_buffer(PageBuffer::from_bytes(bytes))
+        }
+
+        #[rstest]
+        #[case::within_page(4000, 96)]
+        #[case::end_of_page(4096, 0)]
+        #[case::past_page(4097, 0)]
+        #[case::max(65535, 0)]
+        // Zero is interpreted as 65536.
+        #[case::zero(0, 0)]
+        fn bounded(#[case] offset: u16, #[case] expected: usize) {
+            assert_eq!(with_offset(offset).cell_content_area().len(), expected);
+        }
+    }
+
+    mod validate_cell_pointers {
+        use super::*;
+
+        use crate::btree::page::builder::PageBuilder;
+
+        /// Build a 512 byte table leaf page, with a single cell pointer.
+        fn with_pointer(pointer: u16) -> Page<Table> {
+            let mut bytes = PageBuilder::table_leaf(512).build();
+            // Cell count, and cell content area offset.
+            bytes[3..5].copy_from_slice(&1u16.to_be_bytes());
+            bytes[5..7].copy_from_slice(&500u16.to_be_bytes());
+            // Cell pointer array.
+            bytes[8..10].copy_from_slice(&pointer.to_be_bytes());
+
+            Page::<Table>::from_buffer(PageBuffer::from_bytes(bytes))
+        }
+
+        #[rstest]
+        #[case::start(500)]
+        #[case::last_byte(511)]
+        fn valid(#[case] pointer: u16) {
+            assert_eq!(with_pointer(pointer).validate_cell_pointers(), Ok(()));
+        }
+
+        #[rstest]
+        #[case::header(0)]
+        #[case::before_content(499)]
+        #[case::end_of_page(512)]
+        #[case::max(65535)]
+        fn invalid(#[case] pointer: u16) {
+            assert_eq!(
+                with_pointer(pointer).validate_cell_pointers(),
+                Err(PageError::CellPointer { index: 0, pointer })
+            );
+        }
+
+        #[test]
+        fn fixture() {
+            for page_id in [2, 3, 4] {
+                let page = AnyPage::from_buffer(btree_page(page_id));
+                let result = match page {
+                    AnyPage::Table(page) => page.validate_cell_pointers(),
+                    AnyPage::Index(page) => page.validate_cell_pointers(),
+                };
+
+                assert_eq!(result, Ok(()), "page {page_id}");
+            }
+        }
+    }
+
+    mod first_page {
+        use super::*;
+
+        use crate::{ctx::Ctx, disk::var_int::VarInt, record::Record};
+
+        /// Decode the name of each object in the schema table, by reading the cells of the first
+        /// page directly.
+        #[rstest]
+        #[case("btree.db", &["items", "items_name"])]
+        #[case("reserved.db", &["items"])]
+        #[case("schema.db", &["users", "posts", "posts_user_id", "user_posts", "delete_user_posts"])]
+        fn schema_cells(#[case] fixture: &str, #[case] expected: &[&str]) {
+            let ctx = Ctx::new(File::open(format!("fixtures/{fixture}")).unwrap());
+            let buffer = ctx.pager.get_page(1).unwrap();
+
+            // The B-Tree header follows the database header, and the buffer begins after it.
+            assert_eq!(buffer.raw()[SQLITE_HEADER_SIZE], 0x0d);
+            assert_eq!(buffer[0], 0x0d);
+
+            let page = Page::<Table>::from_buffer(buffer);
+            let names = page
+                .cell_content_pointers()
+                .unwrap()
+                .map(|ptr| {
+                    let content = &page.cell_content_area()[ptr..];
+                    let (payload_size, content) = VarInt::from_buffer(content);
+                    let (_row_id, payload) = VarInt::from_buffer(content);
+
+                    let record = Record::from_buf(1, &payload[..*payload_size as usize]).unwrap();
+                    record.fields[1].clone().string().unwrap()
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(names, expected);
+        }
+    }
+}
@@ -1,11 +1,15 @@
 use std::marker::PhantomData;
 
 use derive_more::Deref;
-use zerocopy::TryFromBytes;
+use zerocopy::{FromBytes, TryFromBytes, big_endian::*};
 
 use crate::{
-    btree::page::{Page, PageCommon, PageExt, PageFlag, PageType, disk::DiskInteriorPageHeader},
+    btree::page::{
+        Page, PageCommon, PageError, PageExt, PageFlag, PageType, Table,
+        disk::DiskInteriorPageHeader,
+    },
     ctx::pager::PageBuffer,
+    disk::var_int::VarInt,
 };
 
 use super::PageKindFlag;
@@ -19,9 +23,48 @@ pub struct InteriorPage<T: PageType> {
     pub right_pointer: u32,
 }
 
+impl<T: PageType> InteriorPage<T> {
+    /// Produce the left child pointer of each cell, in order.
+    pub fn left_pointers(&self) -> Result<impl Iterator<Item = u32>, PageError> {
+        let cell_content = self.cell_content_area();
+
+        Ok(self.cell_content_pointers()?.map(|ptr| {
+            let (left_pointer, _) = U32::read_from_prefix(&cell_content[ptr..]).unwrap();
+            left_pointer.get()
+        }))
+    }
+}
+
+impl InteriorPage<Table> {
+    /// Produce each cell within the page, in order.
+    pub fn cells(&self) -> Result<impl Iterator<Item = InteriorTableCell>, PageError> {
+        let cell_content = self.cell_content_area();
+
+        Ok(self.cell_content_pointers()?.map(|ptr| {
+            let (left_pointer, buf) = U32::read_from_prefix(&cell_content[ptr..]).unwrap();
+            let (row_id, _) = VarInt::from_buffer(buf);
+
+            InteriorTableCell {
+                left_pointer: left_pointer.get(),
+                row_id: *row_id,
+            }
+        }))
+    }
+}
+
+/// A cell within an interior table page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InteriorTableCell {
+    /// Page containing rows with ids less than or equal to [`Self::row_id`].
+    pub left_pointer: u32,
+
+    /// Largest row id within the left child.
+    pub row_id: i64,
+}
+
 impl<T: PageType> PageExt<T> for InteriorPage<T> {
     fn from_buffer(buffer: PageBuffer) -> Self {
-        let header = DiskInteriorPageHeader::try_ref_from_bytes(&buffer).unwrap();
+        let (header, _) = DiskInteriorPageHeader::try_ref_from_prefix(&buffer[..]).unwrap();
 
         let Some(flag) = PageFlag::new(header.flag).filter(|flag| {
             matches!(flag.kind_flag, PageKindFlag::Interior) && flag.type_flag.is::<T>()
@@ -49,3 +92,53 @@ impl<T: PageType> PageExt<T> for InteriorPage<T> {
         Page::Interior(self)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+
+    use super::*;
+
+    use crate::{
+        btree::{self, page::Page},
+        ctx::Ctx,
+    };
+
+    #[test]
+    fn cell_row_id_is_max_of_child() {
+        let ctx = Ctx::new(File::open("fixtures/btree.db").unwrap());
+
+        let Page::Interior(page) = Page::<Table>::from_buffer(ctx.pager.get_page(2).unwrap())
+        else {
+            panic!("expected interior page");
+        };
+
+        let cells = page.cells().unwrap().collect::<Vec<_>>();
+        assert!(cells.len() > 1);
+
+        for cell in cells {
+            let child = Page::<Table>::from_buffer(ctx.pager.get_page(cell.left_pointer).unwrap());
+            let max_row_id = btree::traverse(ctx.clone(), child)
+                .map(|cell| cell.row_id)
+                .max();
+
+            assert_eq!(max_row_id, Some(btree::RowId::from(cell.row_id)));
+        }
+    }
+
+    #[test]
+    fn left_pointers_match_cells() {
+        let ctx = Ctx::new(File::open("fixtures/btree.db").unwrap());
+
+        let Page::Interior(page) = Page::<Table>::from_buffer(ctx.pager.get_page(2).unwrap())
+        else {
+            panic!("expected interior page");
+        };
+
+        assert!(
+            page.left_pointers()
+                .unwrap()
+                .eq(page.cells().unwrap().map(|cell| cell.left_pointer))
+        );
+    }
+}
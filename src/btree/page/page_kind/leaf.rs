@@ -4,8 +4,15 @@ use derive_more::Deref;
 use zerocopy::TryFromBytes;
 
 use crate::{
-    btree::page::{Page, PageCommon, PageExt, PageFlag, PageType, disk::DiskLeafPageHeader},
+    btree::{
+        RowId,
+        page::{
+            Page, PageCommon, PageError, PageExt, PageFlag, PageType, Table,
+            disk::DiskLeafPageHeader,
+        },
+    },
     ctx::pager::PageBuffer,
+    disk::var_int::VarInt,
 };
 
 use super::PageKindFlag;
@@ -16,6 +23,21 @@ pub struct LeafPage<T: PageType> {
     common: PageCommon<T>,
 }
 
+impl LeafPage<Table> {
+    /// Produce the row id of each cell within the page, in order, without reading the payloads.
+    #[allow(unused)]
+    pub fn row_ids(&self) -> Result<impl Iterator<Item = RowId>, PageError> {
+        let cell_content = self.cell_content_area();
+
+        Ok(self.cell_content_pointers()?.map(|ptr| {
+            let (_payload_size, buf) = VarInt::from_buffer(&cell_content[ptr..]);
+            let (row_id, _) = VarInt::from_buffer(buf);
+
+            RowId::from(*row_id)
+        }))
+    }
+}
+
 impl<T: PageType> PageExt<T> for LeafPage<T> {
     fn from_buffer(buffer: PageBuffer) -> Self {
         let (header, _) = DiskLeafPageHeader::try_ref_from_prefix(&buffer[..]).unwrap();
@@ -45,3 +67,32 @@ impl<T: PageType> PageExt<T> for LeafPage<T> {
         Page::Leaf(self)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+
+    use super::*;
+
+    use crate::{btree, ctx::Ctx};
+
+    #[test]
+    fn row_ids_match_cells() {
+        let ctx = Ctx::new(File::open("fixtures/btree.db").unwrap());
+
+        let Page::Leaf(page) = Page::<Table>::from_buffer(ctx.pager.get_page(4).unwrap()) else {
+            panic!("expected leaf page");
+        };
+
+        let row_ids = page.row_ids().unwrap().collect::<Vec<_>>();
+        assert!(row_ids.len() > 1);
+        assert!(row_ids.is_sorted_by(|a, b| a < b));
+
+        assert_eq!(
+            row_ids,
+            btree::traverse(ctx, page.to_page())
+                .map(|cell| cell.row_id)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -3,7 +3,7 @@ mod leaf;
 
 pub use self::{interior::InteriorPage, leaf::LeafPage};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PageKindFlag {
     Leaf,
     Interior,
@@ -1,12 +1,8 @@
-use super::PageType;
+use super::{PageType, PageTypeFlag};
 
 #[derive(Clone, Debug)]
 pub enum Index {}
 
 impl PageType for Index {
-    const FLAG: u8 = 0x02;
-
-    fn is_index() -> bool {
-        true
-    }
+    const TYPE_FLAG: PageTypeFlag = PageTypeFlag::Index;
 }
@@ -8,18 +8,26 @@ pub use self::{index::Index, table::Table};
 
 /// Marker trait for page types.
 pub trait PageType: 'static + Clone {
-    const FLAG: u8;
+    /// Type of page this marker represents. All other information about the page type is derived
+    /// from this, so it can't be partially specified.
+    const TYPE_FLAG: PageTypeFlag;
 
+    /// Value of the page flag for an interior page of this type.
+    #[allow(unused)]
+    const FLAG: u8 = Self::TYPE_FLAG.flag();
+
+    #[allow(unused)]
     fn is_table() -> bool {
-        false
+        matches!(Self::TYPE_FLAG, PageTypeFlag::Table)
     }
 
+    #[allow(unused)]
     fn is_index() -> bool {
-        false
+        matches!(Self::TYPE_FLAG, PageTypeFlag::Index)
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PageTypeFlag {
     Table,
     Index,
@@ -36,11 +44,48 @@ impl PageTypeFlag {
         }
     }
 
-    pub fn is<T: PageType>(&self) -> bool {
+    /// Value of the page flag for an interior page of this type.
+    pub const fn flag(&self) -> u8 {
         match self {
-            PageTypeFlag::Table if T::is_table() => true,
-            PageTypeFlag::Index if T::is_index() => true,
-            _ => false,
+            PageTypeFlag::Table => 0x05,
+            PageTypeFlag::Index => 0x02,
         }
     }
+
+    pub fn is<T: PageType>(&self) -> bool {
+        *self == T::TYPE_FLAG
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Each page type must round-trip through its flag, checked at compile time.
+    const _: () = {
+        assert!(matches!(
+            PageTypeFlag::new(Table::FLAG),
+            Some(PageTypeFlag::Table)
+        ));
+        assert!(matches!(
+            PageTypeFlag::new(Index::FLAG),
+            Some(PageTypeFlag::Index)
+        ));
+    };
+
+    #[test]
+    fn table() {
+        assert!(Table::is_table());
+        assert!(!Table::is_index());
+        assert!(PageTypeFlag::Table.is::<Table>());
+        assert!(!PageTypeFlag::Table.is::<Index>());
+    }
+
+    #[test]
+    fn index() {
+        assert!(Index::is_index());
+        assert!(!Index::is_table());
+        assert!(PageTypeFlag::Index.is::<Index>());
+        assert!(!PageTypeFlag::Index.is::<Table>());
+    }
 }
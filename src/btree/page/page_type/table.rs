@@ -1,12 +1,8 @@
-use super::PageType;
+use super::{PageType, PageTypeFlag};
 
 #[derive(Clone, Debug)]
 pub enum Table {}
 
 impl PageType for Table {
-    const FLAG: u8 = 0x05;
-
-    fn is_table() -> bool {
-        true
-    }
+    const TYPE_FLAG: PageTypeFlag = PageTypeFlag::Table;
 }
@@ -1,10 +1,15 @@
-use std::cmp::Ordering;
+use std::io::{self, Read, Seek, SeekFrom};
 
+use thiserror::Error;
 use zerocopy::{FromBytes, big_endian::U32};
 
 use crate::{
     btree::page::{Index, Page, PageType, Table},
-    ctx::Ctx,
+    ctx::{
+        Ctx,
+        pager::{PageBuffer, PagerError},
+    },
+    disk::header::PayloadFractions,
 };
 
 #[derive(Clone)]
@@ -25,127 +30,659 @@ pub struct Payload<T: PageType> {
 
 impl<T: PayloadCalculation> Payload<T> {
     /// Read the payload from the start of the provided buffer.
+    ///
+    /// Will fail if the usable space of each page (as configured in the header) is too small to
+    /// hold a payload, or if the portion of the payload stored on the page (along with the pointer
+    /// to the first overflow page) extends beyond the cell content area.
     pub fn from_buf_with_payload_size(
         ctx: Ctx,
         page: Page<T>,
         offset: usize,
         payload_size: usize,
-    ) -> Self {
+    ) -> Result<Self, PayloadError> {
         // U: The usable size of a database page (the total page size less the reserved space at
         // the end of each page).
-        let usable_space = ctx.header.page_size() as usize - ctx.header.page_end_padding() as usize;
+        let usable_space = (ctx.header.page_size() as usize)
+            .saturating_sub(ctx.header.page_end_padding() as usize);
 
-        // X: The maximum amount of payload that can be stored directly on the b-tree page without
-        // spilling onto an overflow page.
-        let max_page_payload = T::max_page_payload(usable_space);
-
-        // M: The minimum amount of payload that must be stored onthe btree page before spilling is
-        // allowed.
-        let min_page_payload = ((usable_space - 12) * 32 / 255) - 23;
-
-        let k = (min_page_payload as isize
-            + ((payload_size as isize - min_page_payload as isize) % (usable_space as isize - 4)))
-            as usize;
-
-        // Calculate bytes stored, and bytes on overflow page.
-        let (stored, overflow) = match (
-            (payload_size).cmp(&max_page_payload),
-            k.cmp(&max_page_payload),
-        ) {
-            (Ordering::Less | Ordering::Equal, _) => (payload_size, None),
-            (Ordering::Greater, Ordering::Less | Ordering::Equal) => (k, Some(payload_size - k)),
-            (Ordering::Greater, Ordering::Greater) => {
-                (min_page_payload, Some(payload_size - min_page_payload))
-            }
-        };
+        let stored = T::stored_payload(ctx.header.payload_fractions(), usable_space, payload_size)?;
 
         // Calculate where the payload would stop
         let base_offset_end = offset + stored;
+        let overflows = stored < payload_size;
+
+        // The overflow page number is stored at the end of the usable data.
+        let end = base_offset_end + if overflows { size_of::<U32>() } else { 0 };
+        let available = page.cell_content_area().len();
+        if end > available {
+            return Err(PayloadError::Bounds { end, available });
+        }
 
         // If overflow, determine the next page.
-        let next_page = overflow.map(|_| {
-            // Read the overflow page number, which is stored at the end of the usable data.
-            let next_page = U32::ref_from_bytes(
-                &page.cell_content_area()[base_offset_end..base_offset_end + size_of::<U32>()],
-            )
-            .unwrap();
+        let next_page = overflows.then(|| {
+            let next_page =
+                U32::ref_from_bytes(&page.cell_content_area()[base_offset_end..end]).unwrap();
 
             next_page.get()
         });
 
-        Self {
+        Ok(Self {
             length: payload_size,
             base_page: page,
             base_offset: offset,
             base_offset_end,
             next_page,
-        }
+        })
     }
 
     /// Copy the contents of the payload into the provided buffer. The buffer must be equal to
     /// [`Payload::length`].
-    pub fn copy_to_slice(&self, _ctx: Ctx, buf: &mut [u8]) {
+    #[allow(unused)]
+    pub fn copy_to_slice(&self, ctx: Ctx, buf: &mut [u8]) {
         assert_eq!(buf.len(), self.length, "provided buffer must fit payload");
 
-        // TODO: Support overflow payloads.
-        assert!(
-            self.next_page.is_none(),
-            "only support non-overflow payloads for now"
-        );
-
-        // Copy into the slice.
-        buf.copy_from_slice(
-            &self.base_page.cell_content_area()[self.base_offset..self.base_offset_end],
-        );
-    }
-
-    // pub fn debug(&self) {
-    //     let (header_length, buf) = VarInt::from_buffer(self.payload);
-    //     let remaining_header_length = *header_length as usize - (self.payload.len() - buf.len());
-    //
-    //     let mut header_buf = &buf[..remaining_header_length];
-    //
-    //     while !header_buf.is_empty() {
-    //         let (serial_type, buf) = VarInt::from_buffer(header_buf);
-    //         header_buf = buf;
-    //
-    //         println!(
-    //             "{}",
-    //             match *serial_type {
-    //                 0 => "NULL",
-    //                 1 => "i8",
-    //                 2 => "i16",
-    //                 3 => "i24",
-    //                 4 => "i32",
-    //                 5 => "i48",
-    //                 6 => "i64",
-    //                 7 => "f64",
-    //                 8 => "0",
-    //                 9 => "1",
-    //                 10 | 11 => "reserved",
-    //                 n @ 12.. if n % 2 == 0 => "BLOB",
-    //                 n @ 13.. if n % 2 == 1 => "text",
-    //                 _ => unreachable!(),
-    //             }
-    //         )
-    //     }
-    //
-    //     dbg!(remaining_header_length);
-    // }
+        self.reader(ctx)
+            .read_exact(buf)
+            .expect("payload to be readable");
+    }
+}
+
+impl<T: PageType> Payload<T> {
+    /// Create a reader over the payload, which will follow the overflow chain as it's read.
+    pub fn reader(&self, ctx: Ctx) -> PayloadReader<T> {
+        PayloadReader {
+            ctx,
+            payload: self.clone(),
+            position: 0,
+            overflow_page: None,
+        }
+    }
+
+    /// Walk the overflow chain, yielding the ID of each overflow page in order. The walk is bounded
+    /// by the number of pages the payload length requires, so a corrupt chain can't loop forever.
+    ///
+    /// Each page is only read to find the next page in the chain, so the final page isn't read. If
+    /// a page can't be read, an error is produced in its place and the walk stops.
+    #[allow(unused)]
+    pub fn overflow_pages(&self, ctx: Ctx) -> impl Iterator<Item = Result<u32, PagerError>> {
+        let capacity = overflow_capacity(&ctx);
+        let mut remaining = (self.length - self.stored_length()).div_ceil(capacity);
+        let mut next_page = self.next_page;
+
+        std::iter::from_fn(move || {
+            let page_id = next_page.take().filter(|_| remaining > 0)?;
+            remaining -= 1;
+
+            if remaining > 0 {
+                match read_next_page(&ctx, page_id) {
+                    Ok(next) => next_page = next,
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+
+            Some(Ok(page_id))
+        })
+    }
+
+    /// Borrow the portion of the payload stored on the base page, without reading any overflow
+    /// pages.
+    pub fn local(&self) -> &[u8] {
+        &self.base_page.cell_content_area()[self.base_offset..self.base_offset_end]
+    }
+
+    /// Borrow the entire payload from the base page, or `None` if it spills onto overflow pages.
+    pub fn as_local(&self) -> Option<&[u8]> {
+        self.next_page.is_none().then(|| self.local())
+    }
+
+    /// Number of bytes of the payload stored on the base page.
+    fn stored_length(&self) -> usize {
+        self.base_offset_end - self.base_offset
+    }
+}
+
+/// Streaming reader over a [`Payload`]. Overflow pages are only fetched once the read reaches
+/// them.
+pub struct PayloadReader<T: PageType> {
+    ctx: Ctx,
+    payload: Payload<T>,
+
+    /// Current position within the payload.
+    position: usize,
+    /// Most recently used overflow page, and the position within the payload of its first byte.
+    overflow_page: Option<(PageBuffer, usize)>,
+}
+
+/// Read the pointer to the next page in the overflow chain from the start of `page_id`, which will
+/// be `None` at the end of the chain.
+fn read_next_page(ctx: &Ctx, page_id: u32) -> Result<Option<u32>, PagerError> {
+    let page = ctx.pager.get_page(page_id)?;
+    let next_page = next_overflow_page(&page).ok_or(PagerError::ShortRead {
+        page_id,
+        available: page.len(),
+    })?;
+
+    Ok(Some(next_page).filter(|next_page| *next_page != 0))
+}
+
+/// The first 4 bytes of an overflow page point to the next page in the chain. Will be `None` if
+/// the page is too short to hold the pointer.
+fn next_overflow_page(page: &[u8]) -> Option<u32> {
+    let (next_page, _) = U32::read_from_prefix(page).ok()?;
+
+    Some(next_page.get())
+}
+
+/// Number of bytes of payload that are stored on each overflow page (excluding the next page
+/// pointer).
+fn overflow_capacity(ctx: &Ctx) -> usize {
+    ctx.header.page_size() as usize - ctx.header.page_end_padding() as usize - size_of::<U32>()
+}
+
+impl<T: PageType> PayloadReader<T> {
+    /// Find the overflow page containing the current position, walking the overflow chain from
+    /// the most recently used page (or from the beginning, if the position is before it).
+    fn current_overflow_page(&mut self) -> io::Result<(PageBuffer, usize)> {
+        let capacity = overflow_capacity(&self.ctx);
+
+        if self
+            .overflow_page
+            .as_ref()
+            .is_none_or(|(_, start)| *start > self.position)
+        {
+            let first_page = self.payload.next_page.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "payload has no overflow pages")
+            })?;
+
+            self.overflow_page = Some((
+                self.ctx
+                    .pager
+                    .get_page(first_page)
+                    .map_err(io::Error::other)?,
+                self.payload.stored_length(),
+            ));
+        }
+
+        loop {
+            let (page, start) = self.overflow_page.clone().expect("overflow page loaded");
+
+            if self.position < start + capacity {
+                return Ok((page, start));
+            }
+
+            let next_page = next_overflow_page(&page).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "overflow page too short for next page pointer",
+                )
+            })?;
+            if next_page == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "overflow chain ended before payload",
+                ));
+            }
+
+            self.overflow_page = Some((
+                self.ctx
+                    .pager
+                    .get_page(next_page)
+                    .map_err(io::Error::other)?,
+                start + capacity,
+            ));
+        }
+    }
+}
+
+impl<T: PageType> Read for PayloadReader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.payload.length.saturating_sub(self.position);
+        let stored = self.payload.stored_length();
+
+        // Select the bytes available from the current position, up to the end of the page.
+        let n = if self.position < stored {
+            let start = self.payload.base_offset + self.position;
+            let available = &self.payload.base_page.cell_content_area()[start..]
+                [..(stored - self.position).min(remaining)];
+
+            let n = available.len().min(buf.len());
+            buf[..n].copy_from_slice(&available[..n]);
+            n
+        } else if remaining > 0 {
+            let (page, start) = self.current_overflow_page()?;
+            let offset = size_of::<U32>() + (self.position - start);
+            let available = &page[offset..]
+                [..(start + overflow_capacity(&self.ctx) - self.position).min(remaining)];
+
+            let n = available.len().min(buf.len());
+            buf[..n].copy_from_slice(&available[..n]);
+            n
+        } else {
+            0
+        };
+
+        self.position += n;
+        Ok(n)
+    }
+}
+
+impl<T: PageType> Seek for PayloadReader<T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => (self.payload.length as u64).checked_add_signed(offset),
+            SeekFrom::Current(offset) => (self.position as u64).checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        self.position = position as usize;
+        Ok(position)
+    }
+}
+
+/// Error produced whilst locating a payload.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum PayloadError {
+    #[error("usable space of each page ({0} bytes) is too small to hold a payload")]
+    UsableSpace(usize),
+    #[error("payload ends at offset {end}, beyond the cell content area ({available} bytes)")]
+    Bounds { end: usize, available: usize },
 }
 
 pub trait PayloadCalculation: PageType {
-    fn max_page_payload(usable_space: usize) -> usize;
+    /// X: The maximum amount of payload that can be stored directly on the b-tree page without
+    /// spilling onto an overflow page. Produces `None` if the usable space is too small.
+    fn max_page_payload(fractions: PayloadFractions, usable_space: usize) -> Option<usize>;
+
+    /// M: The minimum amount of payload that must be stored on the btree page before spilling is
+    /// allowed. Produces `None` if the usable space is too small.
+    fn min_page_payload(fractions: PayloadFractions, usable_space: usize) -> Option<usize>;
+
+    /// Calculate the number of bytes of a payload which are stored on the b-tree page, with the
+    /// remainder spilling onto overflow pages.
+    fn stored_payload(
+        fractions: PayloadFractions,
+        usable_space: usize,
+        payload_size: usize,
+    ) -> Result<usize, PayloadError> {
+        let error = || PayloadError::UsableSpace(usable_space);
+
+        let max_page_payload = Self::max_page_payload(fractions, usable_space).ok_or_else(error)?;
+        let min_page_payload = Self::min_page_payload(fractions, usable_space).ok_or_else(error)?;
+
+        if payload_size <= max_page_payload {
+            return Ok(payload_size);
+        }
+
+        let k =
+            min_page_payload + payload_size.saturating_sub(min_page_payload) % (usable_space - 4);
+
+        Ok(if k <= max_page_payload {
+            k
+        } else {
+            min_page_payload
+        })
+    }
 }
 
 impl PayloadCalculation for Table {
-    fn max_page_payload(usable_space: usize) -> usize {
-        usable_space - 35
+    fn max_page_payload(_fractions: PayloadFractions, usable_space: usize) -> Option<usize> {
+        usable_space.checked_sub(35)
+    }
+
+    fn min_page_payload(fractions: PayloadFractions, usable_space: usize) -> Option<usize> {
+        fraction_of_page(fractions.leaf, usable_space)
     }
 }
 
 impl PayloadCalculation for Index {
-    fn max_page_payload(usable_space: usize) -> usize {
-        ((usable_space - 12) * 64 / 255) - 23
+    fn max_page_payload(fractions: PayloadFractions, usable_space: usize) -> Option<usize> {
+        fraction_of_page(fractions.max, usable_space)
+    }
+
+    fn min_page_payload(fractions: PayloadFractions, usable_space: usize) -> Option<usize> {
+        fraction_of_page(fractions.min, usable_space)
+    }
+}
+
+/// Number of payload bytes which `fraction` (out of 255) of a page allows, after accounting for
+/// the page and cell overhead.
+fn fraction_of_page(fraction: u8, usable_space: usize) -> Option<usize> {
+    ((usable_space.checked_sub(12)?) * fraction as usize / 255).checked_sub(23)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::{fs::File, io::Cursor};
+
+    use rstest::rstest;
+
+    use crate::{
+        btree::{self, page::PageExt},
+        record::Record,
+    };
+
+    /// Load the payloads of every cell in the overflow fixture.
+    fn payloads() -> (Ctx, Vec<Payload<Table>>) {
+        payloads_of(Ctx::new(File::open("fixtures/overflow.db").unwrap()))
+    }
+
+    /// Load the payloads of every cell in the overflow fixture, after applying `patch` to the
+    /// bytes of the file.
+    fn patched_payloads(patch: impl FnOnce(&mut Vec<u8>)) -> (Ctx, Vec<Payload<Table>>) {
+        let mut bytes = std::fs::read("fixtures/overflow.db").unwrap();
+        patch(&mut bytes);
+
+        payloads_of(Ctx::new(Cursor::new(bytes)))
+    }
+
+    fn payloads_of(ctx: Ctx) -> (Ctx, Vec<Payload<Table>>) {
+        let page = Page::<Table>::from_buffer(ctx.pager.get_page(2).unwrap());
+
+        let payloads = btree::traverse(ctx.clone(), page)
+            .map(|cell| cell.payload)
+            .collect();
+
+        (ctx, payloads)
+    }
+
+    /// Read the entire payload with [`Payload::copy_to_slice`].
+    fn copy_payload(ctx: Ctx, payload: &Payload<Table>) -> Vec<u8> {
+        let mut buf = vec![0; payload.length];
+        payload.copy_to_slice(ctx, &mut buf);
+        buf
+    }
+
+    mod stored_payload {
+        use super::*;
+
+        #[rstest]
+        #[case::fits(100, 100)]
+        #[case::max_local(477, 477)]
+        #[case::barely_spills(478, 39)]
+        #[case::spill_remainder_fits(547, 39)]
+        #[case::spill_remainder_max(985, 477)]
+        #[case::spill_remainder_exceeds(986, 39)]
+        fn table(#[case] payload_size: usize, #[case] expected: usize) {
+            assert_eq!(
+                Table::stored_payload(PayloadFractions::default(), 512, payload_size),
+                Ok(expected)
+            );
+        }
+
+        #[rstest]
+        #[case::fits(100, 100)]
+        #[case::max_local(102, 102)]
+        #[case::barely_spills(103, 39)]
+        #[case::spill_remainder_fits(547, 39)]
+        #[case::spill_remainder_max(610, 102)]
+        fn index(#[case] payload_size: usize, #[case] expected: usize) {
+            assert_eq!(
+                Index::stored_payload(PayloadFractions::default(), 512, payload_size),
+                Ok(expected)
+            );
+        }
+
+        #[rstest]
+        #[case(0)]
+        #[case(11)]
+        #[case(12)]
+        #[case(34)]
+        #[case(100)]
+        fn usable_space_too_small(#[case] usable_space: usize) {
+            assert_eq!(
+                Table::stored_payload(PayloadFractions::default(), usable_space, 1000),
+                Err(PayloadError::UsableSpace(usable_space))
+            );
+            assert_eq!(
+                Index::stored_payload(PayloadFractions::default(), usable_space, 1000),
+                Err(PayloadError::UsableSpace(usable_space))
+            );
+        }
+    }
+
+    mod fractions {
+        use super::*;
+
+        #[test]
+        fn header_matches_default() {
+            let (ctx, _) = payloads();
+
+            assert_eq!(ctx.header.payload_fractions(), PayloadFractions::default());
+        }
+
+        /// Thresholds produced by the hard-coded fractions of the file format.
+        #[rstest]
+        #[case(512, 477, 39, 102, 39)]
+        #[case(4096, 4061, 489, 1002, 489)]
+        #[case(65536, 65501, 8199, 16422, 8199)]
+        fn thresholds(
+            #[case] usable_space: usize,
+            #[case] table_max: usize,
+            #[case] table_min: usize,
+            #[case] index_max: usize,
+            #[case] index_min: usize,
+        ) {
+            let fractions = PayloadFractions::default();
+
+            assert_eq!(
+                Table::max_page_payload(fractions, usable_space),
+                Some(table_max)
+            );
+            assert_eq!(
+                Table::min_page_payload(fractions, usable_space),
+                Some(table_min)
+            );
+            assert_eq!(
+                Index::max_page_payload(fractions, usable_space),
+                Some(index_max)
+            );
+            assert_eq!(
+                Index::min_page_payload(fractions, usable_space),
+                Some(index_min)
+            );
+        }
+    }
+
+    mod reader {
+        use super::*;
+
+        #[rstest]
+        #[case::no_overflow(0, 1)]
+        #[case::no_overflow_large_chunks(0, 64)]
+        #[case::many_overflow(1, 7)]
+        #[case::many_overflow_page_chunks(1, 512)]
+        #[case::single_overflow(2, 13)]
+        fn chunks_match_copy(#[case] cell: usize, #[case] chunk_size: usize) {
+            let (ctx, payloads) = payloads();
+            let payload = &payloads[cell];
+
+            let mut reader = payload.reader(ctx.clone());
+            let mut chunk = vec![0; chunk_size];
+            let mut buf = Vec::new();
+
+            loop {
+                let n = reader.read(&mut chunk).unwrap();
+                if n == 0 {
+                    break;
+                }
+
+                assert!(n <= chunk_size);
+                buf.extend_from_slice(&chunk[..n]);
+            }
+
+            assert_eq!(buf, copy_payload(ctx, payload));
+        }
+
+        #[rstest]
+        #[case(1, (1..=1250).map(|i| format!("{i:04}")).collect())]
+        #[case(2, "x".repeat(600))]
+        fn overflow_contents(#[case] cell: usize, #[case] expected: String) {
+            let (ctx, payloads) = payloads();
+
+            let mut buf = Vec::new();
+            payloads[cell].reader(ctx).read_to_end(&mut buf).unwrap();
+
+            let record = Record::from_buf(0, &buf).unwrap();
+            assert_eq!(record.fields[1].clone().string().unwrap(), expected);
+        }
+
+        #[rstest]
+        #[case(SeekFrom::Start(4000))]
+        #[case(SeekFrom::End(-1000))]
+        #[case(SeekFrom::Start(10))]
+        fn seek(#[case] pos: SeekFrom) {
+            let (ctx, payloads) = payloads();
+            let payload = &payloads[1];
+            let full = copy_payload(ctx.clone(), payload);
+
+            let mut reader = payload.reader(ctx);
+
+            // Read past the seek position first, so seeking backwards is also tested.
+            reader.seek(SeekFrom::Start(4500)).unwrap();
+            reader.read_exact(&mut [0; 100]).unwrap();
+
+            let position = reader.seek(pos).unwrap() as usize;
+
+            let mut buf = vec![0; 600];
+            reader.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, full[position..position + 600]);
+        }
+
+        #[test]
+        fn seek_negative() {
+            let (ctx, payloads) = payloads();
+
+            assert!(payloads[1].reader(ctx).seek(SeekFrom::Current(-1)).is_err());
+        }
+
+        #[test]
+        fn read_past_end() {
+            let (ctx, payloads) = payloads();
+            let mut reader = payloads[1].reader(ctx);
+
+            reader.seek(SeekFrom::End(10)).unwrap();
+            assert_eq!(reader.read(&mut [0; 10]).unwrap(), 0);
+        }
+    }
+
+    mod local {
+        use super::*;
+
+        #[rstest]
+        #[case::no_overflow(0)]
+        #[case::many_overflow(1)]
+        #[case::single_overflow(2)]
+        fn prefix_of_payload(#[case] cell: usize) {
+            let (ctx, payloads) = payloads();
+            let payload = &payloads[cell];
+
+            let local = payload.local();
+            assert_eq!(local.len(), payload.stored_length());
+            assert_eq!(local, &copy_payload(ctx, payload)[..local.len()]);
+        }
+
+        #[test]
+        fn borrows_from_page() {
+            let (ctx, payloads) = payloads();
+            // The first leaf of the `documents` table.
+            let page = ctx.pager.get_page(13).unwrap();
+
+            let local = payloads[0].as_local().unwrap();
+            assert_eq!(local, copy_payload(ctx, &payloads[0]));
+            assert!(page.raw().as_ptr_range().contains(&local.as_ptr()));
+        }
+
+        #[rstest]
+        #[case::many_overflow(1)]
+        #[case::single_overflow(2)]
+        fn overflow(#[case] cell: usize) {
+            let (_, payloads) = payloads();
+
+            assert!(payloads[cell].as_local().is_none());
+        }
+    }
+
+    mod overflow_pages {
+        use super::*;
+
+        // Page IDs taken from the `dbstat` virtual table of the fixture.
+        #[rstest]
+        #[case::no_overflow(0, vec![])]
+        #[case::many_overflow(1, (3..=11).collect())]
+        #[case::single_overflow(2, vec![12])]
+        fn chain(#[case] cell: usize, #[case] expected: Vec<u32>) {
+            let (ctx, payloads) = payloads();
+
+            assert_eq!(
+                payloads[cell]
+                    .overflow_pages(ctx)
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap(),
+                expected
+            );
+        }
+
+        #[test]
+        fn broken_chain() {
+            let (ctx, payloads) = patched_payloads(|bytes| {
+                // Point page 5 of the chain beyond the end of the file.
+                bytes[512 * 4..512 * 4 + 4].copy_from_slice(&9999u32.to_be_bytes());
+            });
+
+            let pages = payloads[1].overflow_pages(ctx).collect::<Vec<_>>();
+
+            assert!(matches!(
+                pages[..],
+                [
+                    Ok(3),
+                    Ok(4),
+                    Ok(5),
+                    Err(PagerError::Read { page_id: 9999, .. })
+                ]
+            ));
+        }
+
+        #[test]
+        fn final_page_not_read() {
+            let (ctx, payloads) = payloads();
+
+            // Point the single overflow page beyond the end of the file.
+            let mut payload = payloads[2].clone();
+            payload.next_page = Some(9999);
+
+            assert_eq!(
+                payload
+                    .overflow_pages(ctx)
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap(),
+                [9999]
+            );
+        }
+    }
+
+    mod from_buf_with_payload_size {
+        use super::*;
+
+        #[rstest]
+        #[case::fits(100, 90)]
+        #[case::overflow_pointer(5000, 2)]
+        fn beyond_cell_content_area(#[case] payload_size: usize, #[case] from_end: usize) {
+            let ctx = Ctx::new(File::open("fixtures/overflow.db").unwrap());
+            let page = Page::<Table>::from_buffer(ctx.pager.get_page(13).unwrap());
+            let available = page.cell_content_area().len();
+
+            assert!(matches!(
+                Payload::from_buf_with_payload_size(ctx, page, available - from_end, payload_size),
+                Err(PayloadError::Bounds { available: a, .. }) if a == available
+            ));
+        }
     }
 }
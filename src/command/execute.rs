@@ -0,0 +1,633 @@
+//! Execution of a [`QueryStatement`] against the records of a table.
+
+use std::{cmp::Ordering, collections::HashSet};
+
+use thiserror::Error;
+
+use crate::{
+    record::{Record, RecordType},
+    value::Value,
+};
+
+use super::{
+    ColumnDef, CompareOp, Expr, PatternOp, QueryStatement, ResultColumn, SortOrder, pattern,
+};
+
+/// Execute a query over the records of a table, where `columns` describes the table and
+/// `rowid_alias` is the index of the column aliasing the row id (see
+/// [`CreateStatement::rowid_alias`](super::CreateStatement::rowid_alias)). Produces the value of
+/// each result column for every resulting row.
+pub fn execute<'a>(
+    query: &QueryStatement,
+    columns: &[ColumnDef],
+    rowid_alias: Option<usize>,
+    records: impl Iterator<Item = Record> + 'a,
+) -> Result<Box<dyn Iterator<Item = Vec<RecordType>> + 'a>, ExecuteError> {
+    let projection = projection(query, columns)?;
+    let order_by = query
+        .order_by
+        .iter()
+        .map(|(column, order)| Ok((column_index(columns, column)?, *order)))
+        .collect::<Result<Vec<_>, ExecuteError>>()?;
+    let filter = query
+        .where_clause
+        .as_ref()
+        .map(|where_clause| Filter::new(&where_clause.expr, columns))
+        .transpose()?;
+
+    let rows = records
+        .map(move |record| {
+            let mut fields = record.fields;
+
+            // The alias is stored as `NULL` in the record, as its value is the row id.
+            if let Some(i) = rowid_alias {
+                if fields.len() <= i {
+                    fields.resize(i + 1, RecordType::Null);
+                }
+
+                fields[i] = RecordType::I64(record.id);
+            }
+
+            fields
+        })
+        // Rows are only produced if the filter is true, not if it is false or unknown.
+        .filter(move |row| {
+            filter
+                .as_ref()
+                .is_none_or(|filter| filter.evaluate(row) == Some(true))
+        });
+
+    let rows: Box<dyn Iterator<Item = Vec<RecordType>>> = if order_by.is_empty() {
+        Box::new(rows)
+    } else {
+        // Sorting requires every row to be present.
+        let mut rows = rows.collect::<Vec<_>>();
+
+        rows.sort_by(|a, b| {
+            order_by
+                .iter()
+                .map(|(i, order)| {
+                    let ordering = field(a, *i).cmp(&field(b, *i));
+
+                    match order {
+                        SortOrder::Asc => ordering,
+                        SortOrder::Desc => ordering.reverse(),
+                    }
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        Box::new(rows.into_iter())
+    };
+
+    let rows = rows.map(move |row| {
+        projection
+            .iter()
+            .map(|i| field(&row, *i))
+            .collect::<Vec<_>>()
+    });
+
+    // Duplicates are removed from the projected rows, before the limit is applied.
+    let rows: Box<dyn Iterator<Item = Vec<RecordType>>> = if query.distinct {
+        let mut seen = HashSet::new();
+        Box::new(rows.filter(move |row| seen.insert(row.clone())))
+    } else {
+        Box::new(rows)
+    };
+
+    Ok(match query.limit {
+        Some(limit) => Box::new(rows.skip(limit.offset).take(limit.count)),
+        None => rows,
+    })
+}
+
+/// Resolve the index of each result column of the query.
+pub fn projection(
+    query: &QueryStatement,
+    columns: &[ColumnDef],
+) -> Result<Vec<usize>, ExecuteError> {
+    query
+        .result_column
+        .clone()
+        .into_iter()
+        .try_fold(Vec::new(), |mut projection, result_column| {
+            match result_column {
+                ResultColumn::All(_) => projection.extend(0..columns.len()),
+                ResultColumn::Column(column) => projection.push(column_index(columns, &column)?),
+            }
+
+            Ok(projection)
+        })
+}
+
+/// An [`Expr`] with each column resolved to its index, ready to be evaluated against rows.
+#[derive(Clone, Debug)]
+enum Filter {
+    Or(Box<Filter>, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Compare {
+        column: usize,
+        op: CompareOp,
+        value: Value,
+    },
+    Pattern {
+        column: usize,
+        op: PatternOp,
+        pattern: String,
+    },
+    In {
+        column: usize,
+        negated: bool,
+        values: Vec<Value>,
+    },
+    IsNull {
+        column: usize,
+        negated: bool,
+    },
+}
+
+impl Filter {
+    fn new(expr: &Expr, columns: &[ColumnDef]) -> Result<Self, ExecuteError> {
+        Ok(match expr {
+            Expr::Or(lhs, rhs) => Self::Or(
+                Box::new(Self::new(lhs, columns)?),
+                Box::new(Self::new(rhs, columns)?),
+            ),
+            Expr::And(lhs, rhs) => Self::And(
+                Box::new(Self::new(lhs, columns)?),
+                Box::new(Self::new(rhs, columns)?),
+            ),
+            Expr::Compare { column, op, value } => {
+                let column = column_index(columns, column)?;
+
+                Self::Compare {
+                    column,
+                    op: *op,
+                    value: Value::from(value.clone()).apply_affinity(columns[column].affinity()),
+                }
+            }
+            Expr::Pattern {
+                column,
+                op,
+                pattern,
+            } => Self::Pattern {
+                column: column_index(columns, column)?,
+                op: *op,
+                pattern: pattern.to_string(),
+            },
+            Expr::In {
+                column,
+                negated,
+                values,
+            } => {
+                let column = column_index(columns, column)?;
+                let affinity = columns[column].affinity();
+
+                Self::In {
+                    column,
+                    negated: *negated,
+                    values: values
+                        .clone()
+                        .into_iter()
+                        .map(|value| Value::from(value).apply_affinity(affinity))
+                        .collect(),
+                }
+            }
+            Expr::IsNull { column, negated } => Self::IsNull {
+                column: column_index(columns, column)?,
+                negated: *negated,
+            },
+        })
+    }
+
+    /// Evaluate the expression against the row, following SQL's three-valued logic where `None`
+    /// is an unknown result (such as when comparing with `NULL`).
+    fn evaluate(&self, row: &[RecordType]) -> Option<bool> {
+        match self {
+            Filter::Or(lhs, rhs) => match (lhs.evaluate(row), rhs.evaluate(row)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+            Filter::And(lhs, rhs) => match (lhs.evaluate(row), rhs.evaluate(row)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            Filter::Compare { column, op, value } => {
+                let ordering = Value::from(field(row, *column)).compare(value)?;
+
+                Some(match op {
+                    CompareOp::Equals => ordering.is_eq(),
+                    CompareOp::NotEquals => ordering.is_ne(),
+                    CompareOp::LessThan => ordering.is_lt(),
+                    CompareOp::LessThanEquals => ordering.is_le(),
+                    CompareOp::GreaterThan => ordering.is_gt(),
+                    CompareOp::GreaterThanEquals => ordering.is_ge(),
+                })
+            }
+            Filter::Pattern {
+                column,
+                op,
+                pattern,
+            } => match field(row, *column) {
+                RecordType::Null => None,
+                RecordType::String(text) => Some(match op {
+                    PatternOp::Like => pattern::like(pattern, &text),
+                    PatternOp::Glob => pattern::glob(pattern, &text),
+                }),
+                // Only text is matched against patterns.
+                _ => Some(false),
+            },
+            Filter::In {
+                column,
+                negated,
+                values,
+            } => {
+                let value = Value::from(field(row, *column));
+
+                // The result is only unknown if no value matched, and at least one comparison was
+                // unknown (such as when comparing with `NULL`).
+                let mut unknown = false;
+                for candidate in values {
+                    match value.equals(candidate) {
+                        Some(true) => return Some(!negated),
+                        Some(false) => {}
+                        None => unknown = true,
+                    }
+                }
+
+                (!unknown).then_some(*negated)
+            }
+            Filter::IsNull { column, negated } => {
+                Some(matches!(field(row, *column), RecordType::Null) != *negated)
+            }
+        }
+    }
+}
+
+/// Find the index of the column with the provided name. Column names are case-insensitive.
+fn column_index(columns: &[ColumnDef], name: &str) -> Result<usize, ExecuteError> {
+    columns
+        .iter()
+        .position(|column| column.column_name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| ExecuteError::UnknownColumn(name.to_string()))
+}
+
+/// Fetch a field from a row. Records may contain fewer fields than the table has columns (such as
+/// after a column is added), in which case the field is `NULL`.
+fn field(row: &[RecordType], i: usize) -> RecordType {
+    row.get(i).cloned().unwrap_or(RecordType::Null)
+}
+
+/// Error produced whilst executing a query.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ExecuteError {
+    #[error("unknown column: {0}")]
+    UnknownColumn(String),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    use crate::command::{CreateStatement, parse_command};
+
+    /// Columns of the table used for testing.
+    fn columns() -> Vec<ColumnDef> {
+        parse_command::<CreateStatement>("create table t (name text, age integer)")
+            .columns
+            .into_iter()
+            .collect()
+    }
+
+    /// Records within the test table.
+    fn records() -> Vec<Record> {
+        [
+            ("carol", RecordType::I8(30)),
+            ("alice", RecordType::I8(25)),
+            ("dave", RecordType::Null),
+            ("bob", RecordType::I8(30)),
+            ("erin", RecordType::F64(25.5)),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, age))| Record {
+            id: i as i64 + 1,
+            fields: vec![RecordType::String(name.to_string()), age],
+        })
+        .collect()
+    }
+
+    /// Execute the query against the test table, returning the first column of each row.
+    fn run(command: &str) -> Vec<String> {
+        execute(
+            &parse_command(command),
+            &columns(),
+            None,
+            records().into_iter(),
+        )
+        .unwrap()
+        .map(|row| row[0].to_string())
+        .collect()
+    }
+
+    #[rstest]
+    #[case("select * from t;", &["carol", "alice", "dave", "bob", "erin"])]
+    #[case("select name from t order by name;", &["alice", "bob", "carol", "dave", "erin"])]
+    #[case("select name from t order by name desc;", &["erin", "dave", "carol", "bob", "alice"])]
+    #[case("select name from t order by age;", &["dave", "alice", "erin", "carol", "bob"])]
+    #[case("select name from t order by age desc, name;", &["bob", "carol", "erin", "alice", "dave"])]
+    #[case("select name from t order by age, name desc;", &["dave", "alice", "erin", "carol", "bob"])]
+    fn order_by(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[rstest]
+    #[case("select name from t limit 2;", &["carol", "alice"])]
+    #[case("select name from t limit 2 offset 1;", &["alice", "dave"])]
+    #[case("select name from t limit 1, 2;", &["alice", "dave"])]
+    #[case("select name from t limit 10 offset 3;", &["bob", "erin"])]
+    #[case("select name from t limit 0;", &[])]
+    #[case("select name from t limit 2 offset 10;", &[])]
+    #[case("select name from t order by name limit 2 offset 1;", &["bob", "carol"])]
+    fn limit(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[rstest]
+    #[case("select distinct age from t;", &["30", "25", "NULL", "25.5"])]
+    #[case("select distinct name, age from t;", &["carol", "alice", "dave", "bob", "erin"])]
+    #[case("select distinct age from t order by age;", &["NULL", "25", "25.5", "30"])]
+    #[case("select distinct age from t limit 2 offset 1;", &["25", "NULL"])]
+    fn distinct(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[test]
+    fn distinct_numeric_equality() {
+        let records = [RecordType::I8(1), RecordType::F64(1.0), RecordType::I64(1)]
+            .into_iter()
+            .map(|age| Record {
+                id: 1,
+                fields: vec![RecordType::Null, age],
+            });
+
+        let rows = execute(
+            &parse_command("select distinct age from t;"),
+            &columns(),
+            None,
+            records,
+        )
+        .unwrap()
+        .collect::<Vec<_>>();
+
+        assert_eq!(rows, [vec![RecordType::I8(1)]]);
+    }
+
+    #[test]
+    fn limit_is_lazy() {
+        let rows = execute(
+            &parse_command("select name from t limit 1 offset 1;"),
+            &columns(),
+            None,
+            records().into_iter().cycle(),
+        )
+        .unwrap()
+        .map(|row| row[0].to_string())
+        .collect::<Vec<_>>();
+
+        assert_eq!(rows, ["alice"]);
+    }
+
+    #[test]
+    fn projection() {
+        let rows = execute(
+            &parse_command("select age, name from t;"),
+            &columns(),
+            None,
+            records().into_iter(),
+        )
+        .unwrap()
+        .collect::<Vec<_>>();
+
+        assert_eq!(rows[0][0].to_string(), "30");
+        assert_eq!(rows[0][1].to_string(), "carol");
+    }
+
+    #[rstest]
+    #[case("select name from t where age is null;", &["dave"])]
+    #[case("select name from t where age isnull;", &["dave"])]
+    #[case("select name from t where age is not null;", &["carol", "alice", "bob", "erin"])]
+    #[case("select name from t where age notnull;", &["carol", "alice", "bob", "erin"])]
+    #[case("select name from t where age not null;", &["carol", "alice", "bob", "erin"])]
+    #[case("select name from t where name is null;", &[])]
+    #[case("select name from t where name is not null;", &["carol", "alice", "dave", "bob", "erin"])]
+    #[case("select name from t where age is not null order by name limit 2;", &["alice", "bob"])]
+    fn where_is_null(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[rstest]
+    #[case("select name from t where age = 30;", &["carol", "bob"])]
+    #[case("select name from t where age == 30;", &["carol", "bob"])]
+    #[case("select name from t where age != 30;", &["alice", "erin"])]
+    #[case("select name from t where age <> 30;", &["alice", "erin"])]
+    #[case("select name from t where age < 30;", &["alice", "erin"])]
+    #[case("select name from t where age <= 25.5;", &["alice", "erin"])]
+    #[case("select name from t where age > 25;", &["carol", "bob", "erin"])]
+    #[case("select name from t where age >= 30;", &["carol", "bob"])]
+    #[case("select name from t where name = 'dave';", &["dave"])]
+    #[case("select name from t where name > 'c';", &["carol", "dave", "erin"])]
+    #[case("select name from t where age = null;", &[])]
+    #[case("select name from t where age != null;", &[])]
+    fn where_compare(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[rstest]
+    #[case("select name from t where age = 30 and name = 'bob';", &["bob"])]
+    #[case("select name from t where age = 30 or name = 'alice';", &["carol", "alice", "bob"])]
+    #[case(
+        "select name from t where name = 'dave' or age = 30 and name = 'carol';",
+        &["carol", "dave"]
+    )]
+    #[case(
+        "select name from t where age = 30 and name = 'carol' or name = 'dave';",
+        &["carol", "dave"]
+    )]
+    #[case("select name from t where age is null or age > 29;", &["carol", "dave", "bob"])]
+    #[case("select name from t where age > 29 or name = 'dave';", &["carol", "dave", "bob"])]
+    #[case("select name from t where age > 29 and name = 'dave';", &[])]
+    fn where_and_or(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[rstest]
+    #[case(
+        "select name from t where name = 'alice' or age = 30 and name = 'carol';",
+        &["carol", "alice"]
+    )]
+    #[case(
+        "select name from t where (name = 'alice' or age = 30) and name = 'carol';",
+        &["carol"]
+    )]
+    #[case(
+        "select name from t where age = 30 and (name = 'carol' or name = 'alice');",
+        &["carol"]
+    )]
+    #[case(
+        "select name from t where ((name = 'alice' or (age = 30))) and name = 'carol';",
+        &["carol"]
+    )]
+    fn where_grouped(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[rstest]
+    #[case("select name from t where name like 'a%';", &["alice"])]
+    #[case("select name from t where name like 'A%';", &["alice"])]
+    #[case("select name from t where name like '%e';", &["alice", "dave"])]
+    #[case("select name from t where name like '%r%';", &["carol", "erin"])]
+    #[case("select name from t where name like '_o_';", &["bob"])]
+    #[case("select name from t where name like '____';", &["dave", "erin"])]
+    #[case("select name from t where name glob 'a*';", &["alice"])]
+    #[case("select name from t where name glob 'A*';", &[])]
+    #[case("select name from t where name glob '*e';", &["alice", "dave"])]
+    #[case("select name from t where name glob 'b?b';", &["bob"])]
+    #[case("select name from t where age like '3%';", &[])]
+    #[case("select name from t where age glob '*';", &[])]
+    #[case("select name from t where name like 'd%' or age glob '*';", &["dave"])]
+    fn where_pattern(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[rstest]
+    #[case("select name from t where age in (30);", &["carol", "bob"])]
+    #[case("select name from t where age in (25, 25.5);", &["alice", "erin"])]
+    #[case("select name from t where age in (1, 2);", &[])]
+    #[case("select name from t where name in ('bob', 'alice', 30);", &["alice", "bob"])]
+    #[case("select name from t where age not in (30);", &["alice", "erin"])]
+    #[case("select name from t where age not in (1, 'x');", &["carol", "alice", "bob", "erin"])]
+    #[case("select name from t where name not in ('bob', 'alice', 30);", &["carol", "dave", "erin"])]
+    #[case("select name from t where age in (30, null);", &["carol", "bob"])]
+    #[case("select name from t where age not in (30, null);", &[])]
+    #[case("select name from t where age not in (30) or age is null;", &["alice", "dave", "erin"])]
+    fn where_in(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[rstest]
+    #[case("select name from t where age = '30';", &["carol", "bob"])]
+    #[case("select name from t where age > '29';", &["carol", "bob"])]
+    #[case("select name from t where age = '25.5';", &["erin"])]
+    #[case("select name from t where age = 'thirty';", &[])]
+    #[case("select name from t where age in ('30', 25);", &["carol", "alice", "bob"])]
+    fn where_numeric_affinity(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(run(command), expected);
+    }
+
+    #[rstest]
+    #[case("select id from t where id = '5';", &["5"])]
+    #[case("select id from t where id = 5;", &["5"])]
+    #[case("select id from t where code = 5;", &["5"])]
+    #[case("select id from t where code = 1.5;", &["6"])]
+    #[case("select id from t where code in (5, 1.5);", &["5", "6"])]
+    #[case("select id from t where code > 2;", &["5"])]
+    #[case("select id from t where raw = 5;", &["5"])]
+    #[case("select id from t where raw = '5';", &[])]
+    fn where_column_affinity(#[case] command: &str, #[case] expected: &[&str]) {
+        let columns =
+            parse_command::<CreateStatement>("create table t (id integer, code text, raw blob)")
+                .columns
+                .into_iter()
+                .collect::<Vec<_>>();
+        let records = [
+            (5, RecordType::String("5".to_string()), RecordType::I8(5)),
+            (
+                6,
+                RecordType::String("1.5".to_string()),
+                RecordType::F64(1.5),
+            ),
+        ]
+        .into_iter()
+        .map(|(id, code, raw)| Record {
+            id,
+            fields: vec![RecordType::I8(id as i8), code, raw],
+        });
+
+        let rows = execute(&parse_command(command), &columns, None, records)
+            .unwrap()
+            .map(|row| row[0].to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(rows, expected);
+    }
+
+    #[rstest]
+    #[case("select id from t where data = x'00ff';", &["1"])]
+    #[case("select id from t where data < x'00ff';", &["2", "4"])]
+    #[case("select id from t where data > x'00';", &["1", "3"])]
+    #[case("select id from t where data in (x'', x'01');", &["3", "4"])]
+    fn where_blob(#[case] command: &str, #[case] expected: &[&str]) {
+        let columns = parse_command::<CreateStatement>("create table t (id integer, data blob)")
+            .columns
+            .into_iter()
+            .collect::<Vec<_>>();
+        let records = [vec![0x00, 0xff], vec![0x00], vec![0x01], vec![]]
+            .into_iter()
+            .enumerate()
+            .map(|(i, data)| Record {
+                id: i as i64 + 1,
+                fields: vec![RecordType::I8(i as i8 + 1), RecordType::Blob(data)],
+            });
+
+        let rows = execute(&parse_command(command), &columns, None, records)
+            .unwrap()
+            .map(|row| row[0].to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(rows, expected);
+    }
+
+    #[test]
+    fn where_missing_field_is_null() {
+        // Records with fewer fields than columns treat the missing fields as `NULL`.
+        let records = [Record {
+            id: 1,
+            fields: vec![RecordType::String("frank".to_string())],
+        }];
+
+        let rows = execute(
+            &parse_command("select name from t where age is null;"),
+            &columns(),
+            None,
+            records.into_iter(),
+        )
+        .unwrap()
+        .count();
+
+        assert_eq!(rows, 1);
+    }
+
+    #[rstest]
+    #[case("select missing from t;")]
+    #[case("select * from t where missing is null;")]
+    #[case("select * from t where age = 1 or missing = 1;")]
+    #[case("select * from t order by missing;")]
+    fn unknown_column(#[case] command: &str) {
+        assert_eq!(
+            execute(
+                &parse_command(command),
+                &columns(),
+                None,
+                records().into_iter()
+            )
+            .err(),
+            Some(ExecuteError::UnknownColumn("missing".into()))
+        );
+    }
+}
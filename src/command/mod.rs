@@ -1,10 +1,17 @@
+mod execute;
+mod pattern;
 mod token;
 
 use lib_parse::{
-    common::{delimiter::Parenthesis, token::*},
+    common::{
+        delimiter::{LeftParenthesis, Parenthesis},
+        token::*,
+    },
     prelude::*,
 };
 
+pub use self::execute::{execute, projection};
+
 use self::token::*;
 
 #[allow(unused)]
@@ -15,7 +22,7 @@ pub enum ResultColumn {
 }
 
 impl Parse<CommonToken> for ResultColumn {
-    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, String> {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
         let mut lookahead = input.lookahead();
 
         if lookahead.peek::<Token![*]>() {
@@ -28,57 +35,535 @@ impl Parse<CommonToken> for ResultColumn {
     }
 }
 
+/// A name made up of one or more `.` separated identifiers, such as `schema.table` or
+/// `table.column`.
+#[allow(unused)]
+#[derive(Clone, Debug)]
+pub struct QualifiedName {
+    pub segments: Punctuated<Ident, Token![.]>,
+}
+
+impl Parse<CommonToken> for QualifiedName {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        Ok(Self {
+            segments: input.parse_with(Punctuated::parse_separated_non_empty)?,
+        })
+    }
+}
+
 #[allow(unused)]
 #[derive(Clone, Debug)]
 pub struct QueryStatement {
     select: Token![select],
+    /// Whether duplicate rows are removed from the result (`SELECT DISTINCT`).
+    pub distinct: bool,
     pub result_column: Punctuated<ResultColumn, Token![,]>,
     from: Token![from],
     pub table_name: Ident,
+    pub where_clause: Option<WhereClause>,
+    pub order_by: Vec<(Ident, SortOrder)>,
+    pub limit: Option<LimitClause>,
     semicolon: Token![;],
 }
 
 impl Parse<CommonToken> for QueryStatement {
-    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, String> {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
         Ok(Self {
             select: input.parse()?,
+            distinct: input.eat::<Token![distinct]>()?,
             result_column: input.parse_with(Punctuated::parse_separated_non_empty)?,
             from: input.parse()?,
             table_name: input.parse()?,
+            where_clause: {
+                let mut lookahead = input.lookahead();
+
+                if lookahead.peek::<Token![where]>() {
+                    Some(input.parse()?)
+                } else {
+                    None
+                }
+            },
+            order_by: {
+                let mut lookahead = input.lookahead();
+
+                if lookahead.peek::<Token![order]>() {
+                    input.parse::<Token![order]>()?;
+                    input.parse::<Token![by]>()?;
+
+                    input
+                        .parse_with(|input| {
+                            Punctuated::<_, Token![,]>::parse_separated_non_empty_with(
+                                input,
+                                |input| Ok((input.parse()?, input.parse()?)),
+                            )
+                        })?
+                        .into_iter()
+                        .collect()
+                } else {
+                    Vec::new()
+                }
+            },
+            limit: {
+                let mut lookahead = input.lookahead();
+
+                if lookahead.peek::<Token![limit]>() {
+                    Some(input.parse()?)
+                } else {
+                    None
+                }
+            },
             semicolon: input.parse()?,
         })
     }
 }
 
+/// A `WHERE` clause, filtering the rows produced.
+#[allow(unused)]
+#[derive(Clone, Debug)]
+pub struct WhereClause {
+    r#where: Token![where],
+    pub expr: Expr,
+}
+
+impl Parse<CommonToken> for WhereClause {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        Ok(Self {
+            r#where: input.parse()?,
+            expr: input.parse()?,
+        })
+    }
+}
+
+/// An expression which is evaluated against each row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// `lhs OR rhs`.
+    Or(Box<Expr>, Box<Expr>),
+    /// `lhs AND rhs`, which binds tighter than `OR`.
+    And(Box<Expr>, Box<Expr>),
+    /// `column op value`.
+    Compare {
+        column: Ident,
+        op: CompareOp,
+        value: Literal,
+    },
+    /// `column LIKE pattern` or `column GLOB pattern`.
+    Pattern {
+        column: Ident,
+        op: PatternOp,
+        pattern: StringLiteral,
+    },
+    /// `column IN (values)`, or `column NOT IN (values)` when negated.
+    In {
+        column: Ident,
+        negated: bool,
+        values: Punctuated<Literal, Token![,]>,
+    },
+    /// `column IS NULL` or `column ISNULL`. When negated, `column IS NOT NULL`, `column NOTNULL`
+    /// or `column NOT NULL`.
+    IsNull { column: Ident, negated: bool },
+}
+
+impl Parse<CommonToken> for Expr {
+    /// Parse an expression, where `AND` binds tighter than `OR`, and both are left associative.
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        let mut expr = Self::parse_and(input)?;
+
+        while input.lookahead().peek::<Token![or]>() {
+            input.parse::<Token![or]>()?;
+            expr = Self::Or(Box::new(expr), Box::new(Self::parse_and(input)?));
+        }
+
+        Ok(expr)
+    }
+}
+
+impl Expr {
+    /// Parse a chain of predicates joined by `AND`.
+    fn parse_and(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        let mut expr = Self::parse_predicate(input)?;
+
+        while input.lookahead().peek::<Token![and]>() {
+            input.parse::<Token![and]>()?;
+            expr = Self::And(Box::new(expr), Box::new(Self::parse_predicate(input)?));
+        }
+
+        Ok(expr)
+    }
+
+    /// Parse a parenthesized expression, or a single predicate on a column.
+    fn parse_predicate(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        if input.lookahead().peek::<LeftParenthesis>() {
+            let (_parens, group) = input.group::<Parenthesis>()?;
+            let expr = group.parse()?;
+
+            if !group.is_empty() {
+                let mut lookahead = group.lookahead();
+                lookahead.peek::<Token![and]>();
+                lookahead.peek::<Token![or]>();
+                return Err(lookahead.error());
+            }
+
+            return Ok(expr);
+        }
+
+        let column = input.parse()?;
+
+        let mut lookahead = input.lookahead();
+
+        let negated = if lookahead.peek::<CompareOp>() {
+            return Ok(Self::Compare {
+                column,
+                op: input.parse()?,
+                value: input.parse()?,
+            });
+        } else if lookahead.peek::<PatternOp>() {
+            return Ok(Self::Pattern {
+                column,
+                op: input.parse()?,
+                pattern: input.parse()?,
+            });
+        } else if lookahead.peek::<Token![is]>() {
+            input.parse::<Token![is]>()?;
+
+            let negated = input.lookahead().peek::<Token![not]>();
+            if negated {
+                input.parse::<Token![not]>()?;
+            }
+
+            input.parse::<Token![null]>()?;
+            negated
+        } else if lookahead.peek::<Token![isnull]>() {
+            input.parse::<Token![isnull]>()?;
+            false
+        } else if lookahead.peek::<Token![notnull]>() {
+            input.parse::<Token![notnull]>()?;
+            true
+        } else if lookahead.peek::<Token![in]>() {
+            input.parse::<Token![in]>()?;
+
+            return Ok(Self::In {
+                column,
+                negated: false,
+                values: parse_parenthesized(input)?,
+            });
+        } else if lookahead.peek::<Token![not]>() {
+            input.parse::<Token![not]>()?;
+
+            let mut lookahead = input.lookahead();
+
+            if lookahead.peek::<Token![in]>() {
+                input.parse::<Token![in]>()?;
+
+                return Ok(Self::In {
+                    column,
+                    negated: true,
+                    values: parse_parenthesized(input)?,
+                });
+            } else if lookahead.peek::<Token![null]>() {
+                input.parse::<Token![null]>()?;
+                true
+            } else {
+                return Err(lookahead.error());
+            }
+        } else {
+            return Err(lookahead.error());
+        };
+
+        Ok(Self::IsNull { column, negated })
+    }
+}
+
+/// Operator used to match a column against a pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatternOp {
+    /// `LIKE`, with `%` and `_` wildcards.
+    Like,
+    /// `GLOB`, with `*` and `?` wildcards.
+    Glob,
+}
+
+impl Parse<CommonToken> for PatternOp {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        let mut lookahead = input.lookahead();
+
+        if lookahead.peek::<Token![like]>() {
+            input.parse::<Token![like]>()?;
+            Ok(Self::Like)
+        } else if lookahead.peek::<Token![glob]>() {
+            input.parse::<Token![glob]>()?;
+            Ok(Self::Glob)
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl Token<CommonToken> for PatternOp {
+    fn peek(cursor: Cursor<'_, CommonToken>) -> bool {
+        <Token![like]>::peek(cursor) || <Token![glob]>::peek(cursor)
+    }
+
+    fn display() -> &'static str {
+        "pattern operator"
+    }
+}
+
+/// Operator used to compare a column with a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareOp {
+    Equals,
+    NotEquals,
+    LessThan,
+    LessThanEquals,
+    GreaterThan,
+    GreaterThanEquals,
+}
+
+impl Parse<CommonToken> for CompareOp {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        match input.parse()? {
+            Punct::Equals => Ok(Self::Equals),
+            Punct::NotEquals => Ok(Self::NotEquals),
+            Punct::LessThan => Ok(Self::LessThan),
+            Punct::LessThanEquals => Ok(Self::LessThanEquals),
+            Punct::GreaterThan => Ok(Self::GreaterThan),
+            Punct::GreaterThanEquals => Ok(Self::GreaterThanEquals),
+            punct => Err(ParseError::expected(Self::display(), punct)),
+        }
+    }
+}
+
+impl Token<CommonToken> for CompareOp {
+    fn peek(cursor: Cursor<'_, CommonToken>) -> bool {
+        <Token![=]>::peek(cursor)
+            || <Token![!=]>::peek(cursor)
+            || <Token![<]>::peek(cursor)
+            || <Token![<=]>::peek(cursor)
+            || <Token![>]>::peek(cursor)
+            || <Token![>=]>::peek(cursor)
+    }
+
+    fn display() -> &'static str {
+        "comparison operator"
+    }
+}
+
+/// Direction to sort by in an `ORDER BY` clause.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl Parse<CommonToken> for SortOrder {
+    /// Parse an optional `ASC` or `DESC`, defaulting to [`SortOrder::Asc`] if neither is present.
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        if input.eat::<Token![asc]>()? {
+            Ok(Self::Asc)
+        } else if input.eat::<Token![desc]>()? {
+            Ok(Self::Desc)
+        } else {
+            Ok(Self::default())
+        }
+    }
+}
+
+/// A `LIMIT` clause, restricting the number of rows produced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitClause {
+    /// Maximum number of rows to produce.
+    pub count: usize,
+    /// Number of rows to skip before producing any.
+    pub offset: usize,
+}
+
+impl Parse<CommonToken> for LimitClause {
+    /// Parse either `LIMIT count [OFFSET offset]` or `LIMIT offset, count`.
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        input.parse::<Token![limit]>()?;
+
+        let count = parse_unsigned(input)?;
+
+        let mut lookahead = input.lookahead();
+
+        if lookahead.peek::<Token![offset]>() {
+            input.parse::<Token![offset]>()?;
+
+            Ok(Self {
+                count,
+                offset: parse_unsigned(input)?,
+            })
+        } else if lookahead.peek::<Token![,]>() {
+            input.parse::<Token![,]>()?;
+
+            Ok(Self {
+                count: parse_unsigned(input)?,
+                offset: count,
+            })
+        } else {
+            Ok(Self { count, offset: 0 })
+        }
+    }
+}
+
+/// Parse a non-negative integer literal.
+fn parse_unsigned(input: BufferParser<'_, CommonToken>) -> Result<usize, ParseError> {
+    match input.parse::<Number>()? {
+        Number::Integer(n) => {
+            usize::try_from(n).map_err(|_| format!("integer out of range: {n}").into())
+        }
+        Number::Float(n) => Err(ParseError::expected("integer", n)),
+    }
+}
+
 pub fn parse_command<T: Parse<CommonToken>>(command: &str) -> T {
     lib_parse::parse_str(command).unwrap()
 }
 
+/// Parse a script containing a sequence of statements, each terminated by `;`. Empty statements
+/// (a lone `;`) are skipped.
+#[allow(unused)]
+pub fn parse_script(script: &str) -> Result<Vec<Statement>, ParseError> {
+    let buffer = TokenBuffer::<CommonToken>::new(script)?;
+    let parser = buffer.parser();
+
+    let mut statements = Vec::new();
+    while !parser.is_empty() {
+        if parser.lookahead().peek::<Token![;]>() {
+            parser.parse::<Token![;]>()?;
+            continue;
+        }
+
+        statements.push(parser.parse()?);
+    }
+
+    Ok(statements)
+}
+
+/// Any of the supported statements, dispatched on the leading keyword.
+#[allow(unused)]
+#[derive(Clone, Debug)]
+pub enum Statement {
+    Select(QueryStatement),
+    Create(CreateStatement),
+    Insert(InsertStatement),
+    Drop(DropStatement),
+}
+
+impl Parse<CommonToken> for Statement {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        let mut lookahead = input.lookahead();
+
+        if lookahead.peek::<Token![select]>() {
+            Ok(Self::Select(input.parse()?))
+        } else if lookahead.peek::<Token![create]>() {
+            let create = input.parse()?;
+
+            // Unlike the other statements, `CREATE` doesn't consume its terminator, as it is also
+            // parsed from the schema table where there is none.
+            input.parse::<Token![;]>()?;
+
+            Ok(Self::Create(create))
+        } else if lookahead.peek::<Token![insert]>() {
+            Ok(Self::Insert(input.parse()?))
+        } else if lookahead.peek::<Token![drop]>() {
+            Ok(Self::Drop(input.parse()?))
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
 #[allow(unused)]
 #[derive(Clone, Debug)]
 pub struct ColumnDef {
     pub column_name: Ident,
     pub type_name: Ident,
     pub not_null: bool,
+    /// The column's `PRIMARY KEY` constraint, if it has one.
+    pub primary_key: Option<PrimaryKey>,
+}
+
+/// A `PRIMARY KEY` constraint on a single column.
+#[allow(unused)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrimaryKey {
+    pub order: SortOrder,
+    /// Whether the column was declared `AUTOINCREMENT`.
+    pub autoincrement: bool,
+}
+
+impl ColumnDef {
+    /// Determine the type affinity of this column from its declared type.
+    #[allow(unused)]
+    pub fn affinity(&self) -> Affinity {
+        Affinity::from_type_name(self.type_name.as_str())
+    }
+}
+
+/// Type affinity of a column, which determines how values are coerced when stored in or compared
+/// against the column.
+#[allow(unused)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Affinity {
+    Integer,
+    Text,
+    Blob,
+    Real,
+    Numeric,
+}
+
+impl Affinity {
+    /// Derive the affinity of a declared type, following SQLite's rules. Each rule matches a
+    /// case-insensitive substring of the type name, and the first matching rule is used.
+    pub fn from_type_name(type_name: &str) -> Self {
+        let type_name = type_name.to_ascii_uppercase();
+        let contains = |patterns: &[&str]| patterns.iter().any(|p| type_name.contains(p));
+
+        if contains(&["INT"]) {
+            Self::Integer
+        } else if contains(&["CHAR", "CLOB", "TEXT"]) {
+            Self::Text
+        } else if type_name.is_empty() || contains(&["BLOB"]) {
+            Self::Blob
+        } else if contains(&["REAL", "FLOA", "DOUB"]) {
+            Self::Real
+        } else {
+            Self::Numeric
+        }
+    }
 }
 
 impl Parse<CommonToken> for ColumnDef {
-    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, String> {
-        Ok(Self {
-            column_name: parser.parse()?,
-            type_name: parser.parse()?,
-            not_null: {
-                let mut look = parser.lookahead();
+    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        let column_name = parser.parse()?;
+        let type_name = parser.parse()?;
 
-                if look.peek::<Token![not]>() {
-                    parser.parse::<Token![not]>()?;
-                    parser.parse::<Token![null]>()?;
+        // Constraints may be declared in any order.
+        let mut not_null = false;
+        let mut primary_key = None;
+        loop {
+            if parser.eat::<Token![not]>()? {
+                parser.parse::<Token![null]>()?;
+                not_null = true;
+            } else if parser.eat::<Token![primary]>()? {
+                parser.parse::<Token![key]>()?;
+                primary_key = Some(PrimaryKey {
+                    order: parser.parse()?,
+                    autoincrement: parser.eat::<Token![autoincrement]>()?,
+                });
+            } else {
+                break;
+            }
+        }
 
-                    true
-                } else {
-                    false
-                }
-            },
+        Ok(Self {
+            column_name,
+            type_name,
+            not_null,
+            primary_key,
         })
     }
 }
@@ -90,19 +575,1022 @@ pub struct CreateStatement {
     table: Token![table],
     pub table_name: Ident,
     pub columns: Punctuated<ColumnDef, Token![,]>,
+    /// Columns named by a table-level `PRIMARY KEY` constraint, following the column definitions.
+    pub primary_key: Option<Punctuated<Ident, Token![,]>>,
+    /// Whether the table was declared `WITHOUT ROWID`, in which case its rows are stored in an
+    /// index B-Tree keyed by the primary key.
+    pub without_rowid: bool,
+}
+
+impl CreateStatement {
+    /// Names of the columns making up the primary key, whether it was declared on a column or as a
+    /// table constraint. Will be empty if the table has no primary key.
+    pub fn primary_key_columns(&self) -> Vec<&Ident> {
+        match &self.primary_key {
+            Some(columns) => columns.iter().collect(),
+            None => self
+                .columns
+                .iter()
+                .filter(|column| column.primary_key.is_some())
+                .map(|column| &column.column_name)
+                .collect(),
+        }
+    }
+
+    /// Index of the column aliasing the row id, if any. A column is an alias if it is the sole
+    /// primary key of a rowid table, and is declared exactly as `INTEGER` (`INTEGER PRIMARY KEY
+    /// DESC` is not an alias, for compatibility with early versions of SQLite).
+    pub fn rowid_alias(&self) -> Option<usize> {
+        if self.without_rowid {
+            return None;
+        }
+
+        let [name] = self.primary_key_columns()[..] else {
+            return None;
+        };
+
+        let (i, column) = self
+            .columns
+            .iter()
+            .enumerate()
+            .find(|(_, column)| column.column_name.eq_ignore_case(name))?;
+
+        let ascending = column
+            .primary_key
+            .as_ref()
+            .is_none_or(|primary_key| primary_key.order == SortOrder::Asc);
+
+        (column.type_name.eq_ignore_case("integer") && ascending).then_some(i)
+    }
 }
 
 impl Parse<CommonToken> for CreateStatement {
-    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, String> {
+    fn parse(parser: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        let create = parser.parse()?;
+        let table = parser.parse()?;
+        let table_name = parser.parse()?;
+
+        let mut columns = Punctuated::new();
+        let mut primary_key = None;
+        {
+            let (_parens, group) = parser.group::<Parenthesis>()?;
+
+            while !group.is_empty() {
+                if group.eat::<Token![primary]>()? {
+                    // Table constraints follow every column definition.
+                    group.parse::<Token![key]>()?;
+                    let (_parens, key) = group.group::<Parenthesis>()?;
+                    primary_key = Some(key.parse_with(Punctuated::parse_separated_non_empty)?);
+                } else if primary_key.is_some() {
+                    return Err("column definitions must precede table constraints".into());
+                } else {
+                    columns.push_value(group.parse()?);
+                }
+
+                if group.is_empty() {
+                    break;
+                }
+
+                let comma = group.parse::<Token![,]>()?;
+                if primary_key.is_none() {
+                    columns.push_punct(comma);
+                }
+            }
+        }
+
+        let statement = Self {
+            create,
+            table,
+            table_name,
+            columns,
+            primary_key,
+            without_rowid: {
+                let mut lookahead = parser.lookahead();
+
+                if lookahead.peek::<Token![without]>() {
+                    parser.parse::<Token![without]>()?;
+
+                    // `rowid` isn't a keyword, so must be matched as an identifier.
+                    let rowid = parser.parse::<Ident>()?;
+                    if !rowid.eq_ignore_case("rowid") {
+                        return Err(ParseError::expected("`rowid`", rowid));
+                    }
+
+                    true
+                } else {
+                    false
+                }
+            },
+        };
+
+        if statement.without_rowid && statement.primary_key_columns().is_empty() {
+            return Err("a `WITHOUT ROWID` table must have a primary key".into());
+        }
+
+        Ok(statement)
+    }
+}
+
+/// A `DROP` statement, removing a table or an index.
+#[allow(unused)]
+#[derive(Clone, Debug)]
+pub enum DropStatement {
+    Table { if_exists: bool, table_name: Ident },
+    Index { if_exists: bool, index_name: Ident },
+}
+
+impl Parse<CommonToken> for DropStatement {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        input.parse::<Token![drop]>()?;
+
+        let mut lookahead = input.lookahead();
+
+        let statement = if lookahead.peek::<Token![table]>() {
+            input.parse::<Token![table]>()?;
+
+            Self::Table {
+                if_exists: parse_if_exists(input)?,
+                table_name: input.parse()?,
+            }
+        } else if lookahead.peek::<Token![index]>() {
+            input.parse::<Token![index]>()?;
+
+            Self::Index {
+                if_exists: parse_if_exists(input)?,
+                index_name: input.parse()?,
+            }
+        } else {
+            return Err(lookahead.error());
+        };
+
+        input.parse::<Token![;]>()?;
+
+        Ok(statement)
+    }
+}
+
+/// Parse an optional `IF EXISTS`, returning whether it was present.
+fn parse_if_exists(input: BufferParser<'_, CommonToken>) -> Result<bool, ParseError> {
+    if input.eat::<Token![if]>()? {
+        input.parse::<Token![exists]>()?;
+
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// A literal value.
+#[allow(unused)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Null(Token![null]),
+    Number(Number),
+    String(StringLiteral),
+    Blob(BlobLiteral),
+}
+
+impl Parse<CommonToken> for Literal {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
+        let mut lookahead = input.lookahead();
+
+        if lookahead.peek::<Token![null]>() {
+            Ok(Self::Null(input.parse()?))
+        } else if lookahead.peek::<Number>() {
+            Ok(Self::Number(input.parse()?))
+        } else if lookahead.peek::<StringLiteral>() {
+            Ok(Self::String(input.parse()?))
+        } else if lookahead.peek::<BlobLiteral>() {
+            Ok(Self::Blob(input.parse()?))
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+#[allow(unused)]
+#[derive(Clone, Debug)]
+pub struct InsertStatement {
+    insert: Token![insert],
+    into: Token![into],
+    pub table_name: Ident,
+    pub columns: Option<Punctuated<Ident, Token![,]>>,
+    values: Token![values],
+    pub rows: Punctuated<Punctuated<Literal, Token![,]>, Token![,]>,
+    semicolon: Token![;],
+}
+
+impl Parse<CommonToken> for InsertStatement {
+    fn parse(input: BufferParser<'_, CommonToken>) -> Result<Self, ParseError> {
         Ok(Self {
-            create: parser.parse()?,
-            table: parser.parse()?,
-            table_name: parser.parse()?,
+            insert: input.parse()?,
+            into: input.parse()?,
+            table_name: input.parse()?,
             columns: {
-                let (_parens, group) = parser.group::<Parenthesis>()?;
+                let mut lookahead = input.lookahead();
 
-                group.parse_with(Punctuated::parse_terminated)?
+                if lookahead.peek::<LeftParenthesis>() {
+                    Some(parse_parenthesized(input)?)
+                } else {
+                    None
+                }
             },
+            values: input.parse()?,
+            rows: input.parse_with(|input| {
+                Punctuated::parse_separated_non_empty_with(input, parse_parenthesized)
+            })?,
+            semicolon: input.parse()?,
         })
     }
 }
+
+/// Parse a non-empty, comma separated list of `T` surrounded by parentheses.
+fn parse_parenthesized<T: Parse<CommonToken>>(
+    input: BufferParser<'_, CommonToken>,
+) -> Result<Punctuated<T, Token![,]>, ParseError> {
+    let (_parens, group) = input.group::<Parenthesis>()?;
+
+    let values = group.parse_with(Punctuated::parse_separated_non_empty)?;
+
+    if !group.is_empty() {
+        let mut lookahead = group.lookahead();
+        lookahead.peek::<Token![,]>();
+        return Err(lookahead.error());
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    mod qualified_name {
+        use super::*;
+
+        #[rstest]
+        #[case("users", &["users"])]
+        #[case("users.id", &["users", "id"])]
+        #[case("main.users.id", &["main", "users", "id"])]
+        #[case("main . \"my table\" . [my col]", &["main", "my table", "my col"])]
+        fn valid(#[case] name: &str, #[case] expected: &[&str]) {
+            let name = parse_command::<QualifiedName>(name);
+
+            assert_eq!(
+                name.segments
+                    .iter()
+                    .map(|segment| segment.as_str())
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+
+        #[rstest]
+        #[case("")]
+        #[case(".")]
+        #[case("users.")]
+        #[case(".users")]
+        #[case("users.1")]
+        fn invalid(#[case] name: &str) {
+            assert!(lib_parse::parse_str::<QualifiedName, CommonToken>(name).is_err());
+        }
+    }
+
+    mod query_statement {
+        use super::*;
+
+        #[rstest]
+        #[case("select * from t;", &[])]
+        #[case("select * from t order by a;", &[("a", SortOrder::Asc)])]
+        #[case("select * from t order by a asc;", &[("a", SortOrder::Asc)])]
+        #[case("select * from t order by a desc;", &[("a", SortOrder::Desc)])]
+        #[case(
+            "select a, b from t order by a desc, b;",
+            &[("a", SortOrder::Desc), ("b", SortOrder::Asc)]
+        )]
+        fn order_by(#[case] command: &str, #[case] expected: &[(&str, SortOrder)]) {
+            let query = parse_command::<QueryStatement>(command);
+
+            assert_eq!(
+                query
+                    .order_by
+                    .iter()
+                    .map(|(column, order)| (column.as_str(), *order))
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+
+        #[rstest]
+        #[case("select * from t;", false)]
+        #[case("select distinct a from t;", true)]
+        #[case("SELECT DISTINCT a, b FROM t ORDER BY a;", true)]
+        fn distinct(#[case] command: &str, #[case] expected: bool) {
+            assert_eq!(parse_command::<QueryStatement>(command).distinct, expected);
+        }
+
+        #[rstest]
+        #[case("SELECT * FROM t ORDER BY a DESC;", &[("a", SortOrder::Desc)])]
+        #[case("Select a, B From t Order By B Asc;", &[("B", SortOrder::Asc)])]
+        #[case("sElEcT * fRoM t;", &[])]
+        fn mixed_case_keywords(#[case] command: &str, #[case] expected: &[(&str, SortOrder)]) {
+            order_by(command, expected);
+        }
+
+        #[rstest]
+        #[case("select \"from\" from t;", &["from"])]
+        #[case("select [order] from t;", &["order"])]
+        #[case("select `select`, \"where\" from t;", &["select", "where"])]
+        fn quoted_keyword_columns(#[case] command: &str, #[case] expected: &[&str]) {
+            let query = parse_command::<QueryStatement>(command);
+
+            let columns = query
+                .result_column
+                .iter()
+                .map(|column| match column {
+                    ResultColumn::Column(ident) => ident.as_str(),
+                    ResultColumn::All(_) => panic!("expected column"),
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(columns, expected);
+            assert_eq!(query.table_name.as_str(), "t");
+        }
+
+        #[test]
+        fn mixed_case_limit() {
+            let query = parse_command::<QueryStatement>("SELECT * FROM Users LIMIT 5 OFFSET 2;");
+
+            assert_eq!(query.table_name.as_str(), "Users");
+            assert_eq!(
+                query.limit,
+                Some(LimitClause {
+                    count: 5,
+                    offset: 2
+                })
+            );
+        }
+
+        #[rstest]
+        #[case("select * from t order a;")]
+        #[case("select * from t order by;")]
+        #[case("select * from t order by a,;")]
+        fn order_by_invalid(#[case] command: &str) {
+            assert!(lib_parse::parse_str::<QueryStatement, CommonToken>(command).is_err());
+        }
+
+        #[test]
+        fn error_span() {
+            let command = "select * from t order a;";
+            let e = lib_parse::parse_str::<QueryStatement, CommonToken>(command).unwrap_err();
+
+            let span = e.span.unwrap();
+            assert_eq!(&command[span.start..span.end], "a");
+        }
+
+        #[rstest]
+        #[case("select * from t;", None)]
+        #[case("select * from t limit 5;", Some((5, 0)))]
+        #[case("select * from t limit 5 offset 2;", Some((5, 2)))]
+        #[case("select * from t limit 2, 5;", Some((5, 2)))]
+        #[case("select * from t order by a desc limit 0;", Some((0, 0)))]
+        fn limit(#[case] command: &str, #[case] expected: Option<(usize, usize)>) {
+            let query = parse_command::<QueryStatement>(command);
+
+            assert_eq!(
+                query.limit,
+                expected.map(|(count, offset)| LimitClause { count, offset })
+            );
+        }
+
+        #[rstest]
+        #[case("select * from t limit;")]
+        #[case("select * from t limit a;")]
+        #[case("select * from t limit 1.5;")]
+        #[case("select * from t limit 5 offset;")]
+        #[case("select * from t limit 2,;")]
+        #[case("select * from t offset 2;")]
+        fn limit_invalid(#[case] command: &str) {
+            assert!(lib_parse::parse_str::<QueryStatement, CommonToken>(command).is_err());
+        }
+
+        #[test]
+        fn trailing_tokens() {
+            let command = "select * from t; garbage";
+            let e = lib_parse::parse_str::<QueryStatement, CommonToken>(command).unwrap_err();
+
+            let span = e.span.unwrap();
+            assert_eq!(&command[span.start..span.end], "garbage");
+            assert!(e.to_string().contains("garbage"), "{e}");
+
+            let query =
+                lib_parse::parse_str_partial::<QueryStatement, CommonToken>(command).unwrap();
+            assert_eq!(query.table_name.as_str(), "t");
+        }
+    }
+
+    mod where_clause {
+        use super::*;
+
+        #[rstest]
+        #[case("select * from t where a is null;", false)]
+        #[case("select * from t where a IS NULL;", false)]
+        #[case("select * from t where a isnull;", false)]
+        #[case("select * from t where a is not null;", true)]
+        #[case("select * from t where a notnull;", true)]
+        #[case("select * from t where a not null;", true)]
+        fn is_null(#[case] command: &str, #[case] expected: bool) {
+            let query = parse_command::<QueryStatement>(command);
+
+            let Some(WhereClause {
+                expr: Expr::IsNull { column, negated },
+                ..
+            }) = query.where_clause
+            else {
+                panic!("expected `IS NULL` expression");
+            };
+
+            assert_eq!(column.as_str(), "a");
+            assert_eq!(negated, expected);
+        }
+
+        /// Render the structure of an expression, with each `AND` and `OR` parenthesized.
+        fn render(expr: &Expr) -> String {
+            match expr {
+                Expr::Or(lhs, rhs) => format!("({} or {})", render(lhs), render(rhs)),
+                Expr::And(lhs, rhs) => format!("({} and {})", render(lhs), render(rhs)),
+                Expr::Compare { column, .. }
+                | Expr::Pattern { column, .. }
+                | Expr::In { column, .. }
+                | Expr::IsNull { column, .. } => column.to_string(),
+            }
+        }
+
+        #[rstest]
+        #[case("a = 1", "a")]
+        #[case("a = 1 and b = 2", "(a and b)")]
+        #[case("a = 1 or b = 2", "(a or b)")]
+        #[case("a = 1 or b = 2 and c = 3", "(a or (b and c))")]
+        #[case("a = 1 and b = 2 or c = 3", "((a and b) or c)")]
+        #[case("a = 1 and b = 2 and c = 3", "((a and b) and c)")]
+        #[case("a = 1 or b = 2 or c = 3", "((a or b) or c)")]
+        #[case("(a = 1 or b = 2) and c = 3", "((a or b) and c)")]
+        #[case("a is null AND b > 2 OR c notnull", "((a and b) or c)")]
+        #[case("((a = 1))", "a")]
+        #[case("a = 1 and (b = 2 or c = 3)", "(a and (b or c))")]
+        #[case("(a = 1 and (b = 2 or c = 3)) or d = 4", "((a and (b or c)) or d)")]
+        fn precedence(#[case] expr: &str, #[case] expected: &str) {
+            let query = parse_command::<QueryStatement>(&format!("select * from t where {expr};"));
+
+            assert_eq!(render(&query.where_clause.unwrap().expr), expected);
+        }
+
+        #[rstest]
+        #[case("a = 1", CompareOp::Equals)]
+        #[case("a == 1", CompareOp::Equals)]
+        #[case("a != 1", CompareOp::NotEquals)]
+        #[case("a <> 1", CompareOp::NotEquals)]
+        #[case("a < 1", CompareOp::LessThan)]
+        #[case("a <= 1", CompareOp::LessThanEquals)]
+        #[case("a > 1", CompareOp::GreaterThan)]
+        #[case("a >= 1", CompareOp::GreaterThanEquals)]
+        fn compare(#[case] expr: &str, #[case] expected: CompareOp) {
+            let query = parse_command::<QueryStatement>(&format!("select * from t where {expr};"));
+
+            let Expr::Compare { column, op, value } = query.where_clause.unwrap().expr else {
+                panic!("expected comparison");
+            };
+
+            assert_eq!(column.as_str(), "a");
+            assert_eq!(op, expected);
+            assert_eq!(value, Literal::Number(Number::Integer(1)));
+        }
+
+        #[rstest]
+        #[case("a like 'x%'", PatternOp::Like, "x%")]
+        #[case("a LIKE '_y'", PatternOp::Like, "_y")]
+        #[case("a glob 'x*'", PatternOp::Glob, "x*")]
+        #[case("a GLOB '?y'", PatternOp::Glob, "?y")]
+        fn pattern(#[case] expr: &str, #[case] expected_op: PatternOp, #[case] expected: &str) {
+            let query = parse_command::<QueryStatement>(&format!("select * from t where {expr};"));
+
+            let Expr::Pattern {
+                column,
+                op,
+                pattern,
+            } = query.where_clause.unwrap().expr
+            else {
+                panic!("expected pattern");
+            };
+
+            assert_eq!(column.as_str(), "a");
+            assert_eq!(op, expected_op);
+            assert_eq!(pattern.as_str(), expected);
+        }
+
+        #[rstest]
+        #[case("a in (1)", false, &[Literal::Number(Number::Integer(1))])]
+        #[case(
+            "a IN (1, 'x', null)",
+            false,
+            &[
+                Literal::Number(Number::Integer(1)),
+                Literal::String(parse_command("'x'")),
+                parse_command("null"),
+            ]
+        )]
+        #[case("a not in (2.5)", true, &[Literal::Number(Number::Float(2.5))])]
+        fn in_list(
+            #[case] expr: &str,
+            #[case] expected_negated: bool,
+            #[case] expected: &[Literal],
+        ) {
+            let query = parse_command::<QueryStatement>(&format!("select * from t where {expr};"));
+
+            let Expr::In {
+                column,
+                negated,
+                values,
+            } = query.where_clause.unwrap().expr
+            else {
+                panic!("expected `IN` expression");
+            };
+
+            assert_eq!(column.as_str(), "a");
+            assert_eq!(negated, expected_negated);
+            assert_eq!(values.into_iter().collect::<Vec<_>>(), expected);
+        }
+
+        #[test]
+        fn absent() {
+            assert!(
+                parse_command::<QueryStatement>("select * from t;")
+                    .where_clause
+                    .is_none()
+            );
+        }
+
+        #[test]
+        fn before_order_by() {
+            let query = parse_command::<QueryStatement>(
+                "select * from t where a is null order by a limit 1;",
+            );
+
+            assert!(query.where_clause.is_some());
+            assert_eq!(query.order_by.len(), 1);
+            assert!(query.limit.is_some());
+        }
+
+        #[rstest]
+        #[case("select * from t where;")]
+        #[case("select * from t where a;")]
+        #[case("select * from t where a is;")]
+        #[case("select * from t where a is not;")]
+        #[case("select * from t where a not;")]
+        #[case("select * from t where is null;")]
+        #[case("select * from t order by a where a is null;")]
+        #[case("select * from t where a =;")]
+        #[case("select * from t where a = b;")]
+        #[case("select * from t where a = 1 and;")]
+        #[case("select * from t where a = 1 or or b = 2;")]
+        #[case("select * from t where (a = 1;")]
+        #[case("select * from t where (a = 1 b = 2);")]
+        #[case("select * from t where ();")]
+        #[case("select * from t where a like;")]
+        #[case("select * from t where a like 1;")]
+        #[case("select * from t where a glob b;")]
+        #[case("select * from t where a in;")]
+        #[case("select * from t where a in ();")]
+        #[case("select * from t where a in (1,);")]
+        #[case("select * from t where a in (1 2);")]
+        #[case("select * from t where a in (b);")]
+        #[case("select * from t where a not in;")]
+        #[case("select * from t where a not like 'x';")]
+        fn invalid(#[case] command: &str) {
+            assert!(lib_parse::parse_str::<QueryStatement, CommonToken>(command).is_err());
+        }
+    }
+
+    mod create_statement {
+        use super::*;
+
+        #[rstest]
+        #[case("create table t (a int")]
+        #[case("create table t (a int, b int")]
+        #[case("create table t (")]
+        fn unclosed(#[case] command: &str) {
+            assert!(lib_parse::parse_str::<CreateStatement, CommonToken>(command).is_err());
+        }
+
+        #[test]
+        fn preserves_identifier_case() {
+            let create = parse_command::<CreateStatement>("CREATE TABLE Users (Id integer)");
+
+            assert_eq!(create.table_name.as_str(), "Users");
+            assert_eq!(create.columns.len(), 1);
+
+            let column = create.columns.iter().next().unwrap();
+            assert_eq!(column.column_name.as_str(), "Id");
+            assert_eq!(column.type_name.as_str(), "integer");
+        }
+
+        #[rstest]
+        #[case("create table t (a int)", false)]
+        #[case(
+            "create table t (a int primary key, b text not null) without rowid",
+            true
+        )]
+        #[case(
+            "CREATE TABLE t (a INT, b TEXT, PRIMARY KEY (a, b)) WITHOUT ROWID",
+            true
+        )]
+        fn without_rowid(#[case] command: &str, #[case] expected: bool) {
+            let create = parse_command::<CreateStatement>(command);
+
+            assert_eq!(create.without_rowid, expected);
+        }
+
+        #[rstest]
+        #[case("create table t (a int) without")]
+        #[case("create table t (a int) without oid")]
+        #[case("create table t (a int) without 1")]
+        #[case::no_primary_key("create table t (a int, b text not null) without rowid")]
+        fn without_rowid_invalid(#[case] command: &str) {
+            assert!(lib_parse::parse_str::<CreateStatement, CommonToken>(command).is_err());
+        }
+    }
+
+    mod primary_key {
+        use super::*;
+
+        #[rstest]
+        #[case("create table t (a int)", &[])]
+        #[case("create table t (id integer primary key, name text)", &["id"])]
+        #[case("create table t (id integer PRIMARY KEY DESC)", &["id"])]
+        #[case("create table t (id integer not null primary key)", &["id"])]
+        #[case("create table t (id integer primary key not null)", &["id"])]
+        #[case("create table t (a int not null, b int, primary key (a, b))", &["a", "b"])]
+        fn columns(#[case] command: &str, #[case] expected: &[&str]) {
+            let create = parse_command::<CreateStatement>(command);
+
+            assert_eq!(
+                create
+                    .primary_key_columns()
+                    .into_iter()
+                    .map(|column| column.as_str())
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+
+        #[rstest]
+        #[case("id integer primary key", SortOrder::Asc, false, false)]
+        #[case("id integer primary key desc", SortOrder::Desc, false, false)]
+        #[case("id integer primary key autoincrement", SortOrder::Asc, false, true)]
+        #[case("id integer not null primary key asc", SortOrder::Asc, true, false)]
+        fn column_constraint(
+            #[case] column: &str,
+            #[case] order: SortOrder,
+            #[case] not_null: bool,
+            #[case] autoincrement: bool,
+        ) {
+            let column = parse_command::<ColumnDef>(column);
+
+            assert_eq!(column.not_null, not_null);
+            assert_eq!(
+                column.primary_key,
+                Some(PrimaryKey {
+                    order,
+                    autoincrement
+                })
+            );
+        }
+
+        #[test]
+        fn table_constraint_columns_kept() {
+            let create =
+                parse_command::<CreateStatement>("create table t (a int, b int, primary key (a))");
+
+            assert_eq!(create.columns.len(), 2);
+        }
+
+        #[rstest]
+        #[case("create table t (id integer primary key, name text)", Some(0))]
+        #[case("create table t (name text, ID Integer Primary Key)", Some(1))]
+        #[case("create table t (name text, id integer, primary key (id))", Some(1))]
+        #[case("create table t (id integer primary key asc)", Some(0))]
+        #[case("create table t (id integer primary key desc)", None)]
+        #[case("create table t (id int primary key)", None)]
+        #[case("create table t (id bigint primary key)", None)]
+        #[case("create table t (a integer, b integer, primary key (a, b))", None)]
+        #[case("create table t (id integer primary key) without rowid", None)]
+        #[case("create table t (id integer)", None)]
+        fn rowid_alias(#[case] command: &str, #[case] expected: Option<usize>) {
+            let create = parse_command::<CreateStatement>(command);
+
+            assert_eq!(create.rowid_alias(), expected);
+        }
+
+        #[rstest]
+        #[case("create table t (a int primary)")]
+        #[case("create table t (a int, primary key ())")]
+        #[case("create table t (a int, primary key a)")]
+        #[case("create table t (primary key (a), a int)")]
+        fn invalid(#[case] command: &str) {
+            assert!(lib_parse::parse_str::<CreateStatement, CommonToken>(command).is_err());
+        }
+    }
+
+    mod affinity {
+        use super::*;
+
+        #[rstest]
+        #[case("int", Affinity::Integer)]
+        #[case("INTEGER", Affinity::Integer)]
+        #[case("bigint", Affinity::Integer)]
+        #[case("varchar(50)", Affinity::Text)]
+        #[case("nchar(55)", Affinity::Text)]
+        #[case("clob", Affinity::Text)]
+        #[case("text", Affinity::Text)]
+        #[case("blob", Affinity::Blob)]
+        #[case("", Affinity::Blob)]
+        #[case("real", Affinity::Real)]
+        #[case("double", Affinity::Real)]
+        #[case("float", Affinity::Real)]
+        #[case("numeric", Affinity::Numeric)]
+        #[case("boolean", Affinity::Numeric)]
+        #[case("datetime", Affinity::Numeric)]
+        #[case("mystery", Affinity::Numeric)]
+        // Earlier rules take precedence, so these don't match what their names suggest.
+        #[case("charint", Affinity::Integer)]
+        #[case("floating point", Affinity::Integer)]
+        #[case("string", Affinity::Numeric)]
+        fn from_type_name(#[case] type_name: &str, #[case] expected: Affinity) {
+            assert_eq!(Affinity::from_type_name(type_name), expected);
+        }
+
+        #[test]
+        fn column_def() {
+            let create = parse_command::<CreateStatement>(
+                "create table t (a int, b varchar, c real, d blob, e whatever)",
+            );
+
+            assert_eq!(
+                create
+                    .columns
+                    .iter()
+                    .map(ColumnDef::affinity)
+                    .collect::<Vec<_>>(),
+                [
+                    Affinity::Integer,
+                    Affinity::Text,
+                    Affinity::Real,
+                    Affinity::Blob,
+                    Affinity::Numeric,
+                ]
+            );
+        }
+    }
+
+    mod insert_statement {
+        use super::*;
+
+        fn columns(insert: &InsertStatement) -> Option<Vec<&str>> {
+            insert
+                .columns
+                .as_ref()
+                .map(|columns| columns.iter().map(|column| column.as_str()).collect())
+        }
+
+        fn rows(insert: &InsertStatement) -> Vec<Vec<Literal>> {
+            insert
+                .rows
+                .iter()
+                .map(|row| row.iter().cloned().collect())
+                .collect()
+        }
+
+        #[test]
+        fn single_row() {
+            let insert = parse_command::<InsertStatement>(
+                "insert into users (id, name) values (1, 'andy');",
+            );
+
+            assert_eq!(insert.table_name.as_str(), "users");
+            assert_eq!(columns(&insert), Some(vec!["id", "name"]));
+            assert_eq!(
+                rows(&insert),
+                [[
+                    Literal::Number(Number::Integer(1)),
+                    Literal::String(parse_command("'andy'")),
+                ]]
+            );
+        }
+
+        #[test]
+        fn multi_row() {
+            let insert = parse_command::<InsertStatement>(
+                "insert into users (id, name) values (1, 'andy'), (2, null), (3.5, 'it''s');",
+            );
+
+            assert_eq!(
+                rows(&insert),
+                [
+                    [
+                        Literal::Number(Number::Integer(1)),
+                        Literal::String(parse_command("'andy'")),
+                    ],
+                    [
+                        Literal::Number(Number::Integer(2)),
+                        Literal::Null(parse_command("null")),
+                    ],
+                    [
+                        Literal::Number(Number::Float(3.5)),
+                        Literal::String(parse_command("'it''s'")),
+                    ],
+                ]
+            );
+        }
+
+        #[test]
+        fn columns_omitted() {
+            let insert = parse_command::<InsertStatement>("insert into users values (1, 'andy');");
+
+            assert_eq!(insert.table_name.as_str(), "users");
+            assert_eq!(columns(&insert), None);
+            assert_eq!(rows(&insert).len(), 1);
+        }
+
+        #[rstest]
+        #[case("insert into users values;")]
+        #[case("insert into users values ();")]
+        #[case("insert into users values (1,);")]
+        #[case("insert into users values (1 2);")]
+        #[case("insert into users values (1), ;")]
+        #[case("insert into users values (1, 'andy'")]
+        #[case("insert into users () values (1);")]
+        #[case("insert into users (id) (1);")]
+        #[case("insert into users (id, 1) values (1);")]
+        #[case("insert into users values (id);")]
+        #[case("insert users values (1);")]
+        #[case("insert into users values (1)")]
+        fn invalid(#[case] command: &str) {
+            assert!(lib_parse::parse_str::<InsertStatement, CommonToken>(command).is_err());
+        }
+    }
+
+    mod drop_statement {
+        use super::*;
+
+        #[test]
+        fn table() {
+            let statement = parse_command::<DropStatement>("drop table t;");
+
+            assert!(matches!(
+                statement,
+                DropStatement::Table { if_exists: false, table_name } if table_name.as_str() == "t"
+            ));
+        }
+
+        #[test]
+        fn index_if_exists() {
+            let statement = parse_command::<DropStatement>("drop index if exists i;");
+
+            assert!(matches!(
+                statement,
+                DropStatement::Index { if_exists: true, index_name } if index_name.as_str() == "i"
+            ));
+        }
+
+        #[rstest]
+        #[case("drop;")]
+        #[case("drop view v;")]
+        #[case("drop table;")]
+        #[case("drop table if t;")]
+        #[case("drop table if exists;")]
+        #[case("drop table t")]
+        fn invalid(#[case] command: &str) {
+            assert!(lib_parse::parse_str::<DropStatement, CommonToken>(command).is_err());
+        }
+    }
+
+    mod script {
+        use super::*;
+
+        /// Summarise each statement of a script by its kind and the name it refers to.
+        fn summary(script: &str) -> Vec<(&'static str, String)> {
+            parse_script(script)
+                .unwrap()
+                .into_iter()
+                .map(|statement| match statement {
+                    Statement::Select(query) => ("select", query.table_name.to_string()),
+                    Statement::Create(create) => ("create", create.table_name.to_string()),
+                    Statement::Insert(insert) => ("insert", insert.table_name.to_string()),
+                    Statement::Drop(DropStatement::Table { table_name, .. }) => {
+                        ("drop", table_name.to_string())
+                    }
+                    Statement::Drop(DropStatement::Index { index_name, .. }) => {
+                        ("drop", index_name.to_string())
+                    }
+                })
+                .collect()
+        }
+
+        #[test]
+        fn three_statements() {
+            assert_eq!(
+                summary(concat!(
+                    "create table users (id integer, name text);\n",
+                    "insert into users values (1, 'a;b'), (2, 'c');\n",
+                    "select name from users where id in (1, 2);",
+                )),
+                [
+                    ("create", "users".to_string()),
+                    ("insert", "users".to_string()),
+                    ("select", "users".to_string()),
+                ]
+            );
+        }
+
+        #[rstest]
+        #[case("", &[])]
+        #[case(";;", &[])]
+        #[case("drop table a;; drop index b;", &["drop", "drop"])]
+        #[case("select * from t;select * from t;", &["select", "select"])]
+        fn statements(#[case] script: &str, #[case] expected: &[&str]) {
+            assert_eq!(
+                summary(script)
+                    .into_iter()
+                    .map(|(kind, _)| kind)
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+
+        #[rstest]
+        #[case("create table t (a int)")]
+        #[case("select * from t; select * from t")]
+        #[case("update t set a = 1;")]
+        #[case("select * from t; 1;")]
+        fn invalid(#[case] script: &str) {
+            assert!(parse_script(script).is_err());
+        }
+    }
+
+    mod statement {
+        use super::*;
+
+        #[test]
+        fn select() {
+            assert!(matches!(
+                parse_command::<Statement>("select * from t;"),
+                Statement::Select(_)
+            ));
+        }
+
+        #[test]
+        fn create() {
+            assert!(matches!(
+                parse_command::<Statement>("create table t (a int);"),
+                Statement::Create(_)
+            ));
+        }
+
+        #[test]
+        fn insert() {
+            assert!(matches!(
+                parse_command::<Statement>("insert into t values (1);"),
+                Statement::Insert(_)
+            ));
+        }
+
+        #[test]
+        fn drop() {
+            assert!(matches!(
+                parse_command::<Statement>("DROP TABLE t;"),
+                Statement::Drop(DropStatement::Table { .. })
+            ));
+        }
+
+        #[rstest]
+        #[case("update t set a = 1;")]
+        #[case("t;")]
+        #[case("1;")]
+        fn unknown(#[case] command: &str) {
+            let e = lib_parse::parse_str::<Statement, CommonToken>(command).unwrap_err();
+
+            assert!(
+                e.to_string()
+                    .contains("expected one of: select, create, insert, drop"),
+                "{e}"
+            );
+        }
+
+        #[test]
+        fn empty() {
+            assert!(lib_parse::parse_str::<Statement, CommonToken>("").is_err());
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Pattern matching for the `LIKE` and `GLOB` operators.
+
+/// Match `text` against a `LIKE` pattern, where `%` matches any sequence of characters (including
+/// none) and `_` matches exactly one character. ASCII characters are compared case-insensitively.
+pub fn like(pattern: &str, text: &str) -> bool {
+    matches(pattern, text, '%', '_', |a, b| a.eq_ignore_ascii_case(&b))
+}
+
+/// Match `text` against a `GLOB` pattern, where `*` matches any sequence of characters (including
+/// none) and `?` matches exactly one character. Characters are compared case-sensitively.
+pub fn glob(pattern: &str, text: &str) -> bool {
+    matches(pattern, text, '*', '?', |a, b| a == b)
+}
+
+/// Match `text` against `pattern`, where `many` matches any sequence of characters and `one`
+/// matches a single character.
+///
+/// Rather than recursing at each `many`, only the most recent `many` is retried, consuming one
+/// more character of the text each time. This is sufficient, as an earlier `many` can never need
+/// to consume more text for a later part of the pattern to match.
+fn matches(
+    pattern: &str,
+    text: &str,
+    many: char,
+    one: char,
+    eq: impl Fn(char, char) -> bool,
+) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    let (mut p, mut t) = (0, 0);
+    // Position in the pattern following the most recent `many`, and the position in the text it
+    // was retried from.
+    let mut retry = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(&c) if c == many => {
+                p += 1;
+                retry = Some((p, t));
+            }
+            Some(&c) if c == one || eq(c, text[t]) => {
+                p += 1;
+                t += 1;
+            }
+            _ => {
+                let Some((retry_p, retry_t)) = retry else {
+                    return false;
+                };
+
+                p = retry_p;
+                t = retry_t + 1;
+                retry = Some((retry_p, t));
+            }
+        }
+    }
+
+    // Any remaining pattern can only match the empty end of the text.
+    pattern[p..].iter().all(|c| *c == many)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("a%", "alice", true)]
+    #[case("a%", "a", true)]
+    #[case("a%", "bob", false)]
+    #[case("%e", "alice", true)]
+    #[case("%e", "bob", false)]
+    #[case("%li%", "alice", true)]
+    #[case("%li%", "bob", false)]
+    #[case("b_b", "bob", true)]
+    #[case("b_b", "bb", false)]
+    #[case("b_b", "boob", false)]
+    #[case("_", "", false)]
+    #[case("%", "", true)]
+    #[case("", "", true)]
+    #[case("", "a", false)]
+    #[case("A%", "alice", true)]
+    #[case("ALICE", "alice", true)]
+    #[case("%a%a%", "banana", true)]
+    #[case("%a%a%a%a%", "banana", false)]
+    #[case("%ana", "banana", true)]
+    #[case("é%", "É", false)]
+    #[case("*", "*", true)]
+    fn like(#[case] pattern: &str, #[case] text: &str, #[case] expected: bool) {
+        assert_eq!(super::like(pattern, text), expected);
+    }
+
+    #[rstest]
+    #[case("a*", "alice", true)]
+    #[case("*e", "alice", true)]
+    #[case("*e", "bob", false)]
+    #[case("b?b", "bob", true)]
+    #[case("b?b", "bb", false)]
+    #[case("A*", "alice", false)]
+    #[case("*", "", true)]
+    #[case("%", "alice", false)]
+    #[case("%", "%", true)]
+    fn glob(#[case] pattern: &str, #[case] text: &str, #[case] expected: bool) {
+        assert_eq!(super::glob(pattern, text), expected);
+    }
+}
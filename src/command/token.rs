@@ -157,5 +157,12 @@ define_tokens! {
         [*] Asterisk
         [,] Comma
         [;] Semicolon
+        [.] Period
+        [=] Equals
+        [!=] NotEquals
+        [<] LessThan
+        [<=] LessThanEquals
+        [>] GreaterThan
+        [>=] GreaterThanEquals
     }
 }
@@ -0,0 +1,137 @@
+use std::{
+    fs::File,
+    io::{self, Cursor, Read, Seek, SeekFrom},
+    path::Path,
+    rc::Rc,
+};
+
+use derive_more::Deref;
+use memmap2::Mmap;
+
+/// Read-only [`Source`](super::pager::Source) backed by a memory mapping of a database file.
+/// A [`Pager`](super::pager::Pager) over this source serves pages as borrows into the mapping,
+/// without copying them.
+#[derive(Debug)]
+pub struct MmapSource(Cursor<Mapping>);
+
+/// Shared, read-only memory mapping of a database file.
+#[derive(Clone, Debug, Deref)]
+#[deref(forward)]
+pub struct Mapping(Rc<Mmap>);
+
+impl AsRef<[u8]> for Mapping {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl MmapSource {
+    /// Map the file at `path` into memory.
+    #[allow(unused)]
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+
+        // SAFETY: The mapping is read-only, and the database file is not expected to be modified
+        // by another process whilst it's open. A concurrent modification could be observed as a
+        // torn page, but not as a change to the length of the mapping.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        Ok(Self(Cursor::new(Mapping(Rc::new(mmap)))))
+    }
+
+    /// Length of the mapped file.
+    #[allow(unused)]
+    pub fn len(&self) -> usize {
+        self.0.get_ref().len()
+    }
+
+    /// Mapping of the file, which pages can borrow from.
+    pub fn mapping(&self) -> &Mapping {
+        self.0.get_ref()
+    }
+}
+
+impl Read for MmapSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Seek for MmapSource {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    use crate::ctx::{Ctx, pager::PagerError};
+
+    #[rstest]
+    #[case("btree.db")]
+    #[case("overflow.db")]
+    #[case("reserved.db")]
+    fn matches_file(#[case] fixture: &str) {
+        let path = format!("fixtures/{fixture}");
+
+        let source = MmapSource::open(&path).unwrap();
+        assert_eq!(source.len() as u64, std::fs::metadata(&path).unwrap().len());
+
+        let mmap = Ctx::new(source);
+        let file = Ctx::new(File::open(&path).unwrap());
+
+        assert_eq!(mmap.header.to_bytes(), file.header.to_bytes());
+
+        for page_id in 1..=file.header.page_count() {
+            assert_eq!(
+                mmap.pager.get_page(page_id).unwrap().raw(),
+                file.pager.get_page(page_id).unwrap().raw(),
+                "page {page_id}"
+            );
+        }
+    }
+
+    #[test]
+    fn borrows_from_mapping() {
+        let source = MmapSource::open("fixtures/btree.db").unwrap();
+        let mapping = source.mapping().clone();
+        let ctx = Ctx::new(source);
+
+        for page_id in 1..=ctx.header.page_count() {
+            let page = ctx.pager.get_page(page_id).unwrap();
+            let offset = (page_id as usize - 1) * 512;
+
+            assert_eq!(
+                page.raw().as_ptr(),
+                mapping[offset..].as_ptr(),
+                "page {page_id}"
+            );
+        }
+    }
+
+    #[test]
+    fn read_past_end() {
+        let path = "fixtures/btree.db";
+        let mmap = Ctx::new(MmapSource::open(path).unwrap());
+        let page_id = mmap.header.page_count() + 1;
+
+        assert!(matches!(
+            mmap.pager.get_page(page_id),
+            Err(PagerError::Read { page_id: id, source })
+                if id == page_id && source.kind() == io::ErrorKind::UnexpectedEof
+        ));
+        assert!(matches!(
+            Ctx::new(File::open(path).unwrap()).pager.get_page(page_id),
+            Err(PagerError::Read { source, .. }) if source.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(MmapSource::open("fixtures/missing.db").is_err());
+    }
+}
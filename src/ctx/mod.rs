@@ -1,9 +1,11 @@
 use std::io::SeekFrom;
 
-use pager::{Pager, Source};
+use pager::{Pager, PagerError, Source};
 
 use crate::disk::header::SqliteHeader;
 
+#[cfg(feature = "mmap")]
+pub mod mmap;
 pub mod pager;
 
 #[derive(Clone, Debug)]
@@ -23,8 +25,117 @@ impl Ctx {
         };
 
         Self {
-            pager: Pager::new(source, header.page_size() as usize),
+            pager: Pager::with_reserved_space(
+                source,
+                header.page_size() as usize,
+                header.page_end_padding() as usize,
+            ),
             header: header.clone(),
         }
     }
+
+    /// Create a context which uses the provided page size, rather than the page size stored in
+    /// the header. The rest of the header is read without validation. This allows a database with
+    /// a corrupt header to be read, if the size of its pages is known.
+    ///
+    /// Will fail if the header can't be read from the source, or if `page_size` isn't valid.
+    #[allow(unused)]
+    pub fn with_page_size(mut source: impl Source, page_size: u32) -> Result<Self, PagerError> {
+        let header = {
+            let mut header_buf = [0; 100];
+            source
+                .seek(SeekFrom::Start(0))
+                .and_then(|_| source.read_exact(&mut header_buf))
+                .map_err(|source| PagerError::Read { page_id: 1, source })?;
+            SqliteHeader::read_with_page_size(&header_buf, page_size)?
+        };
+
+        Ok(Self {
+            pager: Pager::with_reserved_space(
+                source,
+                page_size as usize,
+                header.page_end_padding() as usize,
+            ),
+            header,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    use crate::{
+        btree::{
+            self,
+            page::{Page, PageExt, Table},
+        },
+        disk::header::{SchemaFormat, SqliteHeaderError, TextEncoding},
+    };
+
+    /// Read `btree.db` (which has a page size of 512), with its header corrupted.
+    fn corrupt_btree() -> Cursor<Vec<u8>> {
+        let mut bytes = std::fs::read("fixtures/btree.db").unwrap();
+
+        // Page size.
+        bytes[16..18].copy_from_slice(&[0x12, 0x34]);
+        // Maximum embedded payload fraction.
+        bytes[21] = 0xff;
+        // Schema format.
+        bytes[44..48].copy_from_slice(&0xdead_u32.to_be_bytes());
+        // Text encoding.
+        bytes[56..60].copy_from_slice(&9_u32.to_be_bytes());
+
+        Cursor::new(bytes)
+    }
+
+    #[test]
+    #[should_panic]
+    fn corrupt_header() {
+        Ctx::new(corrupt_btree());
+    }
+
+    #[test]
+    fn with_page_size() {
+        let ctx = Ctx::with_page_size(corrupt_btree(), 512).unwrap();
+        assert_eq!(ctx.header.page_size(), 512);
+        assert_eq!(ctx.header.schema_format(), SchemaFormat::V4);
+        assert_eq!(ctx.header.text_encoding(), TextEncoding::Utf8);
+
+        // Traverse the `items` table.
+        let page = Page::<Table>::from_buffer(ctx.pager.get_page(2).unwrap());
+        let row_ids = btree::traverse(ctx, page)
+            .map(|cell| i64::from(cell.row_id))
+            .collect::<Vec<_>>();
+
+        assert_eq!(row_ids, (1..=1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn with_page_size_65536() {
+        let ctx = Ctx::with_page_size(corrupt_btree(), 65536).unwrap();
+        assert_eq!(ctx.header.page_size(), 65536);
+    }
+
+    #[test]
+    fn with_invalid_page_size() {
+        assert!(matches!(
+            Ctx::with_page_size(corrupt_btree(), 1000),
+            Err(PagerError::Header(SqliteHeaderError::PageSize(_)))
+        ));
+    }
+
+    #[test]
+    fn with_page_size_truncated() {
+        let mut source = corrupt_btree();
+        source.get_mut().truncate(50);
+
+        assert!(matches!(
+            Ctx::with_page_size(source, 512),
+            Err(PagerError::Read { page_id: 1, source })
+                if source.kind() == std::io::ErrorKind::UnexpectedEof
+        ));
+    }
 }
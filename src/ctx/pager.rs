@@ -3,11 +3,17 @@ use std::{
     cell::RefCell,
     collections::HashMap,
     fmt::Debug,
-    io::{Read, Seek, SeekFrom},
+    io::{self, Cursor, Read, Seek, SeekFrom},
     ops::Deref,
     rc::Rc,
 };
 
+use thiserror::Error;
+
+#[cfg(feature = "mmap")]
+use super::mmap::{Mapping, MmapSource};
+use crate::disk::header::{SQLITE_HEADER_SIZE, SqliteHeader, SqliteHeaderError};
+
 #[derive(Clone, Debug)]
 pub struct Pager(Rc<PagerInner>);
 
@@ -19,66 +25,258 @@ struct PagerInner {
     /// Configured page size.
     page_size: usize,
 
+    /// Number of bytes reserved at the end of each page, which are excluded from page buffers.
+    reserved_space: usize,
+
     /// Loaded pages.
     pages: RefCell<HashMap<u32, PageBuffer>>,
+
+    /// Mapping of the source, which pages will borrow from rather than being read.
+    #[cfg(feature = "mmap")]
+    mapping: Option<Mapping>,
 }
 
 impl Pager {
     /// Create a new pager with the provided source. This will configure the pager to use the
-    /// correct page size based on the header.
+    /// correct page size based on the header, with no reserved space at the end of each page.
+    #[allow(unused)]
     pub fn new(source: impl Source, page_size: usize) -> Self {
+        Self::with_reserved_space(source, page_size, 0)
+    }
+
+    /// Create a new pager where `reserved_space` bytes at the end of each page are unusable (as
+    /// configured by `page_end_padding` in the header).
+    ///
+    /// If `source` is an `MmapSource` (with the `mmap` feature), pages will borrow directly from
+    /// its mapping rather than being copied out of it.
+    pub fn with_reserved_space(
+        source: impl Source,
+        page_size: usize,
+        reserved_space: usize,
+    ) -> Self {
+        assert!(
+            reserved_space < page_size,
+            "reserved space must leave some of the page usable"
+        );
+
+        #[cfg(feature = "mmap")]
+        let mapping = (&source as &dyn std::any::Any)
+            .downcast_ref::<MmapSource>()
+            .map(|source| source.mapping().clone());
+
         Self(Rc::new(PagerInner {
             source: RefCell::new(Box::new(source)),
             page_size,
+            reserved_space,
             pages: RefCell::new(HashMap::new()),
+            #[cfg(feature = "mmap")]
+            mapping,
         }))
     }
 
-    /// Read the requested page, and write it to `buf`. It is expected that `buf` is large enough
-    /// to hold the entire page, so it should be created with [`Self::new_page_buffer`].
-    pub fn get_page(&self, page_id: u32) -> PageBuffer {
-        self.0
-            .pages
-            .borrow_mut()
-            .entry(page_id)
-            .or_insert_with(|| {
-                let mut buf = self.0.new_page_buffer();
-
-                // Borrow the source to use it.
-                let mut source = self.0.source.borrow_mut();
-
-                // Seek to the correct position.
-                let offset = (self.0.page_size as u32 * (page_id - 1)) as u64;
-                source.seek(SeekFrom::Start(offset)).unwrap();
-
-                {
-                    // Temporarily mutate the buffer whilst there's no other references.
-                    let buf = Rc::get_mut(&mut buf.0).unwrap();
-
-                    // Fill the buffer.
-                    source.read_exact(&mut buf.buffer).unwrap();
-
-                    // Fix the buffer's size, if the offset means a full page won't be read (page 0).
-                    buf.offset = if page_id == 1 {
-                        crate::disk::header::SQLITE_HEADER_SIZE
-                    } else {
-                        0
-                    };
-                }
+    /// Number of usable bytes in each page, excluding the reserved space at the end.
+    #[allow(unused)]
+    pub fn usable_len(&self) -> usize {
+        self.0.page_size - self.0.reserved_space
+    }
+
+    /// Create a new pager over an in-memory database. The page size is read from the header at the
+    /// beginning of `bytes`, which will fail if it's truncated or invalid.
+    #[allow(unused)]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, PagerError> {
+        let header = SqliteHeader::read_from_buffer(&bytes[..SQLITE_HEADER_SIZE.min(bytes.len())])?;
+
+        Ok(Self::with_reserved_space(
+            Cursor::new(bytes),
+            header.page_size() as usize,
+            header.page_end_padding() as usize,
+        ))
+    }
+
+    /// Read the header directly from the source, bypassing the page cache. This will observe any
+    /// changes made to the header since the pager was created.
+    pub fn read_header(&self) -> Result<SqliteHeader, PagerError> {
+        let mut buf = [0; SQLITE_HEADER_SIZE];
+
+        let mut source = self.0.source.borrow_mut();
+        source
+            .seek(SeekFrom::Start(0))
+            .and_then(|_| source.read_exact(&mut buf))
+            .map_err(|source| PagerError::Read { page_id: 1, source })?;
+
+        Ok(SqliteHeader::read_from_buffer(&buf)?)
+    }
+
+    /// Read the requested page, or produce it from the cache if it has already been read.
+    pub fn get_page(&self, page_id: u32) -> Result<PageBuffer, PagerError> {
+        if let Some(page) = self.0.pages.borrow().get(&page_id) {
+            return Ok(page.clone());
+        }
+
+        let buf = PageBuffer(Rc::new(PageBufferInner {
+            // Fix the buffer's size, if the offset means a full page won't be read (page 0).
+            offset: PageBufferInner::offset(page_id),
+            reserved_space: self.0.reserved_space,
+            buffer: self
+                .0
+                .read_page(page_id)
+                .map_err(|source| PagerError::Read { page_id, source })?,
+        }));
+
+        self.0.pages.borrow_mut().insert(page_id, buf.clone());
+
+        Ok(buf)
+    }
+
+    /// Read the requested page, distinguishing a page which lies entirely beyond the end of the
+    /// source (`None`) from one which is only partially present, such as a final page which was
+    /// only partially written ([`PagerError::ShortRead`]).
+    #[allow(unused)]
+    pub fn read_exact_page(&self, page_id: u32) -> Result<Option<PageBuffer>, PagerError> {
+        if !self.0.pages.borrow().contains_key(&page_id) {
+            let length = self
+                .0
+                .source_len()
+                .map_err(|source| PagerError::Read { page_id, source })?;
+            let offset = self.0.page_offset(page_id);
+
+            if offset >= length {
+                return Ok(None);
+            }
+
+            if offset + self.0.page_size as u64 > length {
+                return Err(PagerError::ShortRead {
+                    page_id,
+                    available: (length - offset) as usize,
+                });
+            }
+        }
+
+        self.get_page(page_id).map(Some)
+    }
+
+    /// Read the requested pages into the cache, so that subsequent calls to [`Self::get_page`]
+    /// won't access the source. Each run of consecutive page ids is read from the source with a
+    /// single seek and read. Pages which are already cached are skipped.
+    ///
+    /// If a run can't be read, the error refers to the first page of the run. Any runs read before
+    /// it remain cached.
+    ///
+    /// Has no effect if the source is mapped, as its pages are already in memory.
+    pub fn prefetch(&self, page_ids: &[u32]) -> Result<(), PagerError> {
+        #[cfg(feature = "mmap")]
+        if self.0.mapping.is_some() {
+            return Ok(());
+        }
+
+        let mut pages = self.0.pages.borrow_mut();
+
+        let mut page_ids = page_ids
+            .iter()
+            .copied()
+            .filter(|page_id| !pages.contains_key(page_id))
+            .collect::<Vec<_>>();
+        page_ids.sort_unstable();
+        page_ids.dedup();
+
+        for run in page_ids.chunk_by(|a, b| a + 1 == *b) {
+            let mut buf = vec![0; self.0.page_size * run.len()];
+
+            self.0
+                .read_at(run[0], &mut buf)
+                .map_err(|source| PagerError::Read {
+                    page_id: run[0],
+                    source,
+                })?;
+
+            for (page_id, buffer) in run.iter().zip(buf.chunks_exact(self.0.page_size)) {
+                pages.insert(
+                    *page_id,
+                    PageBuffer(Rc::new(PageBufferInner {
+                        offset: PageBufferInner::offset(*page_id),
+                        reserved_space: self.0.reserved_space,
+                        buffer: PageStorage::Owned(buffer.to_vec()),
+                    })),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drop the requested page from the cache, so that the next call to [`Self::get_page`] will
+    /// read it from the source again. Any [`PageBuffer`] already handed out is unaffected.
+    #[allow(unused)]
+    pub fn invalidate(&self, page_id: u32) {
+        self.0.pages.borrow_mut().remove(&page_id);
+    }
 
-                buf
-            })
-            .clone()
+    /// Drop every page from the cache, such as after the source has been modified.
+    #[allow(unused)]
+    pub fn clear_cache(&self) {
+        self.0.pages.borrow_mut().clear();
     }
 }
 
 impl PagerInner {
-    /// Create a new buffer suitable for holding a page.
-    fn new_page_buffer(&self) -> PageBuffer {
-        PageBuffer::new(self.page_size)
+    /// Produce the contents of `page_id`, borrowing it from the mapping if there is one, or
+    /// otherwise reading it from the source.
+    fn read_page(&self, page_id: u32) -> io::Result<PageStorage> {
+        #[cfg(feature = "mmap")]
+        if let Some(mapping) = &self.mapping {
+            let start = self.page_offset(page_id) as usize;
+            let range = start..start + self.page_size;
+
+            // Match the error produced by a short read of the source.
+            if range.end > mapping.len() {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+
+            return Ok(PageStorage::Mapped {
+                mapping: mapping.clone(),
+                range,
+            });
+        }
+
+        let mut buf = vec![0; self.page_size];
+        self.read_at(page_id, &mut buf)?;
+
+        Ok(PageStorage::Owned(buf))
+    }
+
+    /// Fill `buf` from the source, beginning at the start of `page_id`.
+    fn read_at(&self, page_id: u32, buf: &mut [u8]) -> io::Result<()> {
+        let mut source = self.source.borrow_mut();
+
+        source.seek(SeekFrom::Start(self.page_offset(page_id)))?;
+        source.read_exact(buf)
+    }
+
+    /// Determine the length of the source.
+    fn source_len(&self) -> io::Result<u64> {
+        self.source.borrow_mut().seek(SeekFrom::End(0))
+    }
+
+    /// Calculate the offset of a page within the source. This is calculated with [`u64`], as the
+    /// offset of pages in large databases won't fit within a 32-bit [`usize`].
+    fn page_offset(&self, page_id: u32) -> u64 {
+        assert_ne!(page_id, 0, "page ids begin at 1");
+
+        self.page_size as u64 * (page_id as u64 - 1)
     }
 }
 
+/// Error produced whilst reading from the source of a [`Pager`].
+#[derive(Debug, Error)]
+pub enum PagerError {
+    #[error("failed to read page {page_id}: {source}")]
+    Read { page_id: u32, source: io::Error },
+    #[error("page {page_id} is incomplete ({available} bytes available)")]
+    ShortRead { page_id: u32, available: usize },
+    #[error(transparent)]
+    Header(#[from] SqliteHeaderError),
+}
+
 pub trait Source: 'static + Read + Seek + Debug {}
 impl<T> Source for T where T: 'static + Read + Seek + Debug {}
 
@@ -90,32 +288,489 @@ pub struct PageBufferInner {
     /// Additional offset to apply to every slice.
     offset: usize,
 
+    /// Number of bytes at the end of the buffer to exclude from every slice.
+    reserved_space: usize,
+
     /// Underlying data.
-    buffer: Vec<u8>,
+    buffer: PageStorage,
+}
+
+/// Bytes of a page, which are either owned or borrowed from a mapping of the source.
+#[derive(Debug)]
+enum PageStorage {
+    Owned(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped {
+        mapping: Mapping,
+        range: std::ops::Range<usize>,
+    },
+}
+
+impl Deref for PageStorage {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            PageStorage::Owned(buffer) => buffer,
+            #[cfg(feature = "mmap")]
+            PageStorage::Mapped { mapping, range } => &mapping[range.clone()],
+        }
+    }
 }
 
+#[cfg(test)]
 impl PageBuffer {
-    fn new(size: usize) -> Self {
+    /// Wrap a page which was constructed in memory, rather than read from a source.
+    pub fn from_bytes(buffer: Vec<u8>) -> Self {
         Self(Rc::new(PageBufferInner {
             offset: 0,
-            buffer: vec![0; size],
+            reserved_space: 0,
+            buffer: PageStorage::Owned(buffer),
         }))
     }
 }
 
 impl PageBufferInner {
+    /// Offset to apply to a page, so the header isn't included in the first page.
+    ///
+    /// This is the only place the database header is accounted for. Offsets relative to the
+    /// B-Tree header should index the buffer directly, whilst offsets stored within the page (such
+    /// as cell pointers, which are relative to the start of the page) should index [`Self::raw`].
+    fn offset(page_id: u32) -> usize {
+        if page_id == 1 { SQLITE_HEADER_SIZE } else { 0 }
+    }
+
     /// Produce the full buffer, even if it has an offset applied to it.
     ///
     /// This is useful for processing offsets stored directly within the binary.
     pub fn raw(&self) -> &[u8] {
         &self.buffer
     }
+
+    /// Number of usable bytes in the full buffer, excluding the reserved space at the end. Any
+    /// slice of [`Self::raw`] containing page content should be bound by this.
+    pub fn usable_len(&self) -> usize {
+        self.buffer.len() - self.reserved_space
+    }
 }
 
 impl Deref for PageBufferInner {
     type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
-        &self.buffer[self.offset..]
+        &self.buffer[self.offset..self.usable_len()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    use crate::disk::header::BinaryError;
+
+    mod from_bytes {
+        use super::*;
+
+        #[test]
+        fn read_header() {
+            let pager = Pager::from_bytes(include_bytes!("../../test.db").to_vec()).unwrap();
+            assert_eq!(pager.0.page_size, 4096);
+
+            let page = pager.get_page(1).unwrap();
+            let header = SqliteHeader::read_from_buffer(&page.raw()[..SQLITE_HEADER_SIZE]).unwrap();
+            assert_eq!(header.page_count(), 5);
+
+            // Header is excluded from the first page.
+            assert_eq!(page.len(), 4096 - SQLITE_HEADER_SIZE);
+        }
+
+        #[test]
+        fn read_page() {
+            let pager = Pager::from_bytes(include_bytes!("../../test.db").to_vec()).unwrap();
+
+            // Table leaf page.
+            assert_eq!(pager.get_page(2).unwrap()[0], 0x0d);
+        }
+
+        #[rstest]
+        #[case::empty(0)]
+        #[case::partial_header(50)]
+        #[case::one_short(SQLITE_HEADER_SIZE - 1)]
+        fn truncated_header(#[case] length: usize) {
+            let mut bytes = include_bytes!("../../test.db").to_vec();
+            bytes.truncate(length);
+
+            assert!(matches!(
+                Pager::from_bytes(bytes),
+                Err(PagerError::Header(SqliteHeaderError::Binary(
+                    BinaryError::Size
+                )))
+            ));
+        }
+
+        #[test]
+        fn invalid_header() {
+            let mut bytes = include_bytes!("../../test.db").to_vec();
+            bytes[0] = b'X';
+
+            assert!(matches!(
+                Pager::from_bytes(bytes),
+                Err(PagerError::Header(SqliteHeaderError::HeaderString(_)))
+            ));
+        }
+    }
+
+    mod reserved_space {
+        use super::*;
+
+        fn pager() -> Pager {
+            Pager::from_bytes(std::fs::read("fixtures/reserved.db").unwrap()).unwrap()
+        }
+
+        #[test]
+        fn usable_len() {
+            let pager = pager();
+
+            assert_eq!(pager.0.page_size, 512);
+            assert_eq!(pager.usable_len(), 512 - 32);
+        }
+
+        #[rstest]
+        #[case::first(1, 512 - 32 - SQLITE_HEADER_SIZE)]
+        #[case::other(2, 512 - 32)]
+        fn excluded_from_page(#[case] page_id: u32, #[case] expected: usize) {
+            let page = pager().get_page(page_id).unwrap();
+
+            assert_eq!(page.len(), expected);
+            assert_eq!(page.usable_len(), 512 - 32);
+            assert_eq!(page.raw().len(), 512);
+        }
+
+        #[test]
+        fn excluded_from_prefetch() {
+            let pager = pager();
+            pager.prefetch(&[1, 2]).unwrap();
+
+            assert_eq!(pager.get_page(2).unwrap().len(), 512 - 32);
+        }
+    }
+
+    mod prefetch {
+        use std::cell::Cell;
+
+        use super::*;
+
+        /// Source which counts the number of seeks performed on it.
+        #[derive(Debug)]
+        struct CountingSource {
+            inner: Cursor<Vec<u8>>,
+            seeks: Rc<Cell<usize>>,
+        }
+
+        impl Read for CountingSource {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.inner.read(buf)
+            }
+        }
+
+        impl Seek for CountingSource {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                self.seeks.set(self.seeks.get() + 1);
+                self.inner.seek(pos)
+            }
+        }
+
+        /// Create a pager over the B-Tree fixture, which has a page size of 512, along with a
+        /// count of the seeks performed on its source.
+        fn counting_pager() -> (Pager, Rc<Cell<usize>>) {
+            let seeks = Rc::new(Cell::new(0));
+            let source = CountingSource {
+                inner: Cursor::new(std::fs::read("fixtures/btree.db").unwrap()),
+                seeks: seeks.clone(),
+            };
+
+            (Pager::new(source, 512), seeks)
+        }
+
+        #[rstest]
+        #[case::contiguous(&[2, 3, 4], 1)]
+        #[case::unordered(&[4, 2, 3], 1)]
+        #[case::gap(&[2, 3, 6], 2)]
+        #[case::duplicates(&[5, 5, 5], 1)]
+        #[case::empty(&[], 0)]
+        fn seeks_per_run(#[case] page_ids: &[u32], #[case] expected: usize) {
+            let (pager, seeks) = counting_pager();
+
+            pager.prefetch(page_ids).unwrap();
+            assert_eq!(seeks.get(), expected);
+        }
+
+        #[test]
+        fn served_from_cache() {
+            let (pager, seeks) = counting_pager();
+            let (reference, _) = counting_pager();
+
+            pager.prefetch(&[1, 2, 3, 4]).unwrap();
+            assert_eq!(seeks.get(), 1);
+
+            for page_id in 1..=4 {
+                let page = pager.get_page(page_id).unwrap();
+
+                assert_eq!(page[..], reference.get_page(page_id).unwrap()[..]);
+                assert_eq!(page.raw(), reference.get_page(page_id).unwrap().raw());
+            }
+
+            // No further seeks were required.
+            assert_eq!(seeks.get(), 1);
+        }
+
+        #[test]
+        fn skips_cached() {
+            let (pager, seeks) = counting_pager();
+
+            pager.get_page(3).unwrap();
+            assert_eq!(seeks.get(), 1);
+
+            // Page 3 is already cached, splitting the remaining pages into two runs.
+            pager.prefetch(&[2, 3, 4]).unwrap();
+            assert_eq!(seeks.get(), 3);
+        }
+    }
+
+    mod invalidate {
+        use std::cell::RefCell;
+
+        use super::*;
+
+        /// Source which can be modified after the pager has been created.
+        #[derive(Clone, Debug)]
+        struct SharedSource(Rc<RefCell<Cursor<Vec<u8>>>>);
+
+        impl Read for SharedSource {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.0.borrow_mut().read(buf)
+            }
+        }
+
+        impl Seek for SharedSource {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                self.0.borrow_mut().seek(pos)
+            }
+        }
+
+        /// Create a pager over three 512 byte pages, filled with `1`, `2` and `3` respectively.
+        fn shared_pager() -> (Pager, SharedSource) {
+            let bytes = (1..=3).flat_map(|page| [page; 512]).collect::<Vec<_>>();
+            let source = SharedSource(Rc::new(RefCell::new(Cursor::new(bytes))));
+
+            (Pager::new(source.clone(), 512), source)
+        }
+
+        /// Overwrite every byte of a page in the source.
+        fn overwrite(source: &SharedSource, page_id: u32, value: u8) {
+            let offset = (page_id as usize - 1) * 512;
+            source.0.borrow_mut().get_mut()[offset..offset + 512].fill(value);
+        }
+
+        #[test]
+        fn rereads_page() {
+            let (pager, source) = shared_pager();
+            assert_eq!(pager.get_page(2).unwrap()[0], 2);
+
+            overwrite(&source, 2, 0xff);
+            assert_eq!(pager.get_page(2).unwrap()[0], 2);
+
+            pager.invalidate(2);
+            assert_eq!(pager.get_page(2).unwrap()[0], 0xff);
+        }
+
+        #[test]
+        fn only_requested_page() {
+            let (pager, source) = shared_pager();
+            pager.get_page(2).unwrap();
+            pager.get_page(3).unwrap();
+
+            overwrite(&source, 2, 0xff);
+            overwrite(&source, 3, 0xff);
+            pager.invalidate(2);
+
+            assert_eq!(pager.get_page(2).unwrap()[0], 0xff);
+            assert_eq!(pager.get_page(3).unwrap()[0], 3);
+        }
+
+        #[test]
+        fn existing_buffers_unaffected() {
+            let (pager, source) = shared_pager();
+            let page = pager.get_page(2).unwrap();
+
+            overwrite(&source, 2, 0xff);
+            pager.invalidate(2);
+
+            assert_eq!(page[0], 2);
+            assert_eq!(pager.get_page(2).unwrap()[0], 0xff);
+        }
+
+        #[test]
+        fn clear_cache() {
+            let (pager, source) = shared_pager();
+            pager.prefetch(&[2, 3]).unwrap();
+
+            overwrite(&source, 2, 0xff);
+            overwrite(&source, 3, 0xfe);
+            pager.clear_cache();
+
+            assert_eq!(pager.get_page(2).unwrap()[0], 0xff);
+            assert_eq!(pager.get_page(3).unwrap()[0], 0xfe);
+        }
+    }
+
+    mod errors {
+        use super::*;
+
+        /// Source which fails to seek beyond `limit`, but otherwise reads from `inner`.
+        #[derive(Debug)]
+        struct FailingSource {
+            inner: Cursor<Vec<u8>>,
+            limit: u64,
+        }
+
+        impl Read for FailingSource {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.inner.read(buf)
+            }
+        }
+
+        impl Seek for FailingSource {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                match pos {
+                    SeekFrom::Start(offset) if offset > self.limit => {
+                        Err(io::Error::other("seek failed"))
+                    }
+                    pos => self.inner.seek(pos),
+                }
+            }
+        }
+
+        /// Create a pager over the B-Tree fixture, where pages beyond `page_id` can't be sought to.
+        fn failing_pager(page_id: u32) -> Pager {
+            let source = FailingSource {
+                inner: Cursor::new(std::fs::read("fixtures/btree.db").unwrap()),
+                limit: (page_id as u64 - 1) * 512,
+            };
+
+            Pager::new(source, 512)
+        }
+
+        #[test]
+        fn seek() {
+            let pager = failing_pager(2);
+
+            assert!(pager.get_page(2).is_ok());
+            assert!(matches!(
+                pager.get_page(3),
+                Err(PagerError::Read { page_id: 3, source }) if source.to_string() == "seek failed"
+            ));
+        }
+
+        #[test]
+        fn read_past_end() {
+            let pager = Pager::from_bytes(std::fs::read("fixtures/btree.db").unwrap()).unwrap();
+
+            let e = pager.get_page(u32::MAX).unwrap_err();
+            assert!(matches!(
+                &e,
+                PagerError::Read { page_id: u32::MAX, source }
+                    if source.kind() == io::ErrorKind::UnexpectedEof
+            ));
+            assert!(
+                e.to_string()
+                    .starts_with("failed to read page 4294967295: ")
+            );
+        }
+
+        #[test]
+        fn not_cached() {
+            let pager = failing_pager(2);
+
+            assert!(pager.get_page(3).is_err());
+            assert!(pager.get_page(3).is_err());
+        }
+
+        #[test]
+        fn prefetch() {
+            let pager = failing_pager(3);
+
+            assert!(matches!(
+                pager.prefetch(&[2, 3, 5, 6]),
+                Err(PagerError::Read { page_id: 5, .. })
+            ));
+
+            // The run read before the failure is cached.
+            assert!(pager.0.pages.borrow().contains_key(&2));
+            assert!(pager.0.pages.borrow().contains_key(&3));
+        }
+    }
+
+    mod read_exact_page {
+        use super::*;
+
+        /// Pager over `btree.db` (which has a page size of 512), truncated to `length` bytes.
+        fn truncated(length: usize) -> Pager {
+            let mut bytes = std::fs::read("fixtures/btree.db").unwrap();
+            bytes.truncate(length);
+
+            Pager::new(Cursor::new(bytes), 512)
+        }
+
+        #[test]
+        fn present() {
+            let pager = truncated(512 * 3 + 100);
+
+            assert_eq!(
+                pager.read_exact_page(3).unwrap().unwrap().raw(),
+                pager.get_page(3).unwrap().raw()
+            );
+        }
+
+        #[rstest]
+        #[case::after_partial_page(512 * 3 + 100, 5)]
+        #[case::page_boundary(512 * 3, 4)]
+        #[case::far_beyond(512 * 3, 100)]
+        fn beyond_end(#[case] length: usize, #[case] page_id: u32) {
+            assert!(
+                truncated(length)
+                    .read_exact_page(page_id)
+                    .unwrap()
+                    .is_none()
+            );
+        }
+
+        #[rstest]
+        #[case(512 * 3 + 100, 100)]
+        #[case(512 * 3 + 1, 1)]
+        #[case(512 * 4 - 1, 511)]
+        fn short_read(#[case] length: usize, #[case] expected: usize) {
+            assert!(matches!(
+                truncated(length).read_exact_page(4),
+                Err(PagerError::ShortRead { page_id: 4, available }) if available == expected
+            ));
+        }
+    }
+
+    mod page_offset {
+        use super::*;
+
+        #[rstest]
+        #[case(4096, 1, 0)]
+        #[case(4096, 2, 4096)]
+        #[case(512, 10, 4608)]
+        #[case(65536, 65537, 1 << 32)]
+        #[case(65536, u32::MAX, 65536 * (u32::MAX as u64 - 1))]
+        fn offset(#[case] page_size: usize, #[case] page_id: u32, #[case] expected: u64) {
+            let pager = Pager::new(Cursor::new(Vec::new()), page_size);
+            assert_eq!(pager.0.page_offset(page_id), expected);
+        }
     }
 }
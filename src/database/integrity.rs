@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use crate::{
+    btree::{
+        self,
+        page::{Index, Page, PageError, PageExt, PageFlag, PageTypeFlag, Table},
+    },
+    ctx::Ctx,
+    database::SchemaError,
+};
+
+/// Walks B-Trees, collecting every structural problem found within them. Each page may only be
+/// visited once across all B-Trees.
+pub(super) struct IntegrityChecker {
+    ctx: Ctx,
+    visited: HashSet<u32>,
+    problems: Vec<IntegrityError>,
+}
+
+impl IntegrityChecker {
+    pub fn new(ctx: Ctx) -> Self {
+        Self {
+            ctx,
+            visited: HashSet::new(),
+            problems: Vec::new(),
+        }
+    }
+
+    /// Check the B-Tree beginning at `root_page`. If `type_flag` isn't provided, the type is taken
+    /// from the root page, and every other page must match it.
+    pub fn check_tree(&mut self, root_page: u32, type_flag: Option<PageTypeFlag>) {
+        self.check_page(root_page, type_flag, 0, (None, None));
+    }
+
+    /// Record a problem found outside of the B-Trees, such as an invalid schema entry.
+    pub fn report(&mut self, problem: IntegrityError) {
+        self.problems.push(problem);
+    }
+
+    /// Whether any problems have been found so far.
+    pub fn has_problems(&self) -> bool {
+        !self.problems.is_empty()
+    }
+
+    /// Produce every problem found so far.
+    pub fn finish(self) -> Vec<IntegrityError> {
+        self.problems
+    }
+
+    /// Check a single page and its children. Row ids within a table page must be within `bounds`,
+    /// which are the (exclusive) lower and (inclusive) upper keys of the parent.
+    fn check_page(
+        &mut self,
+        page_id: u32,
+        type_flag: Option<PageTypeFlag>,
+        depth: usize,
+        bounds: (Option<i64>, Option<i64>),
+    ) {
+        // Pages beyond the end of the file are caught when they can't be read, which is all that
+        // can be done if the page count in the header isn't trustworthy.
+        let beyond_page_count =
+            self.ctx.header.page_count_valid() && page_id > self.ctx.header.page_count();
+
+        if page_id == 0 || beyond_page_count {
+            self.problems.push(IntegrityError::PageNumber(page_id));
+            return;
+        }
+
+        if !self.visited.insert(page_id) {
+            self.problems.push(IntegrityError::Revisited(page_id));
+            return;
+        }
+
+        if depth > btree::MAX_DEPTH {
+            self.problems.push(IntegrityError::Depth(page_id));
+            return;
+        }
+
+        let Ok(buffer) = self.ctx.pager.get_page(page_id) else {
+            self.problems.push(IntegrityError::Unreadable(page_id));
+            return;
+        };
+
+        let flag = buffer[0];
+        let Some(page_flag) = PageFlag::new(flag)
+            .filter(|page_flag| type_flag.is_none_or(|type_flag| page_flag.type_flag == type_flag))
+        else {
+            self.problems.push(IntegrityError::Flag { page_id, flag });
+            return;
+        };
+
+        match page_flag.type_flag {
+            PageTypeFlag::Table => {
+                self.check_table_page(page_id, Page::from_buffer(buffer), depth, bounds)
+            }
+            PageTypeFlag::Index => self.check_index_page(page_id, Page::from_buffer(buffer), depth),
+        }
+    }
+
+    fn check_table_page(
+        &mut self,
+        page_id: u32,
+        page: Page<Table>,
+        depth: usize,
+        (lower, upper): (Option<i64>, Option<i64>),
+    ) {
+        if let Err(source) = page.validate_cell_pointers() {
+            self.problems.push(IntegrityError::Page { page_id, source });
+            return;
+        }
+
+        // Row ids of each cell, alongside the child page which they bound.
+        let cells = match &page {
+            Page::Leaf(leaf_page) => leaf_page
+                .row_ids()
+                .expect("valid cell pointers")
+                .map(|row_id| (i64::from(row_id), None))
+                .collect::<Vec<_>>(),
+            Page::Interior(interior_page) => interior_page
+                .cells()
+                .expect("valid cell pointers")
+                .map(|cell| (cell.row_id, Some(cell.left_pointer)))
+                .collect(),
+        };
+
+        let mut previous = lower;
+        for (row_id, left_pointer) in cells {
+            if previous.is_some_and(|previous| row_id <= previous) {
+                self.problems
+                    .push(IntegrityError::RowIdOrder { page_id, row_id });
+            } else if upper.is_some_and(|upper| row_id > upper) {
+                self.problems
+                    .push(IntegrityError::RowIdRange { page_id, row_id });
+            }
+
+            if let Some(left_pointer) = left_pointer {
+                self.check_page(
+                    left_pointer,
+                    Some(PageTypeFlag::Table),
+                    depth + 1,
+                    (previous, Some(row_id)),
+                );
+            }
+
+            previous = Some(row_id);
+        }
+
+        if let Page::Interior(interior_page) = page {
+            self.check_page(
+                interior_page.right_pointer,
+                Some(PageTypeFlag::Table),
+                depth + 1,
+                (previous, upper),
+            );
+        }
+    }
+
+    fn check_index_page(&mut self, page_id: u32, page: Page<Index>, depth: usize) {
+        if let Err(source) = page.validate_cell_pointers() {
+            self.problems.push(IntegrityError::Page { page_id, source });
+            return;
+        }
+
+        if let Page::Interior(interior_page) = page {
+            let children = interior_page
+                .left_pointers()
+                .expect("valid cell pointers")
+                .chain([interior_page.right_pointer])
+                .collect::<Vec<_>>();
+
+            for child in children {
+                self.check_page(child, Some(PageTypeFlag::Index), depth + 1, (None, None));
+            }
+        }
+    }
+}
+
+/// A problem found within a B-Tree by [`Database::integrity_check`](super::Database::integrity_check).
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum IntegrityError {
+    #[error("page {0} is out of range")]
+    PageNumber(u32),
+    #[error("page {0} is referenced more than once")]
+    Revisited(u32),
+    #[error("page {0} exceeds the maximum B-Tree depth")]
+    Depth(u32),
+    #[error("page {0} couldn't be read")]
+    Unreadable(u32),
+    #[error("page {page_id} has an invalid or unexpected flag: {flag:#04x}")]
+    Flag { page_id: u32, flag: u8 },
+    #[error("page {page_id}: {source}")]
+    Page { page_id: u32, source: PageError },
+    #[error("row id {row_id} on page {page_id} isn't greater than the preceding key")]
+    RowIdOrder { page_id: u32, row_id: i64 },
+    #[error("row id {row_id} on page {page_id} is greater than its key in the parent page")]
+    RowIdRange { page_id: u32, row_id: i64 },
+    #[error("invalid schema entry: {0}")]
+    Schema(SchemaError),
+}
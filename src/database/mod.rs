@@ -0,0 +1,1101 @@
+mod integrity;
+mod schema;
+mod stat1;
+
+use std::{collections::HashMap, io::Read};
+
+use thiserror::Error;
+
+pub use self::{
+    integrity::IntegrityError,
+    schema::{DatabaseSchema, SchemaError, SchemaType, TableInfo},
+    stat1::Stat1Entry,
+};
+
+use crate::{
+    btree::{
+        self, TableCell, TraverseError,
+        page::{Page, PageError, PageTypeFlag, Table},
+        payload::PayloadError,
+    },
+    ctx::{
+        Ctx,
+        pager::{PagerError, Source},
+    },
+    database::integrity::IntegrityChecker,
+    record::{Record, RecordError},
+};
+
+/// Page number of the `sqlite_schema` table's root page.
+const SCHEMA_ROOT_PAGE: u32 = 1;
+
+/// Prefix reserved for the names of internal tables.
+const SQLITE_INTERNAL_PREFIX: &str = "sqlite_";
+
+/// Name of the internal table which tracks `AUTOINCREMENT` values.
+const SQLITE_SEQUENCE: &str = "sqlite_sequence";
+
+/// Name of the internal table which holds the statistics gathered by `ANALYZE`.
+const SQLITE_STAT1: &str = "sqlite_stat1";
+
+/// High-level interface to a database.
+#[derive(Clone, Debug)]
+pub struct Database {
+    ctx: Ctx,
+}
+
+impl Database {
+    /// Open a database from the provided source.
+    pub fn new(source: impl Source) -> Self {
+        Self {
+            ctx: Ctx::new(source),
+        }
+    }
+
+    /// Read all entries from the `sqlite_schema` table. Entries which can't be decoded are
+    /// skipped, see [`Self::try_schemas`] to handle them.
+    pub fn schemas(&self) -> Vec<DatabaseSchema> {
+        self.try_schemas().filter_map(Result::ok).collect()
+    }
+
+    /// Read all entries from the `sqlite_schema` table, or an error for each entry which can't be
+    /// decoded.
+    pub fn try_schemas(&self) -> impl Iterator<Item = Result<DatabaseSchema, SchemaError>> {
+        self.try_scan(SCHEMA_ROOT_PAGE)
+            .map(|record| DatabaseSchema::try_from(record?))
+    }
+
+    /// Read the entries from the `sqlite_schema` table which describe objects of type `r#type`.
+    pub fn schemas_of_type(&self, r#type: SchemaType) -> Vec<DatabaseSchema> {
+        self.schemas()
+            .into_iter()
+            .filter(|schema| schema.r#type == r#type)
+            .collect()
+    }
+
+    /// Find the schema entry with the provided name.
+    #[allow(unused)]
+    pub fn schema(&self, name: &str) -> Option<DatabaseSchema> {
+        self.schemas()
+            .into_iter()
+            .find(|schema| schema.name == name)
+    }
+
+    /// List every table in the database, along with its root page and columns. Internal tables
+    /// (such as `sqlite_sequence`) are only included if `include_internal` is set.
+    pub fn tables(&self, include_internal: bool) -> Vec<TableInfo> {
+        self.schemas_of_type(SchemaType::Table)
+            .iter()
+            .filter(|schema| include_internal || !schema.name.starts_with(SQLITE_INTERNAL_PREFIX))
+            .filter_map(TableInfo::from_schema)
+            .collect()
+    }
+
+    /// Produce every record within the table B-Tree beginning at `root_page`. Will panic if a
+    /// page or record cannot be decoded, see [`Self::try_scan`] to handle invalid records.
+    pub fn scan(&self, root_page: u32) -> impl Iterator<Item = Record> {
+        self.try_scan(root_page)
+            .map(|record| record.expect("valid record"))
+    }
+
+    /// Produce every record within the table B-Tree beginning at `root_page`, or an error for
+    /// each page or record which cannot be decoded.
+    pub fn try_scan(&self, root_page: u32) -> impl Iterator<Item = Result<Record, ScanError>> {
+        let page = match self.ctx.pager.get_page(root_page) {
+            Ok(buffer) => {
+                Page::<Table>::try_from_buffer(buffer, self.ctx.header.page_size() as usize)
+                    .map_err(ScanError::from)
+            }
+            Err(_) => Err(ScanError::Unreadable(root_page)),
+        };
+
+        let cells: Box<dyn Iterator<Item = Result<TableCell, ScanError>>> = match page {
+            Ok(page) => Box::new(btree::try_traverse(self.ctx.clone(), page).map(|cell| Ok(cell?))),
+            Err(e) => Box::new(std::iter::once(Err(e))),
+        };
+
+        cells.map({
+            let ctx = self.ctx.clone();
+            let schema_format = self.ctx.header.schema_format();
+
+            move |cell| {
+                let cell = cell?;
+                let row_id = i64::from(cell.row_id);
+
+                let mut payload = vec![0; cell.payload.length];
+                cell.payload
+                    .reader(ctx.clone())
+                    .read_exact(&mut payload)
+                    .map_err(|_| ScanError::UnreadablePayload { row_id })?;
+
+                Ok(Record::from_buf_with_format(
+                    row_id,
+                    &payload,
+                    schema_format,
+                )?)
+            }
+        })
+    }
+
+    /// Read the current schema cookie, which is incremented whenever the schema changes. This is
+    /// read from the source each time, so it reflects changes made since the database was opened.
+    /// Will fail if the source has since been truncated or its header is no longer valid.
+    #[allow(unused)]
+    pub fn schema_cookie(&self) -> Result<u32, PagerError> {
+        Ok(self.ctx.pager.read_header()?.schema_cookie())
+    }
+
+    /// Read the current file change counter, which is incremented whenever the database is
+    /// modified. This is read from the source each time, like [`Self::schema_cookie`].
+    #[allow(unused)]
+    pub fn file_change_counter(&self) -> Result<u32, PagerError> {
+        Ok(self.ctx.pager.read_header()?.file_change_counter())
+    }
+
+    /// Determine whether the schema has changed since `prev_cookie` was read with
+    /// [`Self::schema_cookie`], meaning any schema information must be read again.
+    #[allow(unused)]
+    pub fn has_changed_since(&self, prev_cookie: u32) -> Result<bool, PagerError> {
+        Ok(self.schema_cookie()? != prev_cookie)
+    }
+
+    /// Read the last `AUTOINCREMENT` value of each table from the `sqlite_sequence` table. Will be
+    /// empty if no tables use `AUTOINCREMENT`.
+    #[allow(unused)]
+    pub fn sqlite_sequence(&self) -> HashMap<String, i64> {
+        let Some(root_page) = self
+            .schema(SQLITE_SEQUENCE)
+            .and_then(|schema| schema.root_page)
+        else {
+            return HashMap::new();
+        };
+
+        self.scan(root_page)
+            .filter_map(|record| {
+                let mut fields = record.fields.into_iter();
+
+                Some((fields.next()?.string()?, fields.next()?.integer()?))
+            })
+            .collect()
+    }
+
+    /// Walk every table and index B-Tree, as per `PRAGMA integrity_check`. Every problem found is
+    /// collected, so an empty list means the database is well formed.
+    ///
+    /// Each page must have a valid flag matching its B-Tree, cell pointers within the cell content
+    /// area, and must only be referenced once. Row ids within table B-Trees must be ascending, and
+    /// consistent with the keys of interior pages. The keys of index B-Trees aren't compared.
+    ///
+    /// If the schema table itself has problems, the remaining B-Trees can't be located, so only
+    /// its problems are reported.
+    #[allow(unused)]
+    pub fn integrity_check(&self) -> Vec<IntegrityError> {
+        let mut checker = IntegrityChecker::new(self.ctx.clone());
+        checker.check_tree(SCHEMA_ROOT_PAGE, Some(PageTypeFlag::Table));
+
+        if checker.has_problems() {
+            return checker.finish();
+        }
+
+        for schema in self.try_schemas() {
+            let schema = match schema {
+                Ok(schema) => schema,
+                Err(e) => {
+                    checker.report(IntegrityError::Schema(e));
+                    continue;
+                }
+            };
+
+            // Views, triggers and virtual tables have no B-Tree.
+            let Some(root_page) = schema.root_page else {
+                continue;
+            };
+
+            match schema.r#type {
+                SchemaType::Index => checker.check_tree(root_page, Some(PageTypeFlag::Index)),
+                // `WITHOUT ROWID` tables are stored in an index B-Tree.
+                SchemaType::Table => checker.check_tree(root_page, None),
+                SchemaType::View | SchemaType::Trigger | SchemaType::VirtualTable => {}
+            }
+        }
+
+        checker.finish()
+    }
+
+    /// Read the statistics gathered by `ANALYZE` from the `sqlite_stat1` table. Will be empty if
+    /// the database has never been analysed. Malformed rows are skipped.
+    #[allow(unused)]
+    pub fn sqlite_stat1(&self) -> Vec<Stat1Entry> {
+        let Some(root_page) = self
+            .schema(SQLITE_STAT1)
+            .and_then(|schema| schema.root_page)
+        else {
+            return Vec::new();
+        };
+
+        self.scan(root_page)
+            .filter_map(Stat1Entry::from_record)
+            .collect()
+    }
+}
+
+/// Error produced whilst scanning a table B-Tree.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ScanError {
+    #[error("page {0} couldn't be read")]
+    Unreadable(u32),
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error(transparent)]
+    Payload(#[from] PayloadError),
+    #[error("payload of row {row_id} couldn't be read")]
+    UnreadablePayload { row_id: i64 },
+    #[error(transparent)]
+    Record(#[from] RecordError),
+}
+
+impl From<TraverseError> for ScanError {
+    fn from(error: TraverseError) -> Self {
+        match error {
+            TraverseError::Unreadable(page_id) => Self::Unreadable(page_id),
+            TraverseError::Page(error) => Self::Page(error),
+            TraverseError::Payload(error) => Self::Payload(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::fs::File;
+
+    use rstest::rstest;
+
+    /// Open a database from the fixtures directory.
+    fn open(name: &str) -> Database {
+        Database::new(File::open(format!("fixtures/{name}")).unwrap())
+    }
+
+    #[test]
+    fn schemas() {
+        let names = open("autoincrement.db")
+            .schemas()
+            .into_iter()
+            .map(|schema| (schema.name, schema.root_page))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            [
+                ("users".to_string(), Some(2)),
+                ("sqlite_sequence".to_string(), Some(3)),
+                ("posts".to_string(), Some(4)),
+                ("tags".to_string(), Some(5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn schemas_without_sql() {
+        let entries = open("autoindex.db")
+            .schemas()
+            .into_iter()
+            .map(|schema| (schema.name, schema.r#type, schema.sql.is_some()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            entries,
+            [
+                ("t".to_string(), SchemaType::Table, true),
+                ("sqlite_autoindex_t_1".to_string(), SchemaType::Index, false),
+            ]
+        );
+    }
+
+    mod try_from_record {
+        use super::*;
+
+        use crate::record::RecordType;
+
+        /// Build a schema record from its fields.
+        fn record(fields: Vec<RecordType>) -> Record {
+            Record { id: 1, fields }
+        }
+
+        #[test]
+        fn unknown_type() {
+            let record = record(vec![
+                RecordType::String("widget".to_string()),
+                RecordType::String("w".to_string()),
+                RecordType::String("w".to_string()),
+                RecordType::I8(2),
+                RecordType::Null,
+            ]);
+
+            assert_eq!(
+                DatabaseSchema::try_from(record).unwrap_err(),
+                SchemaError::Type("widget".to_string())
+            );
+        }
+
+        #[rstest]
+        #[case::missing_sql(vec![
+            RecordType::String("table".to_string()),
+            RecordType::String("t".to_string()),
+            RecordType::String("t".to_string()),
+            RecordType::I8(2),
+        ], "sql")]
+        #[case::text_root_page(vec![
+            RecordType::String("table".to_string()),
+            RecordType::String("t".to_string()),
+            RecordType::String("t".to_string()),
+            RecordType::String("2".to_string()),
+            RecordType::Null,
+        ], "rootpage")]
+        fn invalid_column(#[case] fields: Vec<RecordType>, #[case] column: &'static str) {
+            assert_eq!(
+                DatabaseSchema::try_from(record(fields)).unwrap_err(),
+                SchemaError::Column(column)
+            );
+        }
+    }
+
+    #[test]
+    fn schemas_without_btree() {
+        let entries = open("virtual.db")
+            .schemas()
+            .into_iter()
+            .map(|schema| (schema.name, schema.r#type, schema.root_page))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            entries,
+            [
+                ("users".to_string(), SchemaType::Table, Some(2)),
+                ("user_names".to_string(), SchemaType::View, None),
+                ("page_stats".to_string(), SchemaType::VirtualTable, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn schema_types() {
+        let types = open("schema.db")
+            .schemas()
+            .into_iter()
+            .map(|schema| (schema.name, schema.r#type))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            types,
+            [
+                ("users".to_string(), SchemaType::Table),
+                ("posts".to_string(), SchemaType::Table),
+                ("posts_user_id".to_string(), SchemaType::Index),
+                ("user_posts".to_string(), SchemaType::View),
+                ("delete_user_posts".to_string(), SchemaType::Trigger),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(SchemaType::Table, &["users", "posts"])]
+    #[case(SchemaType::Index, &["posts_user_id"])]
+    #[case(SchemaType::View, &["user_posts"])]
+    #[case(SchemaType::Trigger, &["delete_user_posts"])]
+    fn schemas_of_type(#[case] r#type: SchemaType, #[case] expected: &[&str]) {
+        let names = open("schema.db")
+            .schemas_of_type(r#type)
+            .into_iter()
+            .map(|schema| schema.name)
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, expected);
+    }
+
+    mod tables {
+        use super::*;
+
+        /// Summarise each table as its name, root page and column names.
+        fn tables(
+            fixture: &str,
+            include_internal: bool,
+        ) -> Vec<(String, u32, Option<Vec<String>>)> {
+            open(fixture)
+                .tables(include_internal)
+                .into_iter()
+                .map(|table| {
+                    (
+                        table.name,
+                        table.root_page,
+                        table.columns.map(|columns| {
+                            columns
+                                .into_iter()
+                                .map(|column| column.column_name.to_string())
+                                .collect()
+                        }),
+                    )
+                })
+                .collect()
+        }
+
+        #[test]
+        fn user_tables() {
+            assert_eq!(
+                tables("stat1.db", false),
+                [
+                    (
+                        "users".to_string(),
+                        2,
+                        Some(vec![
+                            "id".to_string(),
+                            "name".to_string(),
+                            "team".to_string()
+                        ])
+                    ),
+                    ("logs".to_string(), 4, Some(vec!["message".to_string()])),
+                ]
+            );
+        }
+
+        #[test]
+        fn include_internal() {
+            let names = tables("stat1.db", true)
+                .into_iter()
+                .map(|(name, root_page, _)| (name, root_page))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                names,
+                [
+                    ("users".to_string(), 2),
+                    ("logs".to_string(), 4),
+                    ("sqlite_stat1".to_string(), 5),
+                ]
+            );
+        }
+
+        #[test]
+        fn excludes_other_types() {
+            let names = tables("schema.db", false)
+                .into_iter()
+                .map(|(name, _, _)| name)
+                .collect::<Vec<_>>();
+
+            assert_eq!(names, ["users", "posts"]);
+        }
+
+        #[test]
+        fn autoindex() {
+            let names = tables("autoindex.db", true)
+                .into_iter()
+                .map(|(name, root_page, _)| (name, root_page))
+                .collect::<Vec<_>>();
+
+            assert_eq!(names, [("t".to_string(), 2)]);
+        }
+
+        #[test]
+        fn excludes_virtual_tables() {
+            assert_eq!(
+                tables("virtual.db", true),
+                [(
+                    "users".to_string(),
+                    2,
+                    Some(vec!["id".to_string(), "name".to_string()])
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn scan_multi_level() {
+        let db = open("btree.db");
+        let items = db.schema("items").unwrap();
+
+        let ids = db
+            .scan(items.root_page.unwrap())
+            .map(|record| record.id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, (1..=1000).collect::<Vec<_>>());
+    }
+
+    mod try_scan {
+        use super::*;
+
+        use std::io::Cursor;
+
+        /// Scan the `items` table of `btree.db` after applying `patch` to the bytes of the file,
+        /// producing each error and the number of records.
+        fn scan_items(patch: impl FnOnce(&mut [u8])) -> (Vec<ScanError>, usize) {
+            let mut bytes = std::fs::read("fixtures/btree.db").unwrap();
+            patch(&mut bytes);
+            let db = Database::new(Cursor::new(bytes));
+
+            let items = db.schema("items").unwrap();
+            let (records, errors) = db
+                .try_scan(items.root_page.unwrap())
+                .partition::<Vec<_>, _>(Result::is_ok);
+
+            (
+                errors.into_iter().map(Result::unwrap_err).collect(),
+                records.len(),
+            )
+        }
+
+        #[test]
+        fn invalid_cell_count() {
+            let (errors, records) = scan_items(|bytes| {
+                // Claim an impossible number of cells on a table leaf page (page 4).
+                let offset = 512 * 3 + 3;
+                bytes[offset..offset + 2].copy_from_slice(&u16::MAX.to_be_bytes());
+            });
+
+            assert!(
+                matches!(
+                    errors[..],
+                    [ScanError::Page(PageError::CellCount {
+                        cell_count: u16::MAX,
+                        ..
+                    })]
+                ),
+                "{errors:?}"
+            );
+
+            // Records on the remaining pages are still produced.
+            assert!(records > 0);
+        }
+
+        #[test]
+        fn child_past_end_of_file() {
+            let (errors, records) = scan_items(|bytes| {
+                // Point the right pointer of the root page (page 2) beyond the end of the file.
+                let offset = 512 + 8;
+                bytes[offset..offset + 4].copy_from_slice(&9999u32.to_be_bytes());
+            });
+
+            assert_eq!(errors, [ScanError::Unreadable(9999)]);
+            assert!(records > 0);
+        }
+
+        #[rstest]
+        #[case::invalid(0xff)]
+        #[case::index_leaf(0x0a)]
+        #[case::zeroed(0x00)]
+        fn child_flag(#[case] flag: u8) {
+            let (errors, records) = scan_items(|bytes| {
+                // Overwrite the flag of the right-most child of the root page (page 2).
+                let right_pointer =
+                    u32::from_be_bytes(bytes[512 + 8..512 + 12].try_into().unwrap());
+                bytes[512 * (right_pointer as usize - 1)] = flag;
+            });
+
+            assert_eq!(errors, [ScanError::Page(PageError::Flag(flag))]);
+            assert!(records > 0);
+        }
+
+        #[rstest]
+        #[case::past_end_of_file(9999, ScanError::Unreadable(9999))]
+        #[case::index(3, ScanError::Page(PageError::Flag(0x02)))]
+        fn root(#[case] root_page: u32, #[case] expected: ScanError) {
+            let db = open("btree.db");
+
+            assert_eq!(
+                db.try_scan(root_page)
+                    .map(|result| result.map(|record| record.id))
+                    .collect::<Vec<_>>(),
+                [Err(expected)]
+            );
+        }
+
+        #[test]
+        fn broken_overflow_chain() {
+            let mut bytes = std::fs::read("fixtures/overflow.db").unwrap();
+            // Point the first overflow page of the second document (page 3) beyond the end of
+            // the file.
+            bytes[512 * 2..512 * 2 + 4].copy_from_slice(&9999u32.to_be_bytes());
+            let db = Database::new(Cursor::new(bytes));
+
+            let results = db
+                .try_scan(db.schema("documents").unwrap().root_page.unwrap())
+                .map(|result| result.map(|record| record.id))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                results,
+                [
+                    Ok(1),
+                    Err(ScanError::UnreadablePayload { row_id: 2 }),
+                    Ok(3)
+                ]
+            );
+        }
+    }
+
+    mod reserved_space {
+        use std::io::Cursor;
+
+        use super::*;
+
+        use crate::btree::page::PageExt;
+
+        /// Read `reserved.db` (which reserves 32 bytes at the end of each 512 byte page), with the
+        /// reserved space of every page optionally overwritten.
+        fn reserved(fill: Option<u8>) -> Database {
+            let mut bytes = std::fs::read("fixtures/reserved.db").unwrap();
+
+            if let Some(fill) = fill {
+                bytes
+                    .chunks_exact_mut(512)
+                    .for_each(|page| page[512 - 32..].fill(fill));
+            }
+
+            Database::new(Cursor::new(bytes))
+        }
+
+        #[rstest]
+        #[case::untouched(None)]
+        #[case::filled(Some(0xff))]
+        fn scan(#[case] fill: Option<u8>) {
+            let db = reserved(fill);
+            let items = db.schema("items").unwrap();
+
+            let rows = db
+                .scan(items.root_page.unwrap())
+                .map(|record| (record.id, record.fields[1].clone().string().unwrap()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                rows,
+                [
+                    (1, "one".to_string()),
+                    (2, "x".repeat(1000)),
+                    (3, "three".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn cell_content_area() {
+            let db = reserved(None);
+            let page = Page::<Table>::from_buffer(db.ctx.pager.get_page(2).unwrap());
+
+            // Cell content area stops at the reserved space.
+            assert!(page.cell_content_area().len() < 512 - 32);
+            assert_eq!(
+                page.cell_content_area().as_ptr_range().end,
+                page.after_header().as_ptr_range().end
+            );
+        }
+    }
+
+    mod change_detection {
+        use std::{
+            cell::RefCell,
+            io::{Cursor, Read, Seek, SeekFrom},
+            rc::Rc,
+        };
+
+        use super::*;
+
+        /// Source which can be modified after the database has been opened.
+        #[derive(Clone, Debug)]
+        struct SharedSource(Rc<RefCell<Cursor<Vec<u8>>>>);
+
+        impl Read for SharedSource {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.0.borrow_mut().read(buf)
+            }
+        }
+
+        impl Seek for SharedSource {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                self.0.borrow_mut().seek(pos)
+            }
+        }
+
+        /// Open `btree.db` from a source which can be modified after the database is opened.
+        fn shared_btree() -> (SharedSource, Database) {
+            let source = SharedSource(Rc::new(RefCell::new(Cursor::new(
+                std::fs::read("fixtures/btree.db").unwrap(),
+            ))));
+
+            (source.clone(), Database::new(source))
+        }
+
+        #[test]
+        fn read_from_fixture() {
+            let db = open("pragmas.db");
+
+            assert_eq!(db.schema_cookie().unwrap(), 1);
+            assert_eq!(db.file_change_counter().unwrap(), 6);
+            assert!(!db.has_changed_since(1).unwrap());
+            assert!(db.has_changed_since(0).unwrap());
+        }
+
+        #[test]
+        fn observes_changes() {
+            let (source, db) = shared_btree();
+
+            let cookie = db.schema_cookie().unwrap();
+            assert_eq!(cookie, 2);
+            assert!(!db.has_changed_since(cookie).unwrap());
+
+            // Simulate another connection changing the schema.
+            source.0.borrow_mut().get_mut()[40..44].copy_from_slice(&3u32.to_be_bytes());
+
+            assert!(db.has_changed_since(cookie).unwrap());
+            assert_eq!(db.schema_cookie().unwrap(), 3);
+        }
+
+        #[test]
+        fn truncated_since_opened() {
+            let (source, db) = shared_btree();
+
+            // Simulate the file being truncated by another process.
+            source.0.borrow_mut().get_mut().truncate(50);
+
+            assert!(matches!(
+                db.schema_cookie(),
+                Err(PagerError::Read { page_id: 1, .. })
+            ));
+            assert!(db.file_change_counter().is_err());
+            assert!(db.has_changed_since(2).is_err());
+        }
+
+        #[test]
+        fn replaced_since_opened() {
+            let (source, db) = shared_btree();
+
+            // Simulate the file being replaced with one that isn't a database.
+            source.0.borrow_mut().get_mut()[..16].copy_from_slice(b"not a database!!");
+
+            assert!(matches!(
+                db.has_changed_since(2),
+                Err(PagerError::Header(_))
+            ));
+        }
+    }
+
+    mod sqlite_sequence {
+        use super::*;
+
+        #[test]
+        fn autoincrement_tables() {
+            let sequence = open("autoincrement.db").sqlite_sequence();
+
+            assert_eq!(
+                sequence,
+                HashMap::from([("users".to_string(), 1), ("posts".to_string(), 5)])
+            );
+        }
+
+        #[test]
+        fn no_autoincrement() {
+            assert!(
+                Database::new(File::open("test.db").unwrap())
+                    .sqlite_sequence()
+                    .is_empty()
+            );
+        }
+    }
+
+    mod sqlite_stat1 {
+        use super::*;
+
+        #[test]
+        fn analysed() {
+            assert_eq!(
+                open("stat1.db").sqlite_stat1(),
+                [
+                    Stat1Entry {
+                        table: "logs".to_string(),
+                        index: None,
+                        row_estimate: 3,
+                        column_estimates: vec![],
+                    },
+                    Stat1Entry {
+                        table: "users".to_string(),
+                        index: Some("users_team_name".to_string()),
+                        row_estimate: 100,
+                        column_estimates: vec![25, 1],
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn not_analysed() {
+            assert!(open("schema.db").sqlite_stat1().is_empty());
+        }
+    }
+
+    mod integrity_check {
+        use super::*;
+
+        use std::io::Cursor;
+
+        use crate::{btree::page::PageError, disk::header::SQLITE_HEADER_SIZE};
+
+        /// Page size of `btree.db`.
+        const PAGE_SIZE: usize = 512;
+
+        /// Open `btree.db`, applying `patch` to the bytes of the file.
+        fn open_patched(patch: impl FnOnce(&mut [u8])) -> Database {
+            let mut bytes = std::fs::read("fixtures/btree.db").unwrap();
+            patch(&mut bytes);
+            Database::new(Cursor::new(bytes))
+        }
+
+        /// Offset of the start of `page_id` within the file.
+        fn page_offset(page_id: usize) -> usize {
+            PAGE_SIZE * (page_id - 1)
+        }
+
+        #[rstest]
+        #[case("btree.db")]
+        #[case("overflow.db")]
+        #[case("schema.db")]
+        #[case("auto_vacuum.db")]
+        #[case("reserved.db")]
+        #[case("stat1.db")]
+        #[case("virtual.db")]
+        #[case("autoindex.db")]
+        #[case("rowid_alias.db")]
+        #[case("without_rowid.db")]
+        fn well_formed(#[case] fixture: &str) {
+            assert_eq!(open(fixture).integrity_check(), []);
+        }
+
+        #[rstest]
+        #[case::trusted(false, true)]
+        #[case::legacy_writer(true, false)]
+        fn page_count(#[case] legacy_writer: bool, #[case] expected_problems: bool) {
+            let db = open_patched(|bytes| {
+                // Claim the database is smaller than it is.
+                bytes[28..32].copy_from_slice(&3u32.to_be_bytes());
+
+                if legacy_writer {
+                    // `version_valid_for` no longer matches the file change counter.
+                    bytes[95] = bytes[27].wrapping_sub(1);
+                }
+            });
+
+            let problems = db.integrity_check();
+
+            assert_eq!(
+                problems.contains(&IntegrityError::PageNumber(4)),
+                expected_problems,
+                "{problems:?}"
+            );
+            if !expected_problems {
+                assert_eq!(problems, []);
+            }
+        }
+
+        #[test]
+        fn collects_problems() {
+            let db = open_patched(|bytes| {
+                // Swap the first two cell pointers of a table leaf page.
+                let offset = page_offset(4) + 8;
+                let first = [bytes[offset], bytes[offset + 1]];
+                bytes.copy_within(offset + 2..offset + 4, offset);
+                bytes[offset + 2..offset + 4].copy_from_slice(&first);
+
+                // Point a cell of another table leaf page into its header.
+                let offset = page_offset(5) + 8;
+                bytes[offset..offset + 2].copy_from_slice(&[0, 0]);
+
+                // Mark an index leaf page as a table leaf page.
+                bytes[page_offset(6)] = 0x0d;
+            });
+
+            let problems = db.integrity_check();
+
+            assert_eq!(problems.len(), 3, "{problems:?}");
+            assert!(problems.contains(&IntegrityError::RowIdOrder {
+                page_id: 4,
+                row_id: 1
+            }));
+            assert!(problems.contains(&IntegrityError::Page {
+                page_id: 5,
+                source: PageError::CellPointer {
+                    index: 0,
+                    pointer: 0
+                }
+            }));
+            assert!(problems.contains(&IntegrityError::Flag {
+                page_id: 6,
+                flag: 0x0d
+            }));
+        }
+
+        #[test]
+        fn shared_page() {
+            // Point the right pointer of the `items` root page at the root of `items_name`.
+            let db = open_patched(|bytes| {
+                let offset = page_offset(2) + 8;
+                bytes[offset..offset + 4].copy_from_slice(&3u32.to_be_bytes());
+            });
+
+            assert_eq!(
+                db.integrity_check(),
+                [
+                    IntegrityError::Flag {
+                        page_id: 3,
+                        flag: 0x02
+                    },
+                    IntegrityError::Revisited(3)
+                ]
+            );
+        }
+
+        #[test]
+        fn page_out_of_range() {
+            let db = open_patched(|bytes| {
+                let offset = page_offset(2) + 8;
+                bytes[offset..offset + 4].copy_from_slice(&1000u32.to_be_bytes());
+            });
+
+            assert_eq!(db.integrity_check(), [IntegrityError::PageNumber(1000)]);
+        }
+
+        #[test]
+        fn corrupt_schema() {
+            // Point the only cell of the schema page beyond the end of the page.
+            let db = open_patched(|bytes| {
+                let offset = SQLITE_HEADER_SIZE + 8;
+                bytes[offset..offset + 2].copy_from_slice(&600u16.to_be_bytes());
+            });
+
+            assert_eq!(
+                db.integrity_check(),
+                [IntegrityError::Page {
+                    page_id: 1,
+                    source: PageError::CellPointer {
+                        index: 0,
+                        pointer: 600
+                    }
+                }]
+            );
+        }
+    }
+
+    mod create_statement {
+        use super::*;
+
+        #[test]
+        fn cached() {
+            let schema = open("stat1.db").schema("logs").unwrap();
+
+            let first = schema.create_statement().unwrap();
+            let second = schema.create_statement().unwrap();
+
+            // The same parsed statement is produced, rather than parsing again.
+            assert!(std::ptr::eq(first, second));
+            assert_eq!(
+                first
+                    .columns
+                    .iter()
+                    .map(|column| column.column_name.as_str())
+                    .collect::<Vec<_>>(),
+                ["message"]
+            );
+        }
+
+        #[rstest]
+        #[case::index("btree.db", "items_name")]
+        // Column constraints other than `NOT NULL` and `PRIMARY KEY` aren't supported by the
+        // parser.
+        #[case::unsupported("autoindex.db", "t")]
+        fn none(#[case] fixture: &str, #[case] name: &str) {
+            let schema = open(fixture).schema(name).unwrap();
+
+            assert!(schema.create_statement().is_none());
+        }
+
+        #[rstest]
+        #[case("settings", &["name"])]
+        #[case("pairs", &["a", "b"])]
+        fn without_rowid(#[case] name: &str, #[case] expected: &[&str]) {
+            let schema = open("without_rowid.db").schema(name).unwrap();
+            let create = schema.create_statement().unwrap();
+
+            assert!(create.without_rowid);
+            assert_eq!(
+                create
+                    .primary_key_columns()
+                    .into_iter()
+                    .map(|column| column.as_str())
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+
+        #[rstest]
+        #[case("users", true)]
+        #[case("posts", true)]
+        #[case("tags", false)]
+        fn autoincrement(#[case] name: &str, #[case] expected: bool) {
+            let schema = open("autoincrement.db").schema(name).unwrap();
+            let create = schema.create_statement().unwrap();
+
+            assert!(!create.without_rowid);
+            assert_eq!(
+                create
+                    .columns
+                    .iter()
+                    .any(|column| column.primary_key.is_some_and(|key| key.autoincrement)),
+                expected
+            );
+        }
+    }
+
+    mod rowid_alias {
+        use super::*;
+
+        use crate::command::{self, QueryStatement, parse_command};
+
+        /// Run the query against a table of `rowid_alias.db`, returning the first column of each
+        /// row.
+        fn run(command: &str) -> Vec<String> {
+            let db = open("rowid_alias.db");
+            let query = parse_command::<QueryStatement>(command);
+            let table = db
+                .tables(false)
+                .into_iter()
+                .find(|table| query.table_name.eq_ignore_case(&table.name))
+                .unwrap();
+
+            command::execute(
+                &query,
+                &table.columns.unwrap(),
+                table.rowid_alias,
+                db.scan(table.root_page),
+            )
+            .unwrap()
+            .map(|row| row[0].to_string())
+            .collect()
+        }
+
+        #[rstest]
+        #[case("select id, name from items;", &["1", "5", "9"])]
+        #[case("select name from items where id = 5;", &["five"])]
+        #[case("select name from items where id = '5';", &["five"])]
+        #[case("select name from items where id > '4';", &["five", "nine"])]
+        #[case("select name from items order by id desc;", &["nine", "five", "one"])]
+        fn alias(#[case] command: &str, #[case] expected: &[&str]) {
+            assert_eq!(run(command), expected);
+        }
+
+        #[rstest]
+        #[case("select id from ranked;", &["1", "5", "9"])]
+        #[case("select name from ranked where id = '5';", &["five"])]
+        fn desc_is_not_alias(#[case] command: &str, #[case] expected: &[&str]) {
+            assert_eq!(open("rowid_alias.db").tables(false)[1].rowid_alias, None);
+            assert_eq!(run(command), expected);
+        }
+    }
+}
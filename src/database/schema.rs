@@ -0,0 +1,154 @@
+use std::cell::OnceCell;
+
+use lib_parse::common::token::CommonToken;
+use thiserror::Error;
+
+use crate::{
+    command::{ColumnDef, CreateStatement},
+    database::ScanError,
+    record::{Record, RecordType},
+};
+
+/// Type of object described by an entry in the `sqlite_schema` table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchemaType {
+    Table,
+    Index,
+    View,
+    Trigger,
+    /// A table implemented by a module (`CREATE VIRTUAL TABLE`), which has no B-Tree.
+    VirtualTable,
+}
+
+impl SchemaType {
+    /// Parse the value of the `type` column.
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "table" => Self::Table,
+            "index" => Self::Index,
+            "view" => Self::View,
+            "trigger" => Self::Trigger,
+            _ => return None,
+        })
+    }
+}
+
+/// A single entry in the `sqlite_schema` table.
+#[allow(unused)]
+#[derive(Clone, Debug)]
+pub struct DatabaseSchema {
+    pub r#type: SchemaType,
+    pub name: String,
+    pub tbl_name: String,
+    /// Root page of the B-Tree for this entry, or `None` for entries without one (views,
+    /// triggers and virtual tables), which are stored with a root page of `0`.
+    pub root_page: Option<u32>,
+    /// SQL which created this entry, or `None` for entries created implicitly (such as the
+    /// `sqlite_autoindex_*` indexes backing `UNIQUE` and `PRIMARY KEY` constraints).
+    pub sql: Option<String>,
+
+    /// `sql` parsed as a `CREATE TABLE` statement, populated on first access.
+    create_statement: OnceCell<Option<CreateStatement>>,
+}
+
+impl DatabaseSchema {
+    /// Parse `sql` as a `CREATE TABLE` statement. It's only parsed on the first call, and the
+    /// result is reused after that. Will be `None` if this entry isn't a table, or if its SQL
+    /// can't be parsed.
+    #[allow(unused)]
+    pub fn create_statement(&self) -> Option<&CreateStatement> {
+        self.create_statement
+            .get_or_init(|| {
+                let sql = self
+                    .sql
+                    .as_deref()
+                    .filter(|_| self.r#type == SchemaType::Table)?;
+
+                lib_parse::parse_str::<_, CommonToken>(sql).ok()
+            })
+            .as_ref()
+    }
+}
+
+/// Summary of a table described by the `sqlite_schema` table.
+#[allow(unused)]
+#[derive(Clone, Debug)]
+pub struct TableInfo {
+    pub name: String,
+    pub root_page: u32,
+    /// Columns declared by the table's `CREATE TABLE` statement, or `None` if it can't be parsed.
+    pub columns: Option<Vec<ColumnDef>>,
+    /// Index of the column aliasing the row id, if any.
+    pub rowid_alias: Option<usize>,
+}
+
+impl TableInfo {
+    /// Summarise a schema entry, or `None` if it doesn't have a B-Tree.
+    pub(super) fn from_schema(schema: &DatabaseSchema) -> Option<Self> {
+        Some(Self {
+            name: schema.name.clone(),
+            root_page: schema.root_page?,
+            columns: schema
+                .create_statement()
+                .map(|statement| statement.columns.iter().cloned().collect()),
+            rowid_alias: schema
+                .create_statement()
+                .and_then(|statement| statement.rowid_alias()),
+        })
+    }
+}
+
+impl TryFrom<Record> for DatabaseSchema {
+    type Error = SchemaError;
+
+    fn try_from(record: Record) -> Result<Self, Self::Error> {
+        let mut fields = record.fields.into_iter();
+        let mut next_field = |column| fields.next().ok_or(SchemaError::Column(column));
+
+        let type_name = next_field("type")?
+            .string()
+            .ok_or(SchemaError::Column("type"))?;
+        let r#type = SchemaType::from_name(&type_name).ok_or(SchemaError::Type(type_name))?;
+        let name = next_field("name")?
+            .string()
+            .ok_or(SchemaError::Column("name"))?;
+        let tbl_name = next_field("tbl_name")?
+            .string()
+            .ok_or(SchemaError::Column("tbl_name"))?;
+        let root_page = match next_field("rootpage")?
+            .integer()
+            .ok_or(SchemaError::Column("rootpage"))? as u32
+        {
+            0 => None,
+            root_page => Some(root_page),
+        };
+        let sql = match next_field("sql")? {
+            RecordType::Null => None,
+            sql => Some(sql.string().ok_or(SchemaError::Column("sql"))?),
+        };
+
+        Ok(Self {
+            // Virtual tables are recorded as tables, but without a B-Tree.
+            r#type: match (r#type, root_page) {
+                (SchemaType::Table, None) => SchemaType::VirtualTable,
+                (r#type, _) => r#type,
+            },
+            name,
+            tbl_name,
+            root_page,
+            sql,
+            create_statement: OnceCell::new(),
+        })
+    }
+}
+
+/// Error produced whilst decoding an entry of the `sqlite_schema` table.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum SchemaError {
+    #[error(transparent)]
+    Scan(#[from] ScanError),
+    #[error("`{0}` column is missing or has the wrong type")]
+    Column(&'static str),
+    #[error("unknown schema type: {0}")]
+    Type(String),
+}
@@ -0,0 +1,64 @@
+use crate::record::{Record, RecordType};
+
+/// A single row of the `sqlite_stat1` table, which is populated by `ANALYZE`.
+#[allow(unused)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stat1Entry {
+    /// Name of the table which was analysed.
+    pub table: String,
+    /// Name of the index which was analysed, or [`None`] if the row describes the table itself.
+    pub index: Option<String>,
+    /// Approximate number of rows in the table or index.
+    pub row_estimate: u64,
+    /// Approximate number of rows which share the same values for each prefix of the index's
+    /// columns, beginning with the first column. Empty for rows describing the table itself.
+    pub column_estimates: Vec<u64>,
+}
+
+impl Stat1Entry {
+    /// Decode a record from the `sqlite_stat1` table, producing [`None`] if it is malformed.
+    pub fn from_record(record: Record) -> Option<Self> {
+        let mut fields = record.fields.into_iter();
+
+        let table = fields.next()?.string()?;
+        let index = match fields.next()? {
+            RecordType::Null => None,
+            field => Some(field.string()?),
+        };
+        let (row_estimate, column_estimates) = parse_stat(&fields.next()?.string()?)?;
+
+        Some(Self {
+            table,
+            index,
+            row_estimate,
+            column_estimates,
+        })
+    }
+}
+
+/// Parse the space-separated integers of a `stat` value. The first integer is the row estimate,
+/// and any that follow are the column estimates. Trailing options (such as `unordered` or
+/// `sz=N`) are ignored.
+fn parse_stat(stat: &str) -> Option<(u64, Vec<u64>)> {
+    let mut estimates = stat
+        .split(' ')
+        .map_while(|estimate| estimate.parse::<u64>().ok());
+
+    Some((estimates.next()?, estimates.collect()))
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("3", Some((3, vec![])))]
+    #[case("100 25 1", Some((100, vec![25, 1])))]
+    #[case("100 25 1 unordered", Some((100, vec![25, 1])))]
+    #[case("100 sz=12 25", Some((100, vec![])))]
+    #[case("", None)]
+    #[case("unordered", None)]
+    fn parse_stat(#[case] stat: &str, #[case] expected: Option<(u64, Vec<u64>)>) {
+        assert_eq!(super::parse_stat(stat), expected);
+    }
+}
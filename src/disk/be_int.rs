@@ -0,0 +1,97 @@
+//! Helpers for reading big-endian, two's complement integers of arbitrary width, as used for
+//! integers stored within records.
+
+use ux::{i24, i48};
+
+/// Read a signed integer from the first `n` bytes of `bytes`, sign-extending it from its most
+/// significant bit.
+///
+/// # Panics
+///
+/// Panics if `n` isn't between 1 and 8 (inclusive), or if `bytes` is shorter than `n`.
+pub fn read_signed(n: usize, bytes: &[u8]) -> i64 {
+    assert!((1..=8).contains(&n), "integer width must be 1 to 8 bytes");
+
+    let value = bytes[..n]
+        .iter()
+        .fold(0i64, |value, b| (value << 8) | *b as i64);
+
+    // Shift the most significant bit of the value into the sign bit, then shift back to extend it.
+    let shift = 64 - 8 * n as u32;
+    (value << shift) >> shift
+}
+
+/// Read a 24-bit signed integer from the first 3 bytes of `bytes`.
+pub fn read_i24(bytes: &[u8]) -> i24 {
+    i24::new(read_signed(3, bytes) as i32)
+}
+
+/// Read a 48-bit signed integer from the first 6 bytes of `bytes`.
+pub fn read_i48(bytes: &[u8]) -> i48 {
+    i48::new(read_signed(6, bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[0x00], 0)]
+    #[case(&[0x7f], 127)]
+    #[case(&[0x80], -128)]
+    #[case(&[0xff], -1)]
+    #[case(&[0x7f, 0xff], 32767)]
+    #[case(&[0x80, 0x00], -32768)]
+    #[case(&[0xff, 0xfe], -2)]
+    #[case(&[0x7f, 0xff, 0xff, 0xff], i32::MAX as i64)]
+    #[case(&[0x80, 0x00, 0x00, 0x00], i32::MIN as i64)]
+    #[case(&[0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], i64::MAX)]
+    #[case(&[0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], i64::MIN)]
+    #[case(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], -1)]
+    fn any_width(#[case] bytes: &[u8], #[case] expected: i64) {
+        assert_eq!(read_signed(bytes.len(), bytes), expected);
+    }
+
+    #[test]
+    fn signed_ignores_trailing_bytes() {
+        assert_eq!(read_signed(2, &[0x01, 0x02, 0xff]), 0x0102);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(9)]
+    #[should_panic]
+    fn signed_invalid_width(#[case] n: usize) {
+        read_signed(n, &[0; 16]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn signed_short_buffer() {
+        read_signed(4, &[0; 3]);
+    }
+
+    #[rstest]
+    #[case(&[0x00, 0x00, 0x00], 0)]
+    #[case(&[0x00, 0x00, 0x01], 1)]
+    #[case(&[0xff, 0xff, 0xff], -1)]
+    #[case(&[0xff, 0xff, 0xfd], -3)]
+    #[case(&[0x7f, 0xff, 0xff], 8388607)]
+    #[case(&[0x80, 0x00, 0x00], -8388608)]
+    fn width_24(#[case] bytes: &[u8], #[case] expected: i32) {
+        assert_eq!(read_i24(bytes), i24::new(expected));
+    }
+
+    #[rstest]
+    #[case(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00], 0)]
+    #[case(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x00], 4294967296)]
+    #[case(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff], -1)]
+    #[case(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xfb], -5)]
+    #[case(&[0x7f, 0xff, 0xff, 0xff, 0xff, 0xff], 140737488355327)]
+    #[case(&[0x80, 0x00, 0x00, 0x00, 0x00, 0x00], -140737488355328)]
+    fn width_48(#[case] bytes: &[u8], #[case] expected: i64) {
+        assert_eq!(read_i48(bytes), i48::new(expected));
+    }
+}
@@ -92,14 +92,7 @@ impl SqliteHeader {
     /// Try read the header from the provided buffer. The buffer must be exactly the correct size
     /// for the header.
     pub fn read_from_buffer(buf: &[u8]) -> Result<Self, SqliteHeaderError> {
-        // Read the header.
-        let header = SqliteHeader::try_read_from_bytes(buf).map_err(|e| match e {
-            zerocopy::ConvertError::Size(_) => BinaryError::Size,
-            zerocopy::ConvertError::Validity(_) => BinaryError::Validity,
-            zerocopy::ConvertError::Alignment(_) => {
-                unreachable!("zerocopy `try_ref_from_bytes` should be infallibale")
-            }
-        })?;
+        let header = Self::read_unvalidated(buf)?;
 
         // Validate the header.
         header.validate()?;
@@ -107,24 +100,70 @@ impl SqliteHeader {
         Ok(header)
     }
 
-    /// Validate the current instance of this header.
-    fn validate(&self) -> Result<(), SqliteHeaderError> {
-        if self.header_string != HEADER_STRING {
-            return Err(SqliteHeaderError::HeaderString(self.header_string));
+    /// Read the header from the provided buffer, using `page_size` in place of the page size
+    /// stored in the header. The remaining fields are read without validation, so that a
+    /// database with a partially corrupt header can still be recovered.
+    ///
+    /// Fields which the accessors rely on are reset to their defaults if they're invalid: the
+    /// schema format becomes [`SchemaFormat::V4`], the text encoding becomes
+    /// [`TextEncoding::Utf8`], and the payload fractions become [`PayloadFractions::default`].
+    pub fn read_with_page_size(buf: &[u8], page_size: u32) -> Result<Self, SqliteHeaderError> {
+        let page_size = PageSize::new(page_size)?;
+
+        let mut header = Self::read_unvalidated(buf)?;
+        header.page_size = U16::new(page_size.to_raw());
+
+        if SchemaFormat::try_from_primitive(header.schema_format.get()).is_err() {
+            header.set_schema_format(SchemaFormat::V4);
+        }
+        if TextEncoding::try_from_primitive(header.text_encoding.get()).is_err() {
+            header.set_text_encoding(TextEncoding::Utf8);
         }
+        header.max_payload_fraction = ConstU8::default();
+        header.min_payload_fraction = ConstU8::default();
+        header.leaf_payload_fraction = ConstU8::default();
 
-        {
-            let page_size = self.page_size.get();
+        Ok(header)
+    }
 
-            if page_size != 1 && !(512..=32768).contains(&page_size) {
-                return Err(PageSizeError::Range(page_size).into());
-            }
+    /// Read the header from the provided buffer, skipping validation. The buffer must be exactly
+    /// the correct size for the header, otherwise this will panic.
+    ///
+    /// This must only be used for a header which is known to be valid (such as one which has
+    /// already been read with [`Self::read_from_buffer`]), as the accessors assume that each field
+    /// holds a valid value, and may panic or produce nonsense otherwise.
+    #[allow(unused)]
+    pub fn read_unchecked(buf: &[u8]) -> Self {
+        Self::read_unvalidated(buf).expect("buffer to be the size of the header")
+    }
 
-            if !page_size.is_power_of_two() {
-                return Err(PageSizeError::PowerOfTwo(page_size).into());
+    /// Serialise the header back into its on-disk representation.
+    #[allow(unused)]
+    pub fn to_bytes(&self) -> [u8; SQLITE_HEADER_SIZE] {
+        self.as_bytes()
+            .try_into()
+            .expect("header to be exactly `SQLITE_HEADER_SIZE` bytes")
+    }
+
+    /// Read the header from the provided buffer, without validating any fields.
+    fn read_unvalidated(buf: &[u8]) -> Result<Self, SqliteHeaderError> {
+        Ok(SqliteHeader::try_read_from_bytes(buf).map_err(|e| match e {
+            zerocopy::ConvertError::Size(_) => BinaryError::Size,
+            zerocopy::ConvertError::Validity(_) => BinaryError::Validity,
+            zerocopy::ConvertError::Alignment(_) => {
+                unreachable!("zerocopy `try_ref_from_bytes` should be infallibale")
             }
+        })?)
+    }
+
+    /// Validate the current instance of this header.
+    fn validate(&self) -> Result<(), SqliteHeaderError> {
+        if self.header_string != HEADER_STRING {
+            return Err(SqliteHeaderError::HeaderString(self.header_string));
         }
 
+        PageSize::from_raw(self.page_size.get())?;
+
         FileFormatVersion::try_from_primitive(self.file_format_read_version).map_err(|e| {
             EnumError {
                 field: "file_format_read_version",
@@ -175,26 +214,142 @@ impl SqliteHeader {
         Ok(())
     }
 
-    /// Get the page size of this database.
-    pub fn page_size(&self) -> u32 {
-        let n = self.page_size.get() as u32;
+    /// Cross-check the relationships between header fields, which aren't covered by validation
+    /// when reading. All violations are collected, rather than stopping at the first.
+    #[allow(unused)]
+    pub fn integrity_check(&self) -> Vec<HeaderIntegrityError> {
+        let mut violations = Vec::new();
+
+        // `sqlite_version_number` and `page_count` are only trustworthy if `version_valid_for` is
+        // up to date. Legacy writers leave it stale, see [`Self::page_count_valid`].
+        if self.version_valid_for.get() != self.file_change_counter.get() {
+            violations.push(HeaderIntegrityError::VersionValidFor {
+                version_valid_for: self.version_valid_for.get(),
+                file_change_counter: self.file_change_counter.get(),
+            });
+        }
 
-        if n == 1 {
-            return 65536;
+        // Incremental-vacuum mode is only possible if auto-vacuum is enabled, which is indicated
+        // by the presence of `largest_root_btree_page`.
+        if self.incremental_vacuum_mode.get() != 0 && self.largest_root_btree_page.get() == 0 {
+            violations.push(HeaderIntegrityError::IncrementalVacuumWithoutRoot);
         }
 
-        n
+        // A trunk page must exist if (and only if) there are pages in the freelist.
+        if (self.freelist_trunk_page.get() == 0) != (self.freelist_page_count.get() == 0) {
+            violations.push(HeaderIntegrityError::Freelist {
+                freelist_trunk_page: self.freelist_trunk_page.get(),
+                freelist_page_count: self.freelist_page_count.get(),
+            });
+        }
+
+        violations
+    }
+
+    /// Decode the header fields which are visible through `PRAGMA` statements.
+    #[allow(unused)]
+    pub fn pragmas(&self) -> HeaderPragmas {
+        HeaderPragmas {
+            application_id: self.application_id.get() as i32,
+            user_version: self.user_version.get() as i32,
+            schema_cookie: self.schema_cookie.get(),
+            freelist_page_count: self.freelist_page_count.get(),
+            text_encoding: self.text_encoding(),
+            auto_vacuum: self.largest_root_btree_page.get() != 0,
+            incremental_vacuum: self.incremental_vacuum_mode.get() != 0,
+        }
+    }
+
+    /// Get the schema cookie, which is incremented whenever the schema changes.
+    pub fn schema_cookie(&self) -> u32 {
+        self.schema_cookie.get()
+    }
+
+    /// Get the file change counter, which is incremented whenever the database is modified.
+    pub fn file_change_counter(&self) -> u32 {
+        self.file_change_counter.get()
+    }
+
+    /// Get the schema format number of this database.
+    pub fn schema_format(&self) -> SchemaFormat {
+        SchemaFormat::try_from_primitive(self.schema_format.get())
+            .expect("schema format validated when reading header")
+    }
+
+    /// Set the schema format number of this database.
+    #[allow(unused)]
+    pub fn set_schema_format(&mut self, schema_format: SchemaFormat) {
+        self.schema_format = U32::new(schema_format.into());
+    }
+
+    /// Get the text encoding of this database.
+    #[allow(unused)]
+    pub fn text_encoding(&self) -> TextEncoding {
+        TextEncoding::try_from_primitive(self.text_encoding.get())
+            .expect("text encoding validated when reading header")
+    }
+
+    /// Set the text encoding of this database.
+    #[allow(unused)]
+    pub fn set_text_encoding(&mut self, text_encoding: TextEncoding) {
+        self.text_encoding = U32::new(text_encoding.into());
+    }
+
+    /// Get the user version, as per `PRAGMA user_version`.
+    #[allow(unused)]
+    pub fn user_version(&self) -> u32 {
+        self.user_version.get()
+    }
+
+    /// Set the user version, as per `PRAGMA user_version`.
+    #[allow(unused)]
+    pub fn set_user_version(&mut self, user_version: u32) {
+        self.user_version = U32::new(user_version);
+    }
+
+    /// Get the page size of this database.
+    pub fn page_size(&self) -> u32 {
+        PageSize::from_raw(self.page_size.get())
+            .expect("page size validated when reading header")
+            .get()
     }
 
     pub fn page_end_padding(&self) -> u8 {
         self.page_end_padding
     }
 
+    /// Get the payload fractions, which control how much of a payload is stored on a B-Tree page
+    /// before it spills onto overflow pages. These are the stored values, which will only differ
+    /// from [`PayloadFractions::default`] if the header was read without validation.
+    pub fn payload_fractions(&self) -> PayloadFractions {
+        PayloadFractions {
+            max: self.max_payload_fraction.get(),
+            min: self.min_payload_fraction.get(),
+            leaf: self.leaf_payload_fraction.get(),
+        }
+    }
+
+    /// Get the size of the database in pages, as recorded in the header. This can only be relied
+    /// upon if [`Self::page_count_valid`] holds.
     #[allow(unused)]
     pub fn page_count(&self) -> u32 {
         self.page_count.get()
     }
 
+    /// Whether [`Self::page_count`] is trustworthy. Legacy writers (before SQLite 3.7.0) don't
+    /// update it, which is detected by `version_valid_for` not matching `file_change_counter`. In
+    /// that case the size of the database must be determined from the size of the file instead.
+    pub fn page_count_valid(&self) -> bool {
+        self.page_count.get() != 0 && self.version_valid_for.get() == self.file_change_counter.get()
+    }
+
+    /// Get the page number of the largest root B-Tree page, which is only non-zero in
+    /// auto-vacuum and incremental-vacuum modes.
+    #[allow(unused)]
+    pub fn largest_root_btree_page(&self) -> u32 {
+        self.largest_root_btree_page.get()
+    }
+
     /// Get the (major, minor, patch) version of this database.
     #[allow(unused)]
     pub fn sqlite_version_number(&self) -> (u16, u16, u16) {
@@ -215,7 +370,8 @@ pub enum FileFormatVersion {
     Wal = 2,
 }
 
-#[derive(Clone, Copy, Debug, IntoPrimitive, TryFromPrimitive)]
+/// Schema format number, which determines the record and schema features available.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, IntoPrimitive, TryFromPrimitive)]
 #[repr(u32)]
 pub enum SchemaFormat {
     V1 = 1,
@@ -224,7 +380,7 @@ pub enum SchemaFormat {
     V4 = 4,
 }
 
-#[derive(Clone, Copy, Debug, IntoPrimitive, TryFromPrimitive)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
 #[repr(u32)]
 pub enum TextEncoding {
     Utf8 = 1,
@@ -232,9 +388,57 @@ pub enum TextEncoding {
     Utf16Be = 3,
 }
 
+/// Header values which are visible through `PRAGMA` statements, as produced by
+/// [`SqliteHeader::pragmas`].
+#[allow(unused)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderPragmas {
+    /// `PRAGMA application_id`.
+    pub application_id: i32,
+    /// `PRAGMA user_version`.
+    pub user_version: i32,
+    /// `PRAGMA schema_version`.
+    pub schema_cookie: u32,
+    /// `PRAGMA freelist_count`.
+    pub freelist_page_count: u32,
+    /// `PRAGMA encoding`.
+    pub text_encoding: TextEncoding,
+    /// Whether auto-vacuum (either full or incremental) is enabled.
+    pub auto_vacuum: bool,
+    /// Whether incremental-vacuum mode is enabled.
+    pub incremental_vacuum: bool,
+}
+
+/// Fractions (out of 255) of the usable space of a page which may be used to store a payload, as
+/// produced by [`SqliteHeader::payload_fractions`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PayloadFractions {
+    /// Maximum fraction of an index page which may be used by a payload.
+    pub max: u8,
+    /// Minimum fraction of an index page which must be used by a payload before it spills.
+    pub min: u8,
+    /// Minimum fraction of a table leaf page which must be used by a payload before it spills.
+    pub leaf: u8,
+}
+
+impl Default for PayloadFractions {
+    /// The only fractions permitted by the file format.
+    fn default() -> Self {
+        Self {
+            max: 64,
+            min: 32,
+            leaf: 32,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum SqliteHeaderError {
-    #[error("invalid header string (expected '{HEADER_STRING:#?}', found '{0:#?}')")]
+    #[error(
+        "invalid header string (expected {}, found {})",
+        display_bytes(&HEADER_STRING),
+        display_bytes(.0)
+    )]
     HeaderString([u8; 16]),
     #[error(transparent)]
     PageSize(#[from] PageSizeError),
@@ -244,7 +448,7 @@ pub enum SqliteHeaderError {
         #[source]
         source: ConstU8Error,
     },
-    #[error("expected reserved 0x00 bytes (found {0:#?})")]
+    #[error("{}", display_reserved(.0))]
     Reserved([u8; 20]),
     #[error(transparent)]
     EnumU8(#[from] EnumError<u8>),
@@ -254,6 +458,30 @@ pub enum SqliteHeaderError {
     Binary(#[from] BinaryError),
 }
 
+/// Render bytes as a (lossy) UTF-8 string, followed by their hex representation.
+fn display_bytes(bytes: &[u8]) -> String {
+    let hex = bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!("{:?} [{hex}]", String::from_utf8_lossy(bytes))
+}
+
+/// Describe the first non-zero byte in the reserved region, by its offset within the header.
+fn display_reserved(reserved: &[u8; 20]) -> String {
+    let offset = std::mem::offset_of!(SqliteHeader, reserved);
+
+    match reserved.iter().enumerate().find(|(_, b)| **b != 0) {
+        Some((i, b)) => format!(
+            "expected reserved bytes to be 0x00 (found {b:#04x} at offset {})",
+            offset + i
+        ),
+        None => "expected reserved bytes to be 0x00".to_string(),
+    }
+}
+
 #[derive(Clone, Debug, Error)]
 #[error("invalid value for {field} (found {value})")]
 pub struct EnumError<T> {
@@ -261,17 +489,82 @@ pub struct EnumError<T> {
     value: T,
 }
 
+/// Size of each page in a database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageSize(u32);
+
+impl PageSize {
+    /// Smallest valid page size.
+    pub const MIN: u32 = 512;
+    /// Largest valid page size.
+    pub const MAX: u32 = 65536;
+
+    /// Create a page size, which must be a power of two between [`Self::MIN`] and [`Self::MAX`]
+    /// (inclusive).
+    pub fn new(value: u32) -> Result<Self, PageSizeError> {
+        if !(Self::MIN..=Self::MAX).contains(&value) {
+            return Err(PageSizeError::Range(value));
+        }
+
+        if !value.is_power_of_two() {
+            return Err(PageSizeError::PowerOfTwo(value));
+        }
+
+        Ok(Self(value))
+    }
+
+    /// Decode the page size as stored in the header, where a page size of 65536 is stored as 1.
+    pub fn from_raw(raw: u16) -> Result<Self, PageSizeError> {
+        Self::new(if raw == 1 { Self::MAX } else { raw as u32 })
+    }
+
+    /// Encode the page size as it is stored in the header.
+    pub fn to_raw(self) -> u16 {
+        if self.0 == Self::MAX {
+            1
+        } else {
+            self.0 as u16
+        }
+    }
+
+    /// Size of each page in bytes.
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum PageSizeError {
     #[error(
-        "page size must be between {min} and {max}, or {end} (found {0})",
-        min = 512,
-        max = 32768,
-        end = 1
+        "page size must be between {min} and {max} (found {0})",
+        min = PageSize::MIN,
+        max = PageSize::MAX
     )]
-    Range(u16),
+    Range(u32),
     #[error("page size must be a power of two (found {0})")]
-    PowerOfTwo(u16),
+    PowerOfTwo(u32),
+}
+
+/// A relationship between header fields which doesn't hold, as found by
+/// [`SqliteHeader::integrity_check`].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum HeaderIntegrityError {
+    #[error(
+        "version_valid_for ({version_valid_for}) doesn't match file_change_counter ({file_change_counter})"
+    )]
+    VersionValidFor {
+        version_valid_for: u32,
+        file_change_counter: u32,
+    },
+    #[error("incremental_vacuum_mode is set, but largest_root_btree_page is 0")]
+    IncrementalVacuumWithoutRoot,
+    #[error(
+        "freelist_trunk_page ({freelist_trunk_page}) is inconsistent with freelist_page_count ({freelist_page_count})"
+    )]
+    Freelist {
+        freelist_trunk_page: u32,
+        freelist_page_count: u32,
+    },
 }
 
 #[derive(Clone, Debug, Error)]
@@ -281,3 +574,383 @@ pub enum BinaryError {
     #[error("Invalid bytes for type")]
     Validity,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Read the header of the test database.
+    fn header() -> SqliteHeader {
+        SqliteHeader::read_from_buffer(&include_bytes!("../../test.db")[..SQLITE_HEADER_SIZE])
+            .unwrap()
+    }
+
+    mod error {
+        use super::*;
+
+        /// Read the header of the test database, after applying `patch` to it.
+        fn read_patched(patch: impl FnOnce(&mut [u8])) -> SqliteHeaderError {
+            let mut buf = include_bytes!("../../test.db")[..SQLITE_HEADER_SIZE].to_vec();
+            patch(&mut buf);
+
+            SqliteHeader::read_from_buffer(&buf).unwrap_err()
+        }
+
+        #[test]
+        fn header_string() {
+            let e = read_patched(|buf| buf[..16].copy_from_slice(b"Not a DB\0\0\0\0\0\0\0\0"));
+
+            assert_eq!(
+                e.to_string(),
+                concat!(
+                    r#"invalid header string (expected "SQLite format 3\0" "#,
+                    "[53 51 4c 69 74 65 20 66 6f 72 6d 61 74 20 33 00], ",
+                    r#"found "Not a DB\0\0\0\0\0\0\0\0" "#,
+                    "[4e 6f 74 20 61 20 44 42 00 00 00 00 00 00 00 00])"
+                )
+            );
+        }
+
+        #[test]
+        fn header_string_invalid_utf8() {
+            let e = read_patched(|buf| buf[..16].fill(0xff));
+
+            assert!(
+                e.to_string()
+                    .contains(&format!("\"{}\"", "\u{fffd}".repeat(16)))
+            );
+            assert!(e.to_string().contains(&["ff"; 16].join(" ")));
+        }
+
+        #[test]
+        fn reserved() {
+            let e = read_patched(|buf| {
+                buf[75] = 0x2a;
+                buf[80] = 0x01;
+            });
+
+            assert_eq!(
+                e.to_string(),
+                "expected reserved bytes to be 0x00 (found 0x2a at offset 75)"
+            );
+        }
+    }
+
+    mod read_unchecked {
+        use super::*;
+
+        #[test]
+        fn matches_checked() {
+            let buf = &include_bytes!("../../test.db")[..SQLITE_HEADER_SIZE];
+
+            let unchecked = SqliteHeader::read_unchecked(buf);
+
+            assert_eq!(unchecked.as_bytes(), header().as_bytes());
+            assert_eq!(unchecked.page_size(), header().page_size());
+            assert_eq!(unchecked.pragmas(), header().pragmas());
+        }
+
+        #[test]
+        fn skips_validation() {
+            let mut buf = include_bytes!("../../test.db")[..SQLITE_HEADER_SIZE].to_vec();
+            buf[..16].fill(0);
+
+            assert!(SqliteHeader::read_from_buffer(&buf).is_err());
+            assert_eq!(SqliteHeader::read_unchecked(&buf).as_bytes(), buf);
+        }
+
+        #[test]
+        #[should_panic]
+        fn incorrect_size() {
+            SqliteHeader::read_unchecked(&[0; SQLITE_HEADER_SIZE - 1]);
+        }
+    }
+
+    mod read_with_page_size {
+        use super::*;
+
+        /// Read the header of the test database with a page size of 512, after applying `patch`.
+        fn read_patched(patch: impl FnOnce(&mut [u8])) -> SqliteHeader {
+            let mut buf = include_bytes!("../../test.db")[..SQLITE_HEADER_SIZE].to_vec();
+            patch(&mut buf);
+
+            SqliteHeader::read_with_page_size(&buf, 512).unwrap()
+        }
+
+        #[test]
+        fn valid_fields_kept() {
+            let header = read_patched(|_| {});
+
+            assert_eq!(header.page_size(), 512);
+            assert_eq!(header.schema_format(), super::header().schema_format());
+            assert_eq!(header.pragmas(), super::header().pragmas());
+        }
+
+        #[test]
+        fn invalid_fields_defaulted() {
+            let header = read_patched(|buf| {
+                // Page size.
+                buf[16..18].copy_from_slice(&[0x12, 0x34]);
+                // Payload fractions.
+                buf[21..24].copy_from_slice(&[0xff, 0x00, 0x01]);
+                // Schema format.
+                buf[44..48].copy_from_slice(&0xdead_u32.to_be_bytes());
+                // Text encoding.
+                buf[56..60].copy_from_slice(&9_u32.to_be_bytes());
+            });
+
+            assert_eq!(header.page_size(), 512);
+            assert_eq!(header.payload_fractions(), PayloadFractions::default());
+            assert_eq!(header.schema_format(), SchemaFormat::V4);
+            assert_eq!(header.text_encoding(), TextEncoding::Utf8);
+            assert_eq!(header.pragmas().text_encoding, TextEncoding::Utf8);
+        }
+    }
+
+    mod page_size {
+        use super::*;
+
+        use rstest::rstest;
+
+        #[rstest]
+        #[case(512, 512)]
+        #[case(4096, 4096)]
+        #[case(32768, 32768)]
+        #[case(65536, 1)]
+        fn valid(#[case] value: u32, #[case] raw: u16) {
+            let page_size = PageSize::new(value).unwrap();
+
+            assert_eq!(page_size.get(), value);
+            assert_eq!(page_size.to_raw(), raw);
+            assert_eq!(PageSize::from_raw(raw).unwrap(), page_size);
+        }
+
+        #[test]
+        fn not_power_of_two() {
+            assert!(matches!(
+                PageSize::new(1000),
+                Err(PageSizeError::PowerOfTwo(1000))
+            ));
+        }
+
+        #[rstest]
+        #[case(0)]
+        #[case(256)]
+        #[case(131072)]
+        fn out_of_range(#[case] value: u32) {
+            assert!(matches!(
+                PageSize::new(value),
+                Err(PageSizeError::Range(found)) if found == value
+            ));
+        }
+    }
+
+    mod pragmas {
+        use super::*;
+
+        #[test]
+        fn defaults() {
+            assert_eq!(
+                header().pragmas(),
+                HeaderPragmas {
+                    application_id: 0,
+                    user_version: 0,
+                    schema_cookie: 4,
+                    freelist_page_count: 0,
+                    text_encoding: TextEncoding::Utf8,
+                    auto_vacuum: false,
+                    incremental_vacuum: false,
+                }
+            );
+        }
+
+        #[test]
+        fn configured() {
+            let header = SqliteHeader::read_from_buffer(
+                &include_bytes!("../../fixtures/pragmas.db")[..SQLITE_HEADER_SIZE],
+            )
+            .unwrap();
+
+            assert_eq!(
+                header.pragmas(),
+                HeaderPragmas {
+                    application_id: 1234567,
+                    user_version: 42,
+                    schema_cookie: 1,
+                    freelist_page_count: 13,
+                    text_encoding: TextEncoding::Utf8,
+                    auto_vacuum: true,
+                    incremental_vacuum: true,
+                }
+            );
+        }
+
+        #[test]
+        fn negative_user_version() {
+            let mut header = header();
+            header.user_version = U32::new(-1i32 as u32);
+
+            assert_eq!(header.pragmas().user_version, -1);
+        }
+    }
+
+    mod integrity_check {
+        use super::*;
+
+        #[test]
+        fn valid() {
+            assert!(header().integrity_check().is_empty());
+        }
+
+        #[test]
+        fn version_valid_for() {
+            let mut header = header();
+            header.version_valid_for = U32::new(header.file_change_counter.get() - 1);
+
+            assert_eq!(
+                header.integrity_check(),
+                [HeaderIntegrityError::VersionValidFor {
+                    version_valid_for: 7,
+                    file_change_counter: 8,
+                }]
+            );
+        }
+
+        #[test]
+        fn incremental_vacuum_without_root() {
+            let mut header = header();
+            header.incremental_vacuum_mode = U32::new(1);
+
+            assert_eq!(
+                header.integrity_check(),
+                [HeaderIntegrityError::IncrementalVacuumWithoutRoot]
+            );
+
+            header.largest_root_btree_page = U32::new(3);
+            assert!(header.integrity_check().is_empty());
+        }
+
+        #[test]
+        fn freelist() {
+            let mut header = header();
+            header.freelist_trunk_page = U32::new(2);
+
+            assert_eq!(
+                header.integrity_check(),
+                [HeaderIntegrityError::Freelist {
+                    freelist_trunk_page: 2,
+                    freelist_page_count: 0,
+                }]
+            );
+
+            header.freelist_trunk_page = U32::new(0);
+            header.freelist_page_count = U32::new(4);
+            assert_eq!(
+                header.integrity_check(),
+                [HeaderIntegrityError::Freelist {
+                    freelist_trunk_page: 0,
+                    freelist_page_count: 4,
+                }]
+            );
+        }
+
+        #[test]
+        fn multiple() {
+            let mut header = header();
+            header.version_valid_for = U32::new(0);
+            header.incremental_vacuum_mode = U32::new(1);
+            header.freelist_page_count = U32::new(1);
+
+            assert_eq!(header.integrity_check().len(), 3);
+        }
+    }
+
+    mod page_count_valid {
+        use super::*;
+
+        #[test]
+        fn valid() {
+            assert!(header().page_count_valid());
+        }
+
+        #[test]
+        fn legacy_version_valid_for() {
+            let mut header = header();
+            header.version_valid_for = U32::new(header.file_change_counter.get() - 1);
+
+            assert!(!header.page_count_valid());
+        }
+
+        #[test]
+        fn zero() {
+            let mut header = header();
+            header.page_count = U32::new(0);
+
+            assert!(!header.page_count_valid());
+        }
+    }
+
+    mod setters {
+        use super::*;
+
+        #[test]
+        fn user_version() {
+            let mut header = header();
+            header.set_user_version(0xdead_beef);
+
+            assert_eq!(header.user_version(), 0xdead_beef);
+            assert_eq!(header.to_bytes()[60..64], [0xde, 0xad, 0xbe, 0xef]);
+        }
+
+        #[test]
+        fn text_encoding() {
+            let mut header = header();
+            header.set_text_encoding(TextEncoding::Utf16Be);
+
+            assert_eq!(header.text_encoding(), TextEncoding::Utf16Be);
+            assert_eq!(header.pragmas().text_encoding, TextEncoding::Utf16Be);
+        }
+
+        #[test]
+        fn schema_format() {
+            let mut header = header();
+            header.set_schema_format(SchemaFormat::V1);
+
+            assert_eq!(header.schema_format(), SchemaFormat::V1);
+        }
+
+        #[test]
+        fn remains_valid() {
+            let mut header = header();
+            header.set_user_version(7);
+            header.set_text_encoding(TextEncoding::Utf16Le);
+            header.set_schema_format(SchemaFormat::V2);
+
+            let reread = SqliteHeader::read_from_buffer(&header.to_bytes()).unwrap();
+            assert_eq!(reread.user_version(), 7);
+            assert_eq!(reread.text_encoding(), TextEncoding::Utf16Le);
+            assert_eq!(reread.schema_format(), SchemaFormat::V2);
+        }
+    }
+
+    mod to_bytes {
+        use super::*;
+
+        use rstest::rstest;
+
+        #[rstest]
+        #[case(include_bytes!("../../test.db"))]
+        #[case(include_bytes!("../../fixtures/pragmas.db"))]
+        #[case(include_bytes!("../../fixtures/reserved.db"))]
+        #[case(include_bytes!("../../fixtures/auto_vacuum.db"))]
+        fn round_trip(#[case] db: &[u8]) {
+            let buf = &db[..SQLITE_HEADER_SIZE];
+            let header = SqliteHeader::read_from_buffer(buf).unwrap();
+
+            assert_eq!(header.to_bytes(), buf);
+
+            let reread = SqliteHeader::read_from_buffer(&header.to_bytes()).unwrap();
+            assert_eq!(reread.to_bytes(), header.to_bytes());
+        }
+    }
+}
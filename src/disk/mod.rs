@@ -1,3 +1,5 @@
+pub mod be_int;
 pub mod header;
+pub mod ptrmap;
 pub mod util;
 pub mod var_int;
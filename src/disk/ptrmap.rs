@@ -0,0 +1,300 @@
+//! Pointer-map (ptrmap) pages, which are present in auto-vacuum and incremental-vacuum databases.
+//! Each ptrmap page records the type and parent of the pages which follow it, allowing pages to
+//! be relocated during vacuuming.
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use thiserror::Error;
+
+/// Size of each entry in a ptrmap page.
+#[allow(unused)]
+pub const PTRMAP_ENTRY_SIZE: usize = 5;
+
+/// Page number of the first ptrmap page, which immediately follows the first page.
+#[allow(unused)]
+pub const FIRST_PTRMAP_PAGE: u32 = 2;
+
+/// Type of a page, as recorded in a ptrmap entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum PtrmapType {
+    /// Root page of a B-Tree. The parent page number is always `0`.
+    RootPage = 1,
+    /// Page on the freelist. The parent page number is always `0`.
+    FreePage = 2,
+    /// First page of an overflow chain. The parent is the B-Tree page containing the cell.
+    Overflow1 = 3,
+    /// Subsequent page of an overflow chain. The parent is the previous overflow page.
+    Overflow2 = 4,
+    /// Non-root B-Tree page. The parent is the B-Tree page which points to it.
+    Btree = 5,
+}
+
+/// Single decoded entry from a ptrmap page.
+#[allow(unused)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PtrmapEntry {
+    pub page_type: PtrmapType,
+    pub parent: u32,
+}
+
+#[allow(unused)]
+impl PtrmapEntry {
+    /// Decode an entry from its 5 byte representation.
+    pub fn from_bytes(bytes: &[u8; PTRMAP_ENTRY_SIZE]) -> Result<Self, PtrmapError> {
+        Ok(Self {
+            page_type: PtrmapType::try_from_primitive(bytes[0])
+                .map_err(|_| PtrmapError::PageType(bytes[0]))?,
+            parent: u32::from_be_bytes(bytes[1..].try_into().expect("4 byte parent")),
+        })
+    }
+}
+
+/// Location math and entry decoding for ptrmap pages.
+#[allow(unused)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PtrmapPage<'a> {
+    /// Page number of this ptrmap page.
+    page_id: u32,
+    /// Usable portion of the page, excluding any reserved space at the end.
+    buffer: &'a [u8],
+}
+
+#[allow(unused)]
+impl<'a> PtrmapPage<'a> {
+    /// Wrap the usable portion of the ptrmap page at `page_id`.
+    pub fn new(page_id: u32, buffer: &'a [u8]) -> Self {
+        debug_assert!(Self::is_ptrmap_page(page_id, buffer.len()));
+
+        Self { page_id, buffer }
+    }
+
+    /// Number of entries which fit on a single ptrmap page.
+    pub fn entries_per_page(usable_size: usize) -> usize {
+        usable_size / PTRMAP_ENTRY_SIZE
+    }
+
+    /// Determine whether `page_id` is a ptrmap page. Ptrmap pages begin at page 2, and repeat
+    /// after every run of pages that a single ptrmap page can describe.
+    pub fn is_ptrmap_page(page_id: u32, usable_size: usize) -> bool {
+        Self::ptrmap_page_for(page_id, usable_size) == Some(page_id)
+    }
+
+    /// Find the ptrmap page which holds the entry for `page_id`. A ptrmap page will resolve to
+    /// itself, and the first page (which has no entry) will produce `None`.
+    pub fn ptrmap_page_for(page_id: u32, usable_size: usize) -> Option<u32> {
+        if page_id < FIRST_PTRMAP_PAGE {
+            return None;
+        }
+
+        // Each ptrmap page is followed by the pages that it describes.
+        let group_size = Self::entries_per_page(usable_size) as u32 + 1;
+        let group = (page_id - FIRST_PTRMAP_PAGE) / group_size;
+
+        Some(FIRST_PTRMAP_PAGE + group * group_size)
+    }
+
+    /// Find the ptrmap page and entry index which describes `page_id`. Produces `None` if the
+    /// page has no entry, which is the case for the first page and ptrmap pages.
+    pub fn locate(page_id: u32, usable_size: usize) -> Option<(u32, usize)> {
+        let ptrmap_page = Self::ptrmap_page_for(page_id, usable_size)?;
+
+        if ptrmap_page == page_id {
+            return None;
+        }
+
+        Some((ptrmap_page, (page_id - ptrmap_page - 1) as usize))
+    }
+
+    /// Decode the entry at `index` on this page, or `None` if the index is out of bounds.
+    pub fn entry(&self, index: usize) -> Option<Result<PtrmapEntry, PtrmapError>> {
+        let offset = index.checked_mul(PTRMAP_ENTRY_SIZE)?;
+        let bytes = self.buffer.get(offset..offset + PTRMAP_ENTRY_SIZE)?;
+
+        Some(PtrmapEntry::from_bytes(
+            bytes.try_into().expect("slice is entry sized"),
+        ))
+    }
+
+    /// Decode the entry describing `page_id`, or `None` if it isn't described by this page.
+    pub fn entry_for(&self, page_id: u32) -> Option<Result<PtrmapEntry, PtrmapError>> {
+        let (ptrmap_page, index) = Self::locate(page_id, self.buffer.len())?;
+
+        if ptrmap_page != self.page_id {
+            return None;
+        }
+
+        self.entry(index)
+    }
+
+    /// Iterate over each page described by this ptrmap page, alongside its entry. Iteration stops
+    /// at the first unused entry (one which is entirely zero), or after `page_count` pages.
+    pub fn entries(
+        &self,
+        page_count: u32,
+    ) -> impl Iterator<Item = (u32, Result<PtrmapEntry, PtrmapError>)> + '_ {
+        self.buffer
+            .chunks_exact(PTRMAP_ENTRY_SIZE)
+            .zip(self.page_id + 1..=page_count)
+            .take_while(|(bytes, _)| bytes.iter().any(|b| *b != 0))
+            .map(|(bytes, page_id)| {
+                (
+                    page_id,
+                    PtrmapEntry::from_bytes(bytes.try_into().expect("chunk is entry sized")),
+                )
+            })
+    }
+}
+
+#[allow(unused)]
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum PtrmapError {
+    #[error("invalid ptrmap page type: {0}")]
+    PageType(u8),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    use crate::{ctx::Ctx, disk::header::SqliteHeader};
+
+    /// Usable size of a 512 byte page, with no reserved space.
+    const USABLE_SIZE: usize = 512;
+
+    mod location {
+        use super::*;
+
+        #[test]
+        fn entries_per_page() {
+            assert_eq!(PtrmapPage::entries_per_page(USABLE_SIZE), 102);
+            assert_eq!(PtrmapPage::entries_per_page(4096), 819);
+            assert_eq!(PtrmapPage::entries_per_page(4096 - 32), 812);
+        }
+
+        #[rstest]
+        #[case(1, false)]
+        #[case(2, true)]
+        #[case(3, false)]
+        #[case(104, false)]
+        #[case(105, true)]
+        #[case(106, false)]
+        #[case(208, true)]
+        fn is_ptrmap_page(#[case] page_id: u32, #[case] expected: bool) {
+            assert_eq!(PtrmapPage::is_ptrmap_page(page_id, USABLE_SIZE), expected);
+        }
+
+        #[rstest]
+        #[case(1, None)]
+        #[case(2, None)]
+        #[case(3, Some((2, 0)))]
+        #[case(4, Some((2, 1)))]
+        #[case(104, Some((2, 101)))]
+        #[case(105, None)]
+        #[case(106, Some((105, 0)))]
+        #[case(207, Some((105, 101)))]
+        #[case(209, Some((208, 0)))]
+        fn locate(#[case] page_id: u32, #[case] expected: Option<(u32, usize)>) {
+            assert_eq!(PtrmapPage::locate(page_id, USABLE_SIZE), expected);
+        }
+    }
+
+    mod entry {
+        use super::*;
+
+        #[rstest]
+        #[case([1, 0, 0, 0, 0], PtrmapType::RootPage, 0)]
+        #[case([2, 0, 0, 0, 0], PtrmapType::FreePage, 0)]
+        #[case([3, 0, 0, 0, 4], PtrmapType::Overflow1, 4)]
+        #[case([4, 0, 0, 1, 0], PtrmapType::Overflow2, 256)]
+        #[case([5, 0xff, 0xff, 0xff, 0xff], PtrmapType::Btree, u32::MAX)]
+        fn valid(
+            #[case] bytes: [u8; PTRMAP_ENTRY_SIZE],
+            #[case] page_type: PtrmapType,
+            #[case] parent: u32,
+        ) {
+            assert_eq!(
+                PtrmapEntry::from_bytes(&bytes),
+                Ok(PtrmapEntry { page_type, parent })
+            );
+        }
+
+        #[rstest]
+        #[case(0)]
+        #[case(6)]
+        #[case(0xff)]
+        fn invalid_type(#[case] page_type: u8) {
+            assert_eq!(
+                PtrmapEntry::from_bytes(&[page_type, 0, 0, 0, 0]),
+                Err(PtrmapError::PageType(page_type))
+            );
+        }
+    }
+
+    mod auto_vacuum {
+        use super::*;
+
+        use std::fs::File;
+
+        fn ctx() -> Ctx {
+            Ctx::new(File::open("fixtures/auto_vacuum.db").unwrap())
+        }
+
+        fn usable_size(header: &SqliteHeader) -> usize {
+            header.page_size() as usize - header.page_end_padding() as usize
+        }
+
+        #[test]
+        fn entries() {
+            let ctx = ctx();
+            let usable_size = usable_size(&ctx.header);
+            assert_eq!(ctx.header.largest_root_btree_page(), 4);
+
+            let buffer = ctx.pager.get_page(FIRST_PTRMAP_PAGE).unwrap();
+            let page = PtrmapPage::new(FIRST_PTRMAP_PAGE, &buffer[..usable_size]);
+
+            let entries = page
+                .entries(ctx.header.page_count())
+                .map(|(page_id, entry)| {
+                    let entry = entry.unwrap();
+                    (page_id, entry.page_type, entry.parent)
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                entries,
+                [
+                    // Root pages of `items` and `notes`.
+                    (3, PtrmapType::RootPage, 0),
+                    (4, PtrmapType::RootPage, 0),
+                    // Leaves of `items`.
+                    (5, PtrmapType::Btree, 3),
+                    (6, PtrmapType::Btree, 3),
+                    (7, PtrmapType::Btree, 3),
+                    // Overflow chain of the row in `notes`.
+                    (8, PtrmapType::Overflow1, 4),
+                    (9, PtrmapType::Overflow2, 8),
+                ]
+            );
+        }
+
+        #[rstest]
+        #[case(3, Some(PtrmapType::RootPage))]
+        #[case(9, Some(PtrmapType::Overflow2))]
+        #[case(1, None)]
+        #[case(2, None)]
+        #[case(106, None)]
+        fn entry_for(#[case] page_id: u32, #[case] expected: Option<PtrmapType>) {
+            let ctx = ctx();
+            let buffer = ctx.pager.get_page(FIRST_PTRMAP_PAGE).unwrap();
+            let page = PtrmapPage::new(FIRST_PTRMAP_PAGE, &buffer[..usable_size(&ctx.header)]);
+
+            assert_eq!(
+                page.entry_for(page_id)
+                    .map(|entry| entry.unwrap().page_type),
+                expected
+            );
+        }
+    }
+}
@@ -7,7 +7,19 @@ use zerocopy::{FromBytes, Immutable, IntoBytes, Unaligned};
 #[repr(transparent)]
 pub struct ConstU8<const N: u8>(u8);
 
+impl<const N: u8> Default for ConstU8<N> {
+    /// The constant value.
+    fn default() -> Self {
+        Self(N)
+    }
+}
+
 impl<const N: u8> ConstU8<N> {
+    /// Get the deserialised value, without checking it against the constant.
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+
     pub fn validate(&self) -> Result<u8, ConstU8Error> {
         if self.0 != N {
             return Err(ConstU8Error {
@@ -4,6 +4,11 @@ use derive_more::{Deref, DerefMut};
 pub struct VarInt(i64);
 
 impl VarInt {
+    #[allow(unused)]
+    pub fn new(value: i64) -> Self {
+        Self(value)
+    }
+
     pub fn from_buffer(mut buf: &[u8]) -> (Self, &[u8]) {
         let mut value: i64 = 0;
 
@@ -23,7 +28,7 @@ impl VarInt {
     }
 
     #[allow(unused)]
-    fn to_bytes(self) -> Vec<u8> {
+    pub fn to_bytes(self) -> Vec<u8> {
         if self.0 == 0 {
             return vec![0x00];
         }
@@ -1,8 +1,20 @@
-use std::iter;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    iter,
+    string::FromUtf8Error,
+};
 
+use thiserror::Error;
 use ux::{i24, i48};
 
-use crate::disk::var_int::VarInt;
+use crate::{
+    btree::{page::PageType, payload::Payload},
+    command::CreateStatement,
+    disk::{be_int, header::SchemaFormat, var_int::VarInt},
+};
 
 #[derive(Clone, Debug)]
 #[allow(unused)]
@@ -17,7 +29,6 @@ pub enum RecordType {
     F64(f64),
     Zero,
     One,
-    Reserved,
     Blob(Vec<u8>),
     String(String),
 }
@@ -38,89 +49,956 @@ impl RecordType {
             RecordType::I32(i) => i.into(),
             RecordType::I48(i) => i.into(),
             RecordType::I64(i) => i,
+            RecordType::Zero => 0,
+            RecordType::One => 1,
             _ => return None,
         })
     }
 }
 
+impl Ord for RecordType {
+    /// Order values following SQLite's sort order: `NULL` first, followed by numeric values (with
+    /// integers and floats compared numerically), text compared by bytes, then blobs.
+    ///
+    /// `NULL` values compare equal to each other for the purpose of sorting.
+    fn cmp(&self, other: &Self) -> Ordering {
+        /// Rank of each storage class in the ordering.
+        fn rank(value: &RecordType) -> u8 {
+            match value {
+                RecordType::Null => 0,
+                RecordType::String(_) => 2,
+                RecordType::Blob(_) => 3,
+                _ => 1,
+            }
+        }
+
+        rank(self)
+            .cmp(&rank(other))
+            .then_with(|| match (self, other) {
+                (RecordType::String(a), RecordType::String(b)) => a.as_bytes().cmp(b.as_bytes()),
+                (RecordType::Blob(a), RecordType::Blob(b)) => a.cmp(b),
+                (RecordType::F64(a), RecordType::F64(b)) => compare_float(*a, *b),
+                (RecordType::F64(a), b) => {
+                    compare_integer_float(b.clone().integer().unwrap_or_default(), *a).reverse()
+                }
+                (a, RecordType::F64(b)) => {
+                    compare_integer_float(a.clone().integer().unwrap_or_default(), *b)
+                }
+                (a, b) => a.clone().integer().cmp(&b.clone().integer()),
+            })
+    }
+}
+
+impl PartialOrd for RecordType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for RecordType {
+    /// Values are equal if they sort equally, so `I8(2)`, `I64(2)`, and `F64(2.0)` are all equal.
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for RecordType {}
+
+impl Hash for RecordType {
+    /// Hash consistently with [`PartialEq`], so floats with an integral value hash as the integer.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        /// 2^63, which is the first float larger than every `i64`.
+        const LIMIT: f64 = 9223372036854775808.0;
+
+        match self {
+            RecordType::Null => state.write_u8(0),
+            RecordType::String(s) => {
+                state.write_u8(2);
+                s.as_bytes().hash(state);
+            }
+            RecordType::Blob(blob) => {
+                state.write_u8(3);
+                blob.hash(state);
+            }
+            RecordType::F64(n) if n.is_nan() => state.write_u8(4),
+            // Also normalises negative zero.
+            RecordType::F64(n) if n.fract() == 0.0 && (-LIMIT..LIMIT).contains(n) => {
+                state.write_u8(1);
+                (*n as i64).hash(state);
+            }
+            RecordType::F64(n) => {
+                state.write_u8(5);
+                n.to_bits().hash(state);
+            }
+            // Every remaining variant is an integer.
+            value => {
+                state.write_u8(1);
+                value.clone().integer().unwrap_or_default().hash(state);
+            }
+        }
+    }
+}
+
+/// Compare two floats, with `NaN` sorting before all other values. Positive and negative zero are
+/// equal.
+pub(crate) fn compare_float(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b)
+        .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()).reverse())
+}
+
+/// Exactly compare an integer to a float, without losing precision by converting the integer.
+pub(crate) fn compare_integer_float(i: i64, f: f64) -> Ordering {
+    // 2^63, which is the first float larger than every `i64`.
+    const LIMIT: f64 = 9223372036854775808.0;
+
+    if f.is_nan() {
+        Ordering::Greater
+    } else if f >= LIMIT {
+        Ordering::Less
+    } else if f < -LIMIT {
+        Ordering::Greater
+    } else {
+        // Within range, so the integral part is exactly representable.
+        i.cmp(&(f.trunc() as i64))
+            .then_with(|| compare_float(0.0, f.fract()))
+    }
+}
+
+impl Display for RecordType {
+    /// Render the value similarly to the SQLite shell, with blobs as a hex literal.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordType::Null => write!(f, "NULL"),
+            RecordType::I8(i) => write!(f, "{i}"),
+            RecordType::I16(i) => write!(f, "{i}"),
+            RecordType::I24(i) => write!(f, "{i}"),
+            RecordType::I32(i) => write!(f, "{i}"),
+            RecordType::I48(i) => write!(f, "{i}"),
+            RecordType::I64(i) => write!(f, "{i}"),
+            RecordType::F64(n) if n.is_nan() => write!(f, "NaN"),
+            RecordType::F64(n) if n.is_infinite() => {
+                write!(f, "{}Inf", if n.is_sign_negative() { "-" } else { "" })
+            }
+            // Debug formatting retains the decimal point for whole numbers.
+            RecordType::F64(n) => write!(f, "{n:?}"),
+            RecordType::Zero => write!(f, "0"),
+            RecordType::One => write!(f, "1"),
+            RecordType::Blob(blob) => {
+                write!(f, "x'")?;
+                for b in blob {
+                    write!(f, "{b:02x}")?;
+                }
+                write!(f, "'")
+            }
+            RecordType::String(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RecordType {
+    /// Serialise the value as its closest native representation. Blobs are serialised as bytes.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RecordType::Null => serializer.serialize_none(),
+            RecordType::I8(i) => serializer.serialize_i8(*i),
+            RecordType::I16(i) => serializer.serialize_i16(*i),
+            RecordType::I24(i) => serializer.serialize_i32((*i).into()),
+            RecordType::I32(i) => serializer.serialize_i32(*i),
+            RecordType::I48(i) => serializer.serialize_i64((*i).into()),
+            RecordType::I64(i) => serializer.serialize_i64(*i),
+            RecordType::F64(n) => serializer.serialize_f64(*n),
+            RecordType::Zero => serializer.serialize_i64(0),
+            RecordType::One => serializer.serialize_i64(1),
+            RecordType::Blob(blob) => serializer.serialize_bytes(blob),
+            RecordType::String(s) => serializer.serialize_str(s),
+        }
+    }
+}
+
 #[allow(unused)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Record {
     pub id: i64,
     pub fields: Vec<RecordType>,
 }
 
 impl Record {
-    pub fn from_buf(id: i64, buf: &[u8]) -> Self {
-        Self {
+    /// Decode a record from its serialised form, assuming the latest schema format.
+    #[allow(unused)]
+    pub fn from_buf(id: i64, buf: &[u8]) -> Result<Self, RecordError> {
+        Self::from_buf_with_format(id, buf, SchemaFormat::V4)
+    }
+
+    /// Decode a record from its serialised form, only accepting serial types which are supported
+    /// by `schema_format`.
+    pub fn from_buf_with_format(
+        id: i64,
+        buf: &[u8],
+        schema_format: SchemaFormat,
+    ) -> Result<Self, RecordError> {
+        Ok(Self {
             id,
-            fields: {
-                let buf_len = buf.len();
-                let (header_length, buf) = VarInt::from_buffer(buf);
-                let remaining_header = *header_length as usize - (buf_len - buf.len());
-
-                let mut header = &buf[..remaining_header];
-                let mut body = &buf[remaining_header..];
-
-                iter::from_fn(|| {
-                    if header.is_empty() {
-                        assert!(body.is_empty());
-                        return None;
-                    }
-
-                    let (serial_type, rest) = VarInt::from_buffer(header);
-                    header = rest;
-
-                    let mut take_bytes = |n| {
-                        let bytes = &body[..n];
-                        body = &body[n..];
-                        bytes
-                    };
-
-                    let mut i64_from_bytes = |n| {
-                        assert!(n <= 8);
-
-                        take_bytes(n).iter().fold(0i64, |n, b| (n << 8) | *b as i64)
-                    };
-
-                    let field = match *serial_type {
-                        0 => RecordType::Null,
-                        1 => RecordType::I8(i64_from_bytes(1) as i8),
-                        2 => RecordType::I16(i64_from_bytes(2) as i16),
-                        3 => RecordType::I24(i24::new(i64_from_bytes(3) as i32)),
-                        4 => RecordType::I32(i64_from_bytes(4) as i32),
-                        5 => RecordType::I48(i48::new(i64_from_bytes(6))),
-                        6 => RecordType::I64(i64_from_bytes(8)),
-                        7 => RecordType::F64(f64::from_bits(i64_from_bytes(8) as u64)),
-                        8 => RecordType::Zero,
-                        9 => RecordType::One,
-                        10 | 11 => RecordType::Reserved,
-                        n @ 12.. if n % 2 == 0 => {
-                            let length = (n as usize - 12) / 2;
-
-                            let mut buf = vec![0; length];
-                            buf.copy_from_slice(take_bytes(length));
-
-                            RecordType::Blob(buf)
-                        }
-                        n @ 13.. if n % 2 == 1 => {
-                            let length = (n as usize - 13) / 2;
-
-                            let mut buf = vec![0; length];
-                            buf.copy_from_slice(take_bytes(length));
-
-                            RecordType::String(
-                                // TODO: Use different encoding depending on DB config
-                                String::from_utf8(buf).unwrap(),
-                            )
-                        }
-                        _ => unreachable!(),
-                    };
-
-                    Some(field)
-                })
-                .collect()
+            fields: Self::decode(buf, schema_format)
+                .map(|field| field.map(|(_, value)| value))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Decode each field of a record from its serialised form, alongside the serial type it was
+    /// stored with. This exposes the physical representation of each value (for example, whether
+    /// an integer was stored in 3 or 4 bytes).
+    ///
+    /// Decoding stops after the first error.
+    #[allow(unused)]
+    pub fn iter_with_types(
+        buf: &[u8],
+    ) -> impl Iterator<Item = Result<(u64, RecordType), RecordError>> + '_ {
+        Self::decode(buf, SchemaFormat::V4)
+    }
+
+    /// Pair each field with the name of its column in `schema`. Fails if the record doesn't have
+    /// exactly one field for each column.
+    #[allow(unused)]
+    pub fn into_map(
+        self,
+        schema: &CreateStatement,
+    ) -> Result<HashMap<String, RecordType>, RecordError> {
+        if schema.columns.len() != self.fields.len() {
+            return Err(RecordError::ColumnCount {
+                columns: schema.columns.len(),
+                fields: self.fields.len(),
+            });
+        }
+
+        Ok(schema
+            .columns
+            .iter()
+            .map(|column| column.column_name.to_string())
+            .zip(self.fields)
+            .collect())
+    }
+
+    /// Count the fields of a record from its serialised form, only reading the serial types in the
+    /// header. The body isn't decoded, so this won't detect any errors within it.
+    #[allow(unused)]
+    pub fn column_count(buf: &[u8]) -> Result<usize, RecordError> {
+        let mut header = RecordFields::new(buf, SchemaFormat::V4)?.header;
+        let mut count = 0;
+
+        while !header.is_empty() {
+            (_, header) = VarInt::from_buffer(header);
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Borrow the text of the field at `index` directly from the page holding `payload`, without
+    /// copying it. Will be `None` if there is no field at `index`, if it isn't text, or if the
+    /// payload spills onto overflow pages (as it isn't stored contiguously).
+    ///
+    /// Only the fields before `index` are read, and they aren't decoded. As with
+    /// [`RecordType::String`], the text is assumed to be UTF-8.
+    #[allow(unused)]
+    pub fn text_ref<T: PageType>(
+        payload: &Payload<T>,
+        index: usize,
+    ) -> Result<Option<&str>, RecordError> {
+        match payload.as_local() {
+            Some(buf) => Self::text_ref_in(buf, index),
+            None => Ok(None),
+        }
+    }
+
+    /// Borrow the text of the field at `index` from a record's serialised form. See
+    /// [`Self::text_ref`].
+    fn text_ref_in(buf: &[u8], index: usize) -> Result<Option<&str>, RecordError> {
+        let mut fields = RecordFields::new(buf, SchemaFormat::V4)?;
+
+        for _ in 0..index {
+            if fields.next_raw()?.is_none() {
+                return Ok(None);
+            }
+        }
+
+        match fields.next_raw()? {
+            Some((serial_type, bytes)) if serial_type >= 13 && serial_type % 2 == 1 => {
+                std::str::from_utf8(bytes)
+                    // Produce the same error as an owned string, only allocating when invalid.
+                    .map_err(|_| String::from_utf8(bytes.to_vec()).unwrap_err().into())
+                    .map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Decode each field of a record, alongside its serial type. See [`Self::iter_with_types`].
+    fn decode(
+        buf: &[u8],
+        schema_format: SchemaFormat,
+    ) -> impl Iterator<Item = Result<(u64, RecordType), RecordError>> + '_ {
+        let mut fields = RecordFields::new(buf, schema_format);
+        let mut done = false;
+
+        iter::from_fn(move || {
+            if done {
+                return None;
+            }
+
+            let field = match &mut fields {
+                Ok(fields) => fields.next_field().transpose()?,
+                Err(e) => Err(e.clone()),
+            };
+
+            done = field.is_err();
+            Some(field)
+        })
+    }
+}
+
+/// Decoder over the header and body of a serialised record.
+struct RecordFields<'a> {
+    /// Remaining serial types in the header.
+    header: &'a [u8],
+    /// Remaining values in the body.
+    body: &'a [u8],
+    /// Schema format that the record was written with.
+    schema_format: SchemaFormat,
+}
+
+impl<'a> RecordFields<'a> {
+    fn new(buf: &'a [u8], schema_format: SchemaFormat) -> Result<Self, RecordError> {
+        let buf_len = buf.len();
+        let (header_length, buf) = VarInt::from_buffer(buf);
+        let header_length = *header_length as usize;
+
+        let remaining_header = header_length
+            .checked_sub(buf_len - buf.len())
+            .filter(|remaining_header| *remaining_header <= buf.len())
+            .ok_or(RecordError::HeaderLength {
+                header_length,
+                length: buf_len,
+            })?;
+
+        Ok(Self {
+            header: &buf[..remaining_header],
+            body: &buf[remaining_header..],
+            schema_format,
+        })
+    }
+
+    /// Take `n` bytes from the body. Bounds-checked, so a truncated record won't slice out of
+    /// range.
+    fn take_bytes(&mut self, n: usize) -> Result<&'a [u8], RecordError> {
+        let bytes = self.body.get(..n).ok_or(RecordError::BodyUnderflow {
+            expected: n,
+            remaining: self.body.len(),
+        })?;
+        self.body = &self.body[n..];
+        Ok(bytes)
+    }
+
+    /// Take the next serial type from the header, alongside the bytes of its value from the body,
+    /// or `None` once the header is exhausted. The value isn't decoded.
+    fn next_raw(&mut self) -> Result<Option<(i64, &'a [u8])>, RecordError> {
+        if self.header.is_empty() {
+            return Ok(None);
+        }
+
+        let (serial_type, rest) = VarInt::from_buffer(self.header);
+        self.header = rest;
+
+        let length = match *serial_type {
+            0 | 8 | 9 => 0,
+            n @ 1..=4 => n as usize,
+            5 => 6,
+            6 | 7 => 8,
+            n @ 12.. => (n as usize - 12) / 2,
+            n => return Err(RecordError::ReservedSerialType(n)),
+        };
+
+        Ok(Some((*serial_type, self.take_bytes(length)?)))
+    }
+
+    /// Decode the next field, or `None` once the header is exhausted.
+    fn next_field(&mut self) -> Result<Option<(u64, RecordType)>, RecordError> {
+        let Some((serial_type, bytes)) = self.next_raw()? else {
+            if !self.body.is_empty() {
+                return Err(RecordError::TrailingBody(self.body.len()));
+            }
+
+            return Ok(None);
+        };
+
+        let value = match serial_type {
+            0 => RecordType::Null,
+            1 => RecordType::I8(be_int::read_signed(1, bytes) as i8),
+            2 => RecordType::I16(be_int::read_signed(2, bytes) as i16),
+            3 => RecordType::I24(be_int::read_i24(bytes)),
+            4 => RecordType::I32(be_int::read_signed(4, bytes) as i32),
+            5 => RecordType::I48(be_int::read_i48(bytes)),
+            6 => RecordType::I64(be_int::read_signed(8, bytes)),
+            7 => {
+                let n = f64::from_be_bytes(bytes.try_into().expect("8 byte float"));
+
+                // SQLite never stores NaN, and decodes any NaN it finds as `NULL`.
+                if n.is_nan() {
+                    RecordType::Null
+                } else {
+                    RecordType::F64(n)
+                }
+            }
+            // The integer constants were introduced in schema format 4.
+            n @ (8 | 9) if self.schema_format < SchemaFormat::V4 => {
+                return Err(RecordError::UnsupportedSerialType {
+                    serial_type: n,
+                    schema_format: self.schema_format,
+                });
+            }
+            8 => RecordType::Zero,
+            9 => RecordType::One,
+            n if n % 2 == 0 => RecordType::Blob(bytes.to_vec()),
+            // TODO: Use different encoding depending on DB config
+            _ => RecordType::String(String::from_utf8(bytes.to_vec())?),
+        };
+
+        Ok(Some((serial_type as u64, value)))
+    }
+}
+
+/// Error produced whilst decoding a [`Record`].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum RecordError {
+    #[error("header length ({header_length}) exceeds record length ({length})")]
+    HeaderLength { header_length: usize, length: usize },
+    #[error("record body too short (expected {expected} bytes, found {remaining})")]
+    BodyUnderflow { expected: usize, remaining: usize },
+    #[error("reserved serial type: {0}")]
+    ReservedSerialType(i64),
+    #[error("serial type {serial_type} is not supported by schema format {schema_format:?}")]
+    UnsupportedSerialType {
+        serial_type: i64,
+        schema_format: SchemaFormat,
+    },
+    #[error("{0} unused bytes at end of record body")]
+    TrailingBody(usize),
+    #[error(transparent)]
+    Utf8(#[from] FromUtf8Error),
+    #[error("record has {fields} fields, but the schema has {columns} columns")]
+    ColumnCount { columns: usize, fields: usize },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    mod display {
+        use super::*;
+
+        #[rstest]
+        #[case(RecordType::Null, "NULL")]
+        #[case(RecordType::I8(-5), "-5")]
+        #[case(RecordType::I16(300), "300")]
+        #[case(RecordType::I24(i24::new(-70000)), "-70000")]
+        #[case(RecordType::I32(5), "5")]
+        #[case(RecordType::I48(i48::new(1 << 40)), "1099511627776")]
+        #[case(RecordType::I64(i64::MIN), "-9223372036854775808")]
+        #[case(RecordType::F64(2.5), "2.5")]
+        #[case(RecordType::F64(1.0), "1.0")]
+        #[case(RecordType::F64(f64::NAN), "NaN")]
+        #[case(RecordType::F64(f64::INFINITY), "Inf")]
+        #[case(RecordType::F64(f64::NEG_INFINITY), "-Inf")]
+        #[case(RecordType::Zero, "0")]
+        #[case(RecordType::One, "1")]
+        #[case(RecordType::Blob(vec![]), "x''")]
+        #[case(RecordType::Blob(vec![0x00, 0xab, 0xff]), "x'00abff'")]
+        #[case(RecordType::String("hello world".into()), "hello world")]
+        fn render(#[case] value: RecordType, #[case] expected: &str) {
+            assert_eq!(value.to_string(), expected);
+        }
+
+        #[test]
+        fn debug_unchanged() {
+            assert_eq!(format!("{:?}", RecordType::I32(5)), "I32(5)");
+        }
+    }
+
+    mod ord {
+        use super::*;
+
+        #[test]
+        fn sort_mixed() {
+            let mut values = vec![
+                RecordType::String("b".into()),
+                RecordType::F64(2.5),
+                RecordType::Blob(vec![0x01]),
+                RecordType::I32(2),
+                RecordType::Null,
+                RecordType::String("B".into()),
+                RecordType::I64(i64::MAX),
+                RecordType::F64(-1.5),
+                RecordType::Blob(vec![0x00, 0xff]),
+                RecordType::One,
+                RecordType::I8(3),
+                RecordType::String("a".into()),
+                RecordType::F64(1e19),
+            ];
+
+            values.sort();
+
+            assert_eq!(
+                values.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                [
+                    "NULL",
+                    "-1.5",
+                    "1",
+                    "2",
+                    "2.5",
+                    "3",
+                    "9223372036854775807",
+                    "1e19",
+                    "B",
+                    "a",
+                    "b",
+                    "x'00ff'",
+                    "x'01'"
+                ]
+            );
+        }
+
+        #[rstest]
+        #[case(RecordType::I32(2), RecordType::F64(2.5), Ordering::Less)]
+        #[case(RecordType::F64(2.5), RecordType::I8(3), Ordering::Less)]
+        #[case(RecordType::I8(2), RecordType::F64(2.0), Ordering::Equal)]
+        #[case(RecordType::I64(-3), RecordType::F64(-2.5), Ordering::Less)]
+        #[case(RecordType::I64(-2), RecordType::F64(-2.5), Ordering::Greater)]
+        #[case(RecordType::Zero, RecordType::F64(-0.0), Ordering::Equal)]
+        #[case(RecordType::One, RecordType::I64(1), Ordering::Equal)]
+        #[case(
+            RecordType::I64(i64::MAX),
+            RecordType::F64(9223372036854775807.0),
+            Ordering::Less
+        )]
+        #[case(RecordType::I64(i64::MIN), RecordType::F64(-9223372036854775808.0), Ordering::Equal)]
+        #[case(RecordType::I64(1 << 53 | 1), RecordType::F64((1u64 << 53) as f64), Ordering::Greater)]
+        #[case(RecordType::Null, RecordType::F64(f64::NEG_INFINITY), Ordering::Less)]
+        #[case(RecordType::F64(f64::NAN), RecordType::I64(i64::MIN), Ordering::Less)]
+        #[case(RecordType::F64(f64::INFINITY), RecordType::String("".into()), Ordering::Less)]
+        #[case(RecordType::String("z".into()), RecordType::Blob(vec![]), Ordering::Less)]
+        #[case(RecordType::String("é".into()), RecordType::String("z".into()), Ordering::Greater)]
+        fn compare(#[case] a: RecordType, #[case] b: RecordType, #[case] expected: Ordering) {
+            assert_eq!(a.cmp(&b), expected);
+            assert_eq!(b.cmp(&a), expected.reverse());
+        }
+    }
+
+    mod hash {
+        use super::*;
+
+        use std::hash::{BuildHasher, RandomState};
+
+        #[rstest]
+        #[case(RecordType::I8(2), RecordType::I64(2))]
+        #[case(RecordType::I8(2), RecordType::F64(2.0))]
+        #[case(RecordType::Zero, RecordType::F64(-0.0))]
+        #[case(RecordType::One, RecordType::I24(i24::new(1)))]
+        #[case(RecordType::I64(i64::MIN), RecordType::F64(i64::MIN as f64))]
+        #[case(RecordType::F64(f64::NAN), RecordType::F64(-f64::NAN))]
+        #[case(RecordType::F64(1.5), RecordType::F64(1.5))]
+        #[case(RecordType::String("a".into()), RecordType::String("a".into()))]
+        fn equal_values(#[case] a: RecordType, #[case] b: RecordType) {
+            let state = RandomState::new();
+
+            assert_eq!(a, b);
+            assert_eq!(state.hash_one(&a), state.hash_one(&b));
+        }
+    }
+
+    mod from_buf {
+        use super::*;
+
+        /// Build a record from a serial type and its body, returning the single field.
+        fn field(serial_type: u8, body: &[u8]) -> Option<RecordType> {
+            let mut buf = vec![2, serial_type];
+            buf.extend_from_slice(body);
+
+            Record::from_buf(1, &buf).ok()?.fields.pop()
+        }
+
+        #[rstest]
+        #[case(1, &[0xff], "-1")]
+        #[case(2, &[0xff, 0xfe], "-2")]
+        #[case(3, &[0xff, 0xff, 0xfd], "-3")]
+        #[case(3, &[0x80, 0x00, 0x00], "-8388608")]
+        #[case(3, &[0x7f, 0xff, 0xff], "8388607")]
+        #[case(4, &[0xff, 0xff, 0xff, 0xfc], "-4")]
+        #[case(5, &[0xff, 0xff, 0xff, 0xff, 0xff, 0xfb], "-5")]
+        #[case(5, &[0x80, 0x00, 0x00, 0x00, 0x00, 0x00], "-140737488355328")]
+        #[case(5, &[0x00, 0x01, 0x00, 0x00, 0x00, 0x00], "4294967296")]
+        #[case(6, &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfa], "-6")]
+        fn sign_extension(#[case] serial_type: u8, #[case] body: &[u8], #[case] expected: &str) {
+            assert_eq!(field(serial_type, body).unwrap().to_string(), expected);
+        }
+
+        #[rstest]
+        #[case(0x3ff8_0000_0000_0000, 1.5)]
+        #[case(0xbff8_0000_0000_0000, -1.5)]
+        #[case(0x0000_0000_0000_0000, 0.0)]
+        #[case(0x8000_0000_0000_0000, -0.0)]
+        #[case(0x7ff0_0000_0000_0000, f64::INFINITY)]
+        #[case(0xfff0_0000_0000_0000, f64::NEG_INFINITY)]
+        #[case(0x0000_0000_0000_0001, f64::from_bits(1))]
+        #[case(0x7fef_ffff_ffff_ffff, f64::MAX)]
+        #[case(0x4059_0000_0000_0000, 100.0)]
+        fn float(#[case] bits: u64, #[case] expected: f64) {
+            let Some(RecordType::F64(n)) = field(7, &bits.to_be_bytes()) else {
+                panic!("expected float");
+            };
+
+            // Compare bits, so that the sign of zero is checked.
+            assert_eq!(n.to_bits(), expected.to_bits());
+        }
+
+        #[rstest]
+        #[case(0x7ff8_0000_0000_0000)]
+        #[case(0xfff8_0000_0000_0000)]
+        #[case(0x7ff0_0000_0000_0001)]
+        #[case(0x7fff_ffff_ffff_ffff)]
+        fn float_nan_is_null(#[case] bits: u64) {
+            assert!(matches!(
+                field(7, &bits.to_be_bytes()),
+                Some(RecordType::Null)
+            ));
+        }
+
+        #[rstest]
+        #[case(3, &[0xff, 0xff])]
+        #[case(5, &[0x00; 5])]
+        #[case(6, &[])]
+        #[case(7, &[0x00; 7])]
+        #[case(12 + 2 * 4, &[0x00; 3])]
+        #[case(13 + 2 * 4, b"abc")]
+        fn truncated_body(#[case] serial_type: u8, #[case] body: &[u8]) {
+            assert!(field(serial_type, body).is_none());
+        }
+
+        #[test]
+        fn fields() {
+            let record = Record::from_buf(3, &[4, 1, 0, 0x13, 0x05, b'a', b'b', b'c']).unwrap();
+
+            assert_eq!(record.id, 3);
+            assert_eq!(
+                record.fields,
+                [
+                    RecordType::I8(5),
+                    RecordType::Null,
+                    RecordType::String("abc".into())
+                ]
+            );
+        }
+
+        #[rstest]
+        #[case(&[10, 1, 1], RecordError::HeaderLength { header_length: 10, length: 3 })]
+        #[case(&[0], RecordError::HeaderLength { header_length: 0, length: 1 })]
+        #[case(&[2, 4, 0], RecordError::BodyUnderflow { expected: 4, remaining: 1 })]
+        #[case(&[2, 10], RecordError::ReservedSerialType(10))]
+        #[case(&[2, 11], RecordError::ReservedSerialType(11))]
+        #[case(&[2, 1, 5, 6], RecordError::TrailingBody(1))]
+        fn invalid(#[case] buf: &[u8], #[case] expected: RecordError) {
+            assert_eq!(Record::from_buf(1, buf).unwrap_err(), expected);
+        }
+
+        #[test]
+        fn invalid_utf8() {
+            assert!(matches!(
+                Record::from_buf(1, &[2, 0x0f, 0xff]),
+                Err(RecordError::Utf8(_))
+            ));
+        }
+    }
+
+    mod column_count {
+        use super::*;
+
+        #[rstest]
+        #[case::empty(&[1])]
+        #[case::single(&[2, 1, 5])]
+        #[case::mixed(&[4, 1, 0, 0x13, 0x05, b'a', b'b', b'c'])]
+        #[case::constants(&[3, 8, 9])]
+        // Serial type of a 64 byte blob, which requires a 2 byte varint.
+        #[case::multi_byte_serial_type(&[3, 0x81, 0x0c].iter().copied().chain([0; 64]).collect::<Vec<_>>())]
+        fn matches_from_buf(#[case] buf: &[u8]) {
+            assert_eq!(
+                Record::column_count(buf).unwrap(),
+                Record::from_buf(1, buf).unwrap().fields.len()
+            );
+        }
+
+        #[test]
+        fn body_not_decoded() {
+            // Header is valid, but the body is truncated and contains invalid UTF-8.
+            let buf = [3, 6, 0x0f, 0xff];
+
+            assert!(Record::from_buf(1, &buf).is_err());
+            assert_eq!(Record::column_count(&buf).unwrap(), 2);
+        }
+
+        #[rstest]
+        #[case(&[10, 1, 1], RecordError::HeaderLength { header_length: 10, length: 3 })]
+        #[case(&[0], RecordError::HeaderLength { header_length: 0, length: 1 })]
+        fn invalid_header(#[case] buf: &[u8], #[case] expected: RecordError) {
+            assert_eq!(Record::column_count(buf).unwrap_err(), expected);
+        }
+    }
+
+    mod text_ref {
+        use super::*;
+
+        use std::fs::File;
+
+        use crate::{
+            btree::{
+                self,
+                page::{Page, PageExt, Table},
             },
+            ctx::Ctx,
+        };
+
+        /// Record containing an integer, text, a blob, null, and more text.
+        const BUF: &[u8] = &[
+            6, 1, 0x13, 0x10, 0, 0x17, 5, b'a', b'b', b'c', 0x00, 0x01, b'h', b'e', b'l', b'l',
+            b'o',
+        ];
+
+        #[test]
+        fn matches_owned() {
+            let owned = Record::from_buf(1, BUF).unwrap();
+
+            for index in [1, 4] {
+                assert_eq!(
+                    Record::text_ref_in(BUF, index).unwrap(),
+                    owned.fields[index].clone().string().as_deref()
+                );
+            }
+        }
+
+        #[test]
+        fn borrows_from_buffer() {
+            let text = Record::text_ref_in(BUF, 4).unwrap().unwrap();
+
+            assert_eq!(text, "hello");
+            assert!(BUF.as_ptr_range().contains(&text.as_ptr()));
+        }
+
+        #[rstest]
+        #[case::integer(0)]
+        #[case::blob(2)]
+        #[case::null(3)]
+        #[case::out_of_range(5)]
+        fn not_text(#[case] index: usize) {
+            assert_eq!(Record::text_ref_in(BUF, index).unwrap(), None);
+        }
+
+        #[test]
+        fn later_fields_not_decoded() {
+            // Second field is truncated, but isn't read.
+            assert_eq!(
+                Record::text_ref_in(&[3, 0x0f, 6, b'a'], 0).unwrap(),
+                Some("a")
+            );
+        }
+
+        #[rstest]
+        #[case::no_overflow(0, Some("short"))]
+        #[case::overflow(2, None)]
+        fn from_payload(#[case] cell: usize, #[case] expected: Option<&str>) {
+            let ctx = Ctx::new(File::open("fixtures/overflow.db").unwrap());
+            let page = Page::<Table>::from_buffer(ctx.pager.get_page(2).unwrap());
+            let cell = btree::traverse(ctx.clone(), page).nth(cell).unwrap();
+
+            let text = Record::text_ref(&cell.payload, 1).unwrap();
+
+            assert_eq!(text, expected);
+            if let Some(text) = text {
+                // Borrowed from the leaf page, rather than a copy of the payload.
+                let page = ctx.pager.get_page(13).unwrap();
+                assert!(page.raw().as_ptr_range().contains(&text.as_ptr()));
+            }
+        }
+
+        #[test]
+        fn invalid_utf8() {
+            assert!(matches!(
+                Record::text_ref_in(&[2, 0x0f, 0xff], 0),
+                Err(RecordError::Utf8(_))
+            ));
+        }
+    }
+
+    mod into_map {
+        use super::*;
+
+        use crate::command::parse_command;
+
+        fn schema() -> CreateStatement {
+            parse_command("create table t (id integer, name text, score real)")
+        }
+
+        #[test]
+        fn by_name() {
+            let record = Record {
+                id: 1,
+                fields: vec![
+                    RecordType::I8(1),
+                    RecordType::String("alice".to_string()),
+                    RecordType::F64(2.5),
+                ],
+            };
+
+            let map = record.into_map(&schema()).unwrap();
+
+            assert_eq!(map.len(), 3);
+            assert_eq!(map["id"], RecordType::I8(1));
+            assert_eq!(map["name"], RecordType::String("alice".to_string()));
+            assert_eq!(map["score"], RecordType::F64(2.5));
+        }
+
+        #[rstest]
+        #[case::too_few(vec![RecordType::I8(1)])]
+        #[case::too_many(vec![RecordType::Null; 4])]
+        fn column_count(#[case] fields: Vec<RecordType>) {
+            let length = fields.len();
+            let record = Record { id: 1, fields };
+
+            assert!(matches!(
+                record.into_map(&schema()),
+                Err(RecordError::ColumnCount { columns: 3, fields }) if fields == length
+            ));
+        }
+    }
+
+    mod schema_format {
+        use super::*;
+
+        #[rstest]
+        #[case(SchemaFormat::V1)]
+        #[case(SchemaFormat::V2)]
+        #[case(SchemaFormat::V3)]
+        #[case(SchemaFormat::V4)]
+        fn supported(#[case] schema_format: SchemaFormat) {
+            let record = Record::from_buf_with_format(1, &[3, 1, 0, 5], schema_format).unwrap();
+
+            assert_eq!(record.fields, [RecordType::I8(5), RecordType::Null]);
+        }
+
+        #[rstest]
+        #[case(SchemaFormat::V1, 8)]
+        #[case(SchemaFormat::V3, 9)]
+        fn integer_constants_unsupported(
+            #[case] schema_format: SchemaFormat,
+            #[case] serial_type: u8,
+        ) {
+            assert_eq!(
+                Record::from_buf_with_format(1, &[2, serial_type], schema_format).unwrap_err(),
+                RecordError::UnsupportedSerialType {
+                    serial_type: serial_type as i64,
+                    schema_format,
+                }
+            );
+        }
+
+        #[test]
+        fn integer_constants_supported() {
+            let record = Record::from_buf_with_format(1, &[3, 8, 9], SchemaFormat::V4).unwrap();
+
+            assert_eq!(record.fields, [RecordType::Zero, RecordType::One]);
+        }
+    }
+
+    mod iter_with_types {
+        use super::*;
+
+        #[test]
+        fn serial_types_match_storage() {
+            // Header: i24, i32, zero, one, 3 byte text, 2 byte blob.
+            let buf = [
+                7, 3, 4, 8, 9, 0x13, 0x10, // header
+                0x00, 0x00, 0x05, // i24
+                0x00, 0x00, 0x00, 0x05, // i32
+                b'a', b'b', b'c', // text
+                0xde, 0xad, // blob
+            ];
+
+            let fields = Record::iter_with_types(&buf)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(
+                fields
+                    .iter()
+                    .map(|(serial_type, _)| *serial_type)
+                    .collect::<Vec<_>>(),
+                [3, 4, 8, 9, 0x13, 0x10]
+            );
+            assert!(matches!(fields[0].1, RecordType::I24(_)));
+            assert!(matches!(fields[1].1, RecordType::I32(5)));
+
+            // Values are equal, despite being stored with different serial types.
+            assert_eq!(fields[0].1, fields[1].1);
+
+            assert_eq!(
+                fields
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .collect::<Vec<_>>(),
+                Record::from_buf(1, &buf).unwrap().fields
+            );
+        }
+
+        #[rstest]
+        #[case::header_length(&[5, 1], 1)]
+        #[case::reserved(&[3, 1, 10, 5], 2)]
+        #[case::trailing(&[2, 1, 5, 6], 2)]
+        fn stops_after_error(#[case] buf: &[u8], #[case] expected: usize) {
+            let fields = Record::iter_with_types(buf).collect::<Vec<_>>();
+
+            assert_eq!(fields.len(), expected);
+            assert!(fields.last().unwrap().is_err());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serialize {
+        use super::*;
+
+        #[test]
+        fn record() {
+            let record = Record {
+                id: 3,
+                fields: vec![
+                    RecordType::Null,
+                    RecordType::I8(-1),
+                    RecordType::I16(2),
+                    RecordType::I24(i24::new(3)),
+                    RecordType::I32(4),
+                    RecordType::I48(i48::new(5)),
+                    RecordType::I64(6),
+                    RecordType::F64(1.5),
+                    RecordType::Zero,
+                    RecordType::One,
+                    RecordType::Blob(vec![0x00, 0xff]),
+                    RecordType::String("hello".into()),
+                ],
+            };
+
+            assert_eq!(
+                serde_json::to_string(&record).unwrap(),
+                r#"{"id":3,"fields":[null,-1,2,3,4,5,6,1.5,0,1,[0,255],"hello"]}"#
+            );
         }
     }
 }
@@ -0,0 +1,282 @@
+use std::cmp::Ordering;
+
+use lib_parse::common::token::Number;
+
+use crate::{
+    command::{Affinity, Literal},
+    record::{RecordType, compare_float, compare_integer_float},
+};
+
+/// A value used whilst evaluating a query, independent of whether it was read from a record or
+/// written as a literal in the query.
+#[allow(unused)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+#[allow(unused)]
+impl Value {
+    /// Compare two values following SQLite's rules for comparison operators. Comparing with `NULL`
+    /// produces an unknown result (`None`).
+    ///
+    /// Otherwise numeric values are less than text, which is less than blobs. Integers and reals are
+    /// compared numerically, text is compared by bytes, and blobs are compared with `memcmp`.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        /// Rank of each storage class in the ordering.
+        fn rank(value: &Value) -> u8 {
+            match value {
+                Value::Null => 0,
+                Value::Integer(_) | Value::Real(_) => 1,
+                Value::Text(_) => 2,
+                Value::Blob(_) => 3,
+            }
+        }
+
+        Some(match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => return None,
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Real(a), Value::Real(b)) => compare_float(*a, *b),
+            (Value::Integer(a), Value::Real(b)) => compare_integer_float(*a, *b),
+            (Value::Real(a), Value::Integer(b)) => compare_integer_float(*b, *a).reverse(),
+            (Value::Text(a), Value::Text(b)) => a.as_bytes().cmp(b.as_bytes()),
+            (Value::Blob(a), Value::Blob(b)) => a.cmp(b),
+            (a, b) => rank(a).cmp(&rank(b)),
+        })
+    }
+
+    /// Determine if two values are equal with [`Self::compare`]. Comparing with `NULL` produces an
+    /// unknown result (`None`).
+    pub fn equals(&self, other: &Value) -> Option<bool> {
+        self.compare(other).map(Ordering::is_eq)
+    }
+
+    /// Apply numeric affinity to the value, converting text which is a well-formed integer or real
+    /// into the corresponding numeric value. Any other value is unchanged.
+    ///
+    /// SQLite applies this to a text operand when it is compared against a column with numeric
+    /// affinity, so `'5' = 5` holds for an `INTEGER` column.
+    pub fn numeric_affinity(self) -> Value {
+        let Value::Text(text) = &self else {
+            return self;
+        };
+
+        let text = text.trim();
+
+        // Rust accepts `inf` and `nan` as floats, which SQLite doesn't.
+        if text.is_empty() || text.contains(|c: char| c.is_alphabetic() && c != 'e' && c != 'E') {
+            return self;
+        }
+
+        if let Ok(i) = text.parse() {
+            Value::Integer(i)
+        } else if let Ok(n) = text.parse() {
+            Value::Real(n)
+        } else {
+            self
+        }
+    }
+
+    /// Apply text affinity to the value, rendering numeric values as text. Any other value is
+    /// unchanged.
+    ///
+    /// SQLite applies this to a numeric operand when it is compared against a column with text
+    /// affinity, so `'5' = 5` holds for a `TEXT` column.
+    pub fn text_affinity(self) -> Value {
+        match self {
+            Value::Integer(i) => Value::Text(i.to_string()),
+            // Debug formatting retains the decimal point for whole numbers, as SQLite does.
+            Value::Real(n) => Value::Text(format!("{n:?}")),
+            value => value,
+        }
+    }
+
+    /// Apply the affinity of a column to the value, as SQLite does to an operand which is compared
+    /// against the column.
+    pub fn apply_affinity(self, affinity: Affinity) -> Value {
+        match affinity {
+            Affinity::Integer | Affinity::Real | Affinity::Numeric => self.numeric_affinity(),
+            Affinity::Text => self.text_affinity(),
+            Affinity::Blob => self,
+        }
+    }
+}
+
+impl From<RecordType> for Value {
+    fn from(value: RecordType) -> Self {
+        match value {
+            RecordType::Null => Value::Null,
+            RecordType::F64(n) => Value::Real(n),
+            RecordType::String(s) => Value::Text(s),
+            RecordType::Blob(blob) => Value::Blob(blob),
+            integer => Value::Integer(integer.integer().expect("remaining types are integers")),
+        }
+    }
+}
+
+impl From<Literal> for Value {
+    fn from(literal: Literal) -> Self {
+        match literal {
+            Literal::Null(_) => Value::Null,
+            Literal::Number(Number::Integer(i)) => Value::Integer(i),
+            Literal::Number(Number::Float(n)) => Value::Real(n),
+            Literal::String(s) => Value::Text(s.to_string()),
+            Literal::Blob(blob) => Value::Blob(blob.to_vec()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    use crate::command::parse_command;
+
+    fn literal(s: &str) -> Value {
+        parse_command::<Literal>(s).into()
+    }
+
+    mod from {
+        use super::*;
+
+        #[rstest]
+        #[case("null", Value::Null)]
+        #[case("5", Value::Integer(5))]
+        #[case("1.5", Value::Real(1.5))]
+        #[case("'andy'", Value::Text("andy".to_string()))]
+        #[case("x'00ff'", Value::Blob(vec![0x00, 0xff]))]
+        fn literal_value(#[case] s: &str, #[case] expected: Value) {
+            assert_eq!(literal(s), expected);
+        }
+
+        #[rstest]
+        #[case(RecordType::Null, Value::Null)]
+        #[case(RecordType::I8(-5), Value::Integer(-5))]
+        #[case(RecordType::I32(70000), Value::Integer(70000))]
+        #[case(RecordType::Zero, Value::Integer(0))]
+        #[case(RecordType::One, Value::Integer(1))]
+        #[case(RecordType::F64(2.5), Value::Real(2.5))]
+        #[case(RecordType::String("a".to_string()), Value::Text("a".to_string()))]
+        #[case(RecordType::Blob(vec![1, 2]), Value::Blob(vec![1, 2]))]
+        fn record_value(#[case] record: RecordType, #[case] expected: Value) {
+            assert_eq!(Value::from(record), expected);
+        }
+    }
+
+    mod compare {
+        use super::*;
+
+        #[test]
+        fn integer_literal_against_record() {
+            let value = Value::from(RecordType::I32(5));
+
+            assert_eq!(literal("5").compare(&value), Some(Ordering::Equal));
+            assert_eq!(literal("5.0").compare(&value), Some(Ordering::Equal));
+            assert_eq!(literal("4").compare(&value), Some(Ordering::Less));
+            assert_eq!(literal("5.5").compare(&value), Some(Ordering::Greater));
+        }
+
+        #[rstest]
+        #[case::equal("x'00ff'", "x'00FF'", Ordering::Equal)]
+        #[case::prefix_first("x'00'", "x'0000'", Ordering::Less)]
+        #[case::empty_first("x''", "x'00'", Ordering::Less)]
+        #[case::unsigned_bytes("x'ff'", "x'7f00'", Ordering::Greater)]
+        fn blob_literals(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+            assert_eq!(literal(a).compare(&literal(b)), Some(expected));
+        }
+
+        #[test]
+        fn text_against_blob() {
+            let text = Value::Text("a".to_string());
+            let blob = Value::Blob(b"a".to_vec());
+
+            assert_eq!(text.equals(&blob), Some(false));
+            assert_eq!(text.compare(&blob), Some(Ordering::Less));
+            assert_eq!(blob.compare(&text), Some(Ordering::Greater));
+        }
+
+        #[rstest]
+        #[case(Value::Integer(100), Value::Text("1".to_string()), Ordering::Less)]
+        #[case(Value::Text("b".to_string()), Value::Text("a".to_string()), Ordering::Greater)]
+        #[case(Value::Blob(vec![1]), Value::Blob(vec![1, 0]), Ordering::Less)]
+        #[case(Value::Real(f64::MAX), Value::Integer(i64::MAX), Ordering::Greater)]
+        fn storage_class(#[case] a: Value, #[case] b: Value, #[case] expected: Ordering) {
+            assert_eq!(a.compare(&b), Some(expected));
+        }
+
+        #[rstest]
+        #[case(Value::Null, Value::Null)]
+        #[case(Value::Null, Value::Integer(1))]
+        #[case(Value::Text("a".to_string()), Value::Null)]
+        fn null_is_unknown(#[case] a: Value, #[case] b: Value) {
+            assert_eq!(a.compare(&b), None);
+            assert_eq!(a.equals(&b), None);
+        }
+    }
+
+    mod numeric_affinity {
+        use super::*;
+
+        #[rstest]
+        #[case("5", Value::Integer(5))]
+        #[case(" -12 ", Value::Integer(-12))]
+        #[case("1.5", Value::Real(1.5))]
+        #[case("1e3", Value::Real(1000.0))]
+        #[case("abc", Value::Text("abc".to_string()))]
+        #[case("inf", Value::Text("inf".to_string()))]
+        #[case("", Value::Text("".to_string()))]
+        fn text(#[case] s: &str, #[case] expected: Value) {
+            assert_eq!(Value::Text(s.to_string()).numeric_affinity(), expected);
+        }
+
+        #[test]
+        fn compares_numerically() {
+            let text = Value::Text("5".to_string()).numeric_affinity();
+
+            assert_eq!(text.equals(&Value::Integer(5)), Some(true));
+        }
+    }
+
+    mod text_affinity {
+        use super::*;
+
+        #[rstest]
+        #[case(Value::Integer(5), Value::Text("5".to_string()))]
+        #[case(Value::Integer(-12), Value::Text("-12".to_string()))]
+        #[case(Value::Real(1.5), Value::Text("1.5".to_string()))]
+        #[case(Value::Real(5.0), Value::Text("5.0".to_string()))]
+        #[case(Value::Text("abc".to_string()), Value::Text("abc".to_string()))]
+        #[case(Value::Blob(vec![1]), Value::Blob(vec![1]))]
+        #[case(Value::Null, Value::Null)]
+        fn convert(#[case] value: Value, #[case] expected: Value) {
+            assert_eq!(value.text_affinity(), expected);
+        }
+    }
+
+    mod apply_affinity {
+        use super::*;
+
+        #[rstest]
+        #[case(Affinity::Integer, literal("'5'"), Value::Integer(5))]
+        #[case(Affinity::Real, literal("'1.5'"), Value::Real(1.5))]
+        #[case(Affinity::Numeric, literal("'5'"), Value::Integer(5))]
+        #[case(Affinity::Integer, literal("'abc'"), Value::Text("abc".to_string()))]
+        #[case(Affinity::Text, literal("5"), Value::Text("5".to_string()))]
+        #[case(Affinity::Text, literal("'5'"), Value::Text("5".to_string()))]
+        #[case(Affinity::Blob, literal("5"), Value::Integer(5))]
+        #[case(Affinity::Blob, literal("'5'"), Value::Text("5".to_string()))]
+        fn literal_value(
+            #[case] affinity: Affinity,
+            #[case] value: Value,
+            #[case] expected: Value,
+        ) {
+            assert_eq!(value.apply_affinity(affinity), expected);
+        }
+    }
+}